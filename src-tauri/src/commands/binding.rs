@@ -0,0 +1,391 @@
+use crate::utils::escape_literal;
+use base64::Engine;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+use sqlx::{Row, ValueRef};
+use std::collections::HashMap;
+
+/// How values of a column are parsed before binding
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnKind {
+    Integer,
+    Float,
+    Decimal,
+    Boolean,
+    Date,
+    Time,
+    DateTime,
+    Binary,
+    Json,
+    Text,
+}
+
+/// A cell value converted to the type of its column, ready to bind
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BindValue {
+    Null,
+    Text(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+    Bytes(Vec<u8>),
+}
+
+impl BindValue {
+    /// The value as a SQL literal, for statements shown to the user or written to scripts
+    pub(crate) fn to_sql_literal(&self, db_type: &str) -> String {
+        match self {
+            BindValue::Null => "NULL".to_string(),
+            BindValue::Text(s) => escape_literal(s, db_type),
+            BindValue::Int(n) => n.to_string(),
+            BindValue::Float(f) if f.is_finite() => f.to_string(),
+            BindValue::Float(f) => escape_literal(&f.to_string(), db_type),
+            BindValue::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            BindValue::Date(d) => escape_literal(&d.to_string(), db_type),
+            BindValue::Time(t) => escape_literal(&t.to_string(), db_type),
+            BindValue::DateTime(dt) => escape_literal(&dt.to_string(), db_type),
+            BindValue::Bytes(bytes) => {
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                if db_type == "postgres" { format!("'\\x{}'", hex) } else { format!("X'{}'", hex) }
+            }
+        }
+    }
+
+    /// The value as text `bind_value` parses back (binary as `0x` hex); None for NULL
+    pub(crate) fn to_param(&self) -> Option<String> {
+        match self {
+            BindValue::Null => None,
+            BindValue::Text(s) => Some(s.clone()),
+            BindValue::Int(n) => Some(n.to_string()),
+            BindValue::Float(f) => Some(f.to_string()),
+            // 1/0 is accepted by boolean, TINYINT(1) and SQLite integer columns alike
+            BindValue::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+            BindValue::Date(d) => Some(d.to_string()),
+            BindValue::Time(t) => Some(t.to_string()),
+            BindValue::DateTime(dt) => Some(dt.to_string()),
+            BindValue::Bytes(bytes) => Some(format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>())),
+        }
+    }
+}
+
+/// Classify a column type: `udt_name` on Postgres, `COLUMN_TYPE` on MySQL, the declared type on SQLite
+fn column_kind(db_type: &str, type_name: &str) -> ColumnKind {
+    let lower = type_name.trim().to_ascii_lowercase();
+    let base = lower.split(['(', ' ']).next().unwrap_or("");
+
+    match db_type {
+        "postgres" => match base {
+            "int2" | "int4" | "int8" => ColumnKind::Integer,
+            "float4" | "float8" => ColumnKind::Float,
+            "numeric" => ColumnKind::Decimal,
+            "bool" => ColumnKind::Boolean,
+            "date" => ColumnKind::Date,
+            "time" => ColumnKind::Time,
+            "timestamp" => ColumnKind::DateTime,
+            "bytea" => ColumnKind::Binary,
+            "json" | "jsonb" => ColumnKind::Json,
+            _ => ColumnKind::Text,
+        },
+        "mysql" => match base {
+            "tinyint" if lower.starts_with("tinyint(1)") => ColumnKind::Boolean,
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "year" => ColumnKind::Integer,
+            "float" | "double" | "real" => ColumnKind::Float,
+            "decimal" | "numeric" => ColumnKind::Decimal,
+            "date" => ColumnKind::Date,
+            "time" => ColumnKind::Time,
+            "datetime" | "timestamp" => ColumnKind::DateTime,
+            "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => ColumnKind::Binary,
+            "json" => ColumnKind::Json,
+            _ => ColumnKind::Text,
+        },
+        // SQLite type affinity rules; dates and decimals are stored as given, so they stay text
+        _ => {
+            if lower.contains("bool") {
+                ColumnKind::Boolean
+            } else if lower.contains("int") {
+                ColumnKind::Integer
+            } else if lower.contains("char") || lower.contains("clob") || lower.contains("text") {
+                ColumnKind::Text
+            } else if lower.contains("blob") {
+                ColumnKind::Binary
+            } else if lower.contains("real") || lower.contains("floa") || lower.contains("doub") {
+                ColumnKind::Float
+            } else {
+                ColumnKind::Text
+            }
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "t" | "yes" | "y" | "on" => Some(true),
+        "0" | "false" | "f" | "no" | "n" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S%.f", "%H:%M"].iter().find_map(|f| NaiveTime::parse_from_str(value, f).ok())
+}
+
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.strip_suffix(" UTC").unwrap_or(value);
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+        .or_else(|| parse_date(value).and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+/// Binary input: `0x` or `\x` prefixed hex, `base64:` prefixed base64, anything else as its UTF-8 bytes
+fn decode_binary(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(hex) = value.strip_prefix("0x").or_else(|| value.strip_prefix("\\x")) {
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            return Err(format!("'{}' is not valid hex", value));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| format!("'{}' is not valid hex", value))
+    } else if let Some(encoded) = value.strip_prefix("base64:") {
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| format!("invalid base64: {}", e))
+    } else {
+        Ok(value.as_bytes().to_vec())
+    }
+}
+
+/// Convert a value for a column of `type_name` (None when the type is unknown, bound as text)
+pub(crate) fn bind_value(db_type: &str, type_name: Option<&str>, value: Option<&str>) -> Result<BindValue, String> {
+    let Some(value) = value else { return Ok(BindValue::Null) };
+    let kind = type_name.map(|t| column_kind(db_type, t)).unwrap_or(ColumnKind::Text);
+    let trimmed = value.trim();
+    let text = || BindValue::Text(value.to_string());
+
+    let parsed = match kind {
+        ColumnKind::Text => Ok(text()),
+        ColumnKind::Integer => match trimmed.parse::<i64>() {
+            Ok(n) => Ok(BindValue::Int(n)),
+            // Beyond i64 (BIGINT UNSIGNED): the database parses the digits exactly
+            Err(_) if trimmed.parse::<u64>().is_ok() => Ok(BindValue::Text(trimmed.to_string())),
+            Err(_) => Err(format!("'{}' is not a valid integer", value)),
+        },
+        ColumnKind::Float => trimmed
+            .parse::<f64>()
+            .map(BindValue::Float)
+            .map_err(|_| format!("'{}' is not a valid number", value)),
+        // Sent as text so no precision is lost; the database converts it exactly
+        ColumnKind::Decimal => match trimmed.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(BindValue::Text(trimmed.to_string())),
+            _ => Err(format!("'{}' is not a valid number", value)),
+        },
+        ColumnKind::Boolean => parse_bool(trimmed)
+            .map(BindValue::Bool)
+            .ok_or_else(|| format!("'{}' is not a valid boolean", value)),
+        // Other formats (zero dates, 'infinity', MySQL TIME beyond 24h) are left to the database
+        ColumnKind::Date => Ok(parse_date(trimmed).map(BindValue::Date).unwrap_or_else(text)),
+        ColumnKind::Time => Ok(parse_time(trimmed).map(BindValue::Time).unwrap_or_else(text)),
+        ColumnKind::DateTime => Ok(parse_datetime(trimmed).map(BindValue::DateTime).unwrap_or_else(text)),
+        ColumnKind::Binary => decode_binary(value).map(BindValue::Bytes),
+        ColumnKind::Json => serde_json::from_str::<serde_json::Value>(value)
+            .map(|_| text())
+            .map_err(|e| format!("not valid JSON: {}", e)),
+    };
+
+    match parsed {
+        // SQLite columns accept any value, so whatever does not parse is kept as text
+        Err(_) if db_type == "sqlite" => Ok(text()),
+        other => other,
+    }
+}
+
+/// The type of `column` in a table's column types, matched case-insensitively as a fallback
+pub(crate) fn column_type<'a>(column_types: Option<&'a HashMap<String, String>>, column: &str) -> Option<&'a str> {
+    let types = column_types?;
+    types
+        .get(column)
+        .or_else(|| types.iter().find(|(name, _)| name.eq_ignore_ascii_case(column)).map(|(_, t)| t))
+        .map(String::as_str)
+}
+
+/// Convert `(column, value)` arguments using the table's column types
+pub(crate) fn bind_args(
+    db_type: &str,
+    args: &[(String, Option<String>)],
+    column_types: Option<&HashMap<String, String>>,
+) -> Result<Vec<BindValue>, String> {
+    args.iter()
+        .map(|(column, value)| {
+            bind_value(db_type, column_type(column_types, column), value.as_deref())
+                .map_err(|e| format!("Invalid value for column {}: {}", column, e))
+        })
+        .collect()
+}
+
+/// Bind converted values, in order, to a `sqlx::query` / `query_as` / `query_scalar`
+macro_rules! bind_values {
+    ($query:expr, $values:expr) => {{
+        use $crate::commands::binding::BindValue;
+        let mut query = $query;
+        for value in $values {
+            query = match value {
+                BindValue::Null => query.bind(None::<String>),
+                BindValue::Text(v) => query.bind(v),
+                BindValue::Int(v) => query.bind(v),
+                BindValue::Float(v) => query.bind(v),
+                BindValue::Bool(v) => query.bind(v),
+                BindValue::Date(v) => query.bind(v),
+                BindValue::Time(v) => query.bind(v),
+                BindValue::DateTime(v) => query.bind(v),
+                BindValue::Bytes(v) => query.bind(v),
+            };
+        }
+        query
+    }};
+}
+pub(crate) use bind_values;
+
+// Typed row conversion for values that are written back (before-images, row comparison).
+// Unlike the export converters binary stays bytes; a type that cannot be read is None.
+macro_rules! row_bind_values {
+    ($row:expr) => {{
+        let row = &$row;
+        (0..row.columns().len())
+            .map(|i| {
+                if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+                    Some(BindValue::Null)
+                } else if let Ok(v) = row.try_get::<String, _>(i) {
+                    Some(BindValue::Text(v))
+                } else if let Ok(v) = row.try_get::<i64, _>(i) {
+                    Some(BindValue::Int(v))
+                } else if let Ok(v) = row.try_get::<i32, _>(i) {
+                    Some(BindValue::Int(v.into()))
+                } else if let Ok(v) = row.try_get::<i16, _>(i) {
+                    Some(BindValue::Int(v.into()))
+                } else if let Ok(v) = row.try_get::<f64, _>(i) {
+                    Some(BindValue::Float(v))
+                } else if let Ok(v) = row.try_get::<f32, _>(i) {
+                    Some(BindValue::Float(v.into()))
+                } else if let Ok(v) = row.try_get::<bool, _>(i) {
+                    Some(BindValue::Bool(v))
+                } else if let Ok(v) = row.try_get::<Decimal, _>(i) {
+                    Some(BindValue::Text(v.to_string()))
+                } else if let Ok(v) = row.try_get::<NaiveDate, _>(i) {
+                    Some(BindValue::Date(v))
+                } else if let Ok(v) = row.try_get::<NaiveDateTime, _>(i) {
+                    Some(BindValue::DateTime(v))
+                } else if let Ok(v) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
+                    Some(BindValue::Text(v.to_rfc3339()))
+                } else if let Ok(v) = row.try_get::<NaiveTime, _>(i) {
+                    Some(BindValue::Time(v))
+                } else if let Ok(v) = row.try_get::<serde_json::Value, _>(i) {
+                    Some(BindValue::Text(v.to_string()))
+                } else {
+                    row.try_get::<Vec<u8>, _>(i).ok().map(BindValue::Bytes)
+                }
+            })
+            .collect::<Vec<Option<BindValue>>>()
+    }};
+}
+
+macro_rules! row_bind_values_sqlite {
+    ($row:expr) => {{
+        let row = &$row;
+        (0..row.columns().len())
+            .map(|i| {
+                if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+                    Some(BindValue::Null)
+                } else if let Ok(v) = row.try_get::<String, _>(i) {
+                    Some(BindValue::Text(v))
+                } else if let Ok(v) = row.try_get::<i64, _>(i) {
+                    Some(BindValue::Int(v))
+                } else if let Ok(v) = row.try_get::<f64, _>(i) {
+                    Some(BindValue::Float(v))
+                } else {
+                    row.try_get::<Vec<u8>, _>(i).ok().map(BindValue::Bytes)
+                }
+            })
+            .collect::<Vec<Option<BindValue>>>()
+    }};
+}
+
+pub(crate) fn mysql_row_bind_values(row: &sqlx::mysql::MySqlRow) -> Vec<Option<BindValue>> {
+    row_bind_values!(row)
+}
+
+pub(crate) fn postgres_row_bind_values(row: &sqlx::postgres::PgRow) -> Vec<Option<BindValue>> {
+    row_bind_values!(row)
+}
+
+pub(crate) fn sqlite_row_bind_values(row: &sqlx::sqlite::SqliteRow) -> Vec<Option<BindValue>> {
+    row_bind_values_sqlite!(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_kinds() {
+        assert_eq!(column_kind("mysql", "tinyint(1)"), ColumnKind::Boolean);
+        assert_eq!(column_kind("mysql", "tinyint(4) unsigned"), ColumnKind::Integer);
+        assert_eq!(column_kind("mysql", "decimal(10,2)"), ColumnKind::Decimal);
+        assert_eq!(column_kind("postgres", "int4"), ColumnKind::Integer);
+        assert_eq!(column_kind("postgres", "bytea"), ColumnKind::Binary);
+        assert_eq!(column_kind("postgres", "varchar"), ColumnKind::Text);
+        // SQLite affinities, in the order the rules apply
+        assert_eq!(column_kind("sqlite", "BIGINT"), ColumnKind::Integer);
+        assert_eq!(column_kind("sqlite", "VARCHAR(20)"), ColumnKind::Text);
+        assert_eq!(column_kind("sqlite", "BLOB"), ColumnKind::Binary);
+        assert_eq!(column_kind("sqlite", "DOUBLE PRECISION"), ColumnKind::Float);
+        // "INT" is checked first, so this one gets integer affinity as in SQLite itself
+        assert_eq!(column_kind("sqlite", "FLOATING POINT"), ColumnKind::Integer);
+        assert_eq!(column_kind("sqlite", "BOOLEAN"), ColumnKind::Boolean);
+        assert_eq!(column_kind("sqlite", "NUMERIC"), ColumnKind::Text);
+    }
+
+    #[test]
+    fn binary_input_formats() {
+        assert_eq!(decode_binary("0x0aFF"), Ok(vec![0x0a, 0xff]));
+        assert_eq!(decode_binary("\\x0aff"), Ok(vec![0x0a, 0xff]));
+        assert_eq!(decode_binary("base64:AAE="), Ok(vec![0, 1]));
+        assert_eq!(decode_binary("ab"), Ok(b"ab".to_vec()));
+        assert!(decode_binary("0xabc").is_err());
+        assert!(decode_binary("0xzz").is_err());
+        assert!(decode_binary("base64:***").is_err());
+    }
+
+    #[test]
+    fn invalid_values_fail_except_on_sqlite() {
+        assert!(bind_value("mysql", Some("int"), Some("abc")).is_err());
+        assert!(bind_value("postgres", Some("bool"), Some("maybe")).is_err());
+        assert!(bind_value("postgres", Some("jsonb"), Some("{")).is_err());
+        assert!(bind_value("postgres", Some("bytea"), Some("0x1")).is_err());
+        assert_eq!(bind_value("sqlite", Some("INTEGER"), Some("abc")), Ok(BindValue::Text("abc".to_string())));
+        assert_eq!(bind_value("sqlite", Some("BLOB"), Some("0x1")), Ok(BindValue::Text("0x1".to_string())));
+        assert_eq!(bind_value("mysql", Some("tinyint(1)"), Some("true")), Ok(BindValue::Bool(true)));
+        assert_eq!(bind_value("mysql", None, Some("abc")), Ok(BindValue::Text("abc".to_string())));
+        assert_eq!(bind_value("mysql", Some("int"), None), Ok(BindValue::Null));
+    }
+
+    #[test]
+    fn unsigned_integers_beyond_i64() {
+        let max = u64::MAX.to_string();
+        assert_eq!(
+            bind_value("mysql", Some("bigint unsigned"), Some(&max)),
+            Ok(BindValue::Text(max.clone()))
+        );
+        assert_eq!(bind_value("mysql", Some("bigint"), Some(" 42 ")), Ok(BindValue::Int(42)));
+        assert!(bind_value("mysql", Some("bigint unsigned"), Some("18446744073709551616")).is_err());
+        assert!(bind_value("mysql", Some("bigint unsigned"), Some("-1.5")).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+use crate::commands::binding::{bind_value, BindValue};
+use crate::commands::crud::{apply_changes, ApplyBatchOptions, BatchChange, CellUpdate, RowIdentifier};
+use crate::db::{AppState, PoolWrapper, Session};
+use serde::Serialize;
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+/// Applied batches kept per connection; older history is pruned
+const MAX_BATCHES_PER_CONNECTION: i64 = 200;
+
+#[derive(Debug, Serialize)]
+pub struct AppliedBatch {
+    pub id: i64,
+    pub database_name: Option<String>,
+    pub tables: Vec<String>,
+    pub changes: Vec<BatchChange>,
+    pub can_revert: bool,
+    pub reverts_batch_id: Option<i64>, // Set when this batch was itself an undo
+    pub applied_at: String,
+    pub reverted_at: Option<String>,
+}
+
+/// Before-image value as it will be bound when the inverse runs, or None when binding it back
+/// to its column would not give the captured value (the batch then cannot be reverted)
+pub(crate) fn value_to_param(db_type: &str, type_name: Option<&str>, value: &BindValue) -> Option<Option<String>> {
+    let param = value.to_param();
+    let bound = bind_value(db_type, type_name, param.as_deref()).ok()?;
+    let same_kind = matches!(bound, BindValue::Bytes(_)) == matches!(value, BindValue::Bytes(_));
+    (same_kind && bound.to_param() == param).then_some(param)
+}
+
+/// Columns the database computes and won't take a value for on insert: generated columns, and
+/// `GENERATED ALWAYS` identities on Postgres. They are left out of before-images, so re-inserting
+/// a deleted row lets the database fill them in again. Empty if the table can't be read.
+pub(crate) async fn generated_columns(pool: &PoolWrapper, table_name: &str) -> HashSet<String> {
+    let rows = match pool {
+        PoolWrapper::Postgres(p) => {
+            let (schema, table) = match table_name.split_once('.') {
+                Some((s, t)) => (s.trim_matches('"'), t.trim_matches('"')),
+                None => ("public", table_name.trim_matches('"')),
+            };
+            sqlx::query_scalar(
+                "SELECT column_name::TEXT FROM information_schema.columns
+                 WHERE table_schema = $1 AND table_name = $2 AND (is_generated = 'ALWAYS' OR identity_generation = 'ALWAYS')",
+            )
+            .bind(schema)
+            .bind(table)
+            .fetch_all(p)
+            .await
+        }
+        PoolWrapper::Mysql(p) => {
+            let (schema, table) = match table_name.split_once('.') {
+                Some((s, t)) => (Some(s.trim_matches('`')), t.trim_matches('`')),
+                None => (None, table_name.trim_matches('`')),
+            };
+            // DEFAULT_GENERATED only marks expression defaults, which take values
+            sqlx::query_scalar(
+                "SELECT CAST(COLUMN_NAME AS CHAR) FROM information_schema.COLUMNS
+                 WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ? AND EXTRA IN ('VIRTUAL GENERATED', 'STORED GENERATED')",
+            )
+            .bind(schema)
+            .bind(table)
+            .fetch_all(p)
+            .await
+        }
+        PoolWrapper::Sqlite(p) => {
+            sqlx::query_scalar("SELECT name FROM pragma_table_xinfo(?) WHERE hidden IN (2, 3)")
+                .bind(table_name.trim_matches('"'))
+                .fetch_all(p)
+                .await
+        }
+    };
+    rows.unwrap_or_default().into_iter().collect()
+}
+
+/// Operations that undo `change`, given the before-image of the rows it touched.
+/// Returns None when the change cannot be inverted (an INSERT with no values to find the row by).
+pub(crate) fn inverse_changes(change: &BatchChange, before: &[HashMap<String, Option<String>>]) -> Option<Vec<BatchChange>> {
+    match change.operation.as_str() {
+        "UPDATE" => {
+            let (identifier, updates) = (change.identifier.as_ref()?, change.updates.as_ref()?);
+            let new_values: HashMap<&str, &Option<String>> = updates.iter().map(|u| (u.column.trim(), &u.value)).collect();
+
+            Some(
+                before
+                    .iter()
+                    .map(|row| {
+                        // Find the row by its post-update values
+                        let values = identifier
+                            .columns
+                            .iter()
+                            .zip(&identifier.values)
+                            .map(|(col, value)| match new_values.get(col.trim()) {
+                                Some(new_value) => (*new_value).clone(),
+                                None => row.get(col.trim()).cloned().unwrap_or_else(|| value.clone()),
+                            })
+                            .collect();
+
+                        BatchChange {
+                            operation: "UPDATE".to_string(),
+                            table_name: change.table_name.clone(),
+                            identifier: Some(RowIdentifier { columns: identifier.columns.clone(), values }),
+                            updates: Some(
+                                updates
+                                    .iter()
+                                    .map(|u| CellUpdate { column: u.column.clone(), value: row.get(u.column.trim()).cloned().flatten() })
+                                    .collect(),
+                            ),
+                            insert_values: None,
+                            original_values: Some(updates.iter().map(|u| (u.column.trim().to_string(), u.value.clone())).collect()),
+                            expected_rows: None,
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        "DELETE" => Some(
+            before
+                .iter()
+                .map(|row| BatchChange {
+                    operation: "INSERT".to_string(),
+                    table_name: change.table_name.clone(),
+                    identifier: None,
+                    updates: None,
+                    insert_values: Some(row.clone()),
+                    original_values: None,
+                    expected_rows: None,
+                })
+                .collect(),
+        ),
+        "INSERT" => {
+            // NULL values are usually defaults filled in by the database (e.g. auto-increment keys)
+            let mut provided: Vec<(&String, &Option<String>)> =
+                change.insert_values.as_ref()?.iter().filter(|(_, v)| v.is_some()).collect();
+            if provided.is_empty() {
+                return None;
+            }
+            provided.sort_by(|a, b| a.0.cmp(b.0));
+
+            Some(vec![BatchChange {
+                operation: "DELETE".to_string(),
+                table_name: change.table_name.clone(),
+                identifier: Some(RowIdentifier {
+                    columns: provided.iter().map(|(c, _)| (*c).clone()).collect(),
+                    values: provided.iter().map(|(_, v)| (*v).clone()).collect(),
+                }),
+                updates: None,
+                insert_values: None,
+                original_values: None,
+                expected_rows: None,
+            }])
+        }
+        _ => None,
+    }
+}
+
+/// Store an applied batch and its inverse (None when it cannot be reverted)
+pub(crate) async fn record_batch(
+    db: &Pool<Sqlite>,
+    session: &Session,
+    changes: &[&BatchChange],
+    inverse: &Option<Vec<BatchChange>>,
+    reverts_batch_id: Option<i64>,
+) -> Result<(), String> {
+    let changes_json = serde_json::to_string(changes).map_err(|e| format!("Failed to serialize batch: {}", e))?;
+    let inverse_json = match inverse {
+        Some(ops) => Some(serde_json::to_string(ops).map_err(|e| format!("Failed to serialize batch: {}", e))?),
+        None => None,
+    };
+
+    sqlx::query(
+        "INSERT INTO applied_batches (connection_id, database_name, changes_json, inverse_json, reverts_batch_id)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(session.connection_id)
+    .bind(&session.database_name)
+    .bind(&changes_json)
+    .bind(&inverse_json)
+    .bind(reverts_batch_id)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Failed to record batch: {}", e))?;
+
+    sqlx::query(
+        "DELETE FROM applied_batches WHERE connection_id = ? AND id NOT IN
+         (SELECT id FROM applied_batches WHERE connection_id = ? ORDER BY id DESC LIMIT ?)",
+    )
+    .bind(session.connection_id)
+    .bind(session.connection_id)
+    .bind(MAX_BATCHES_PER_CONNECTION)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Failed to prune batch history: {}", e))?;
+
+    Ok(())
+}
+
+/// List batches applied through this connection, newest first
+#[tauri::command]
+pub async fn list_applied_batches(
+    state: State<'_, AppState>,
+    connection_string: String,
+    limit: Option<i64>,
+) -> Result<Vec<AppliedBatch>, String> {
+    let session = state
+        .sessions
+        .get_session(&connection_string)
+        .ok_or("Batch history requires a saved connection")?;
+
+    let rows = sqlx::query(
+        "SELECT id, database_name, changes_json, inverse_json IS NOT NULL AS has_inverse, reverts_batch_id,
+                CAST(applied_at AS TEXT) AS applied_at, CAST(reverted_at AS TEXT) AS reverted_at
+         FROM applied_batches WHERE connection_id = ? ORDER BY id DESC LIMIT ?",
+    )
+    .bind(session.connection_id)
+    .bind(limit.unwrap_or(50))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| format!("Failed to load batch history: {}", e))?;
+
+    rows.iter()
+        .map(|row| {
+            let changes: Vec<BatchChange> = serde_json::from_str(&row.get::<String, _>("changes_json"))
+                .map_err(|e| format!("Failed to parse batch: {}", e))?;
+            let mut tables: Vec<String> = changes.iter().map(|c| c.table_name.clone()).collect();
+            tables.sort();
+            tables.dedup();
+            let reverted_at: Option<String> = row.get("reverted_at");
+
+            Ok(AppliedBatch {
+                id: row.get("id"),
+                database_name: row.get("database_name"),
+                tables,
+                changes,
+                can_revert: row.get::<bool, _>("has_inverse") && reverted_at.is_none(),
+                reverts_batch_id: row.get("reverts_batch_id"),
+                applied_at: row.get("applied_at"),
+                reverted_at,
+            })
+        })
+        .collect()
+}
+
+/// Undo an applied batch by running its inverse in one transaction. Rows changed since the
+/// batch was applied are reported as conflicts and nothing is reverted.
+#[tauri::command]
+pub async fn revert_batch(
+    state: State<'_, AppState>,
+    connection_string: String,
+    batch_id: i64,
+) -> Result<u64, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
+    let session = state
+        .sessions
+        .get_session(&connection_string)
+        .ok_or("Batch history requires a saved connection")?;
+
+    let row = sqlx::query(
+        "SELECT database_name, inverse_json, reverted_at IS NOT NULL AS reverted
+         FROM applied_batches WHERE id = ? AND connection_id = ?",
+    )
+    .bind(batch_id)
+    .bind(session.connection_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| format!("Failed to load batch: {}", e))?
+    .ok_or_else(|| format!("Batch {} not found", batch_id))?;
+
+    if row.get::<bool, _>("reverted") {
+        return Err(format!("Batch {} has already been reverted", batch_id));
+    }
+    let database_name: Option<String> = row.get("database_name");
+    if database_name != session.database_name {
+        return Err(format!(
+            "Batch {} was applied to database '{}'; open that database to revert it",
+            batch_id,
+            database_name.unwrap_or_default()
+        ));
+    }
+    let inverse: Vec<BatchChange> = match row.get::<Option<String>, _>("inverse_json") {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to parse batch: {}", e))?,
+        None => return Err(format!("Batch {} cannot be reverted", batch_id)),
+    };
+
+    claim_revert(&state.db, batch_id, session.connection_id).await?;
+    let options = ApplyBatchOptions { check_conflicts: true, ..Default::default() };
+    let applied = apply_changes(&state, &connection_string, inverse, &options, Some(batch_id)).await;
+    if applied.is_err() {
+        // Nothing was reverted, so the batch can be tried again
+        sqlx::query("UPDATE applied_batches SET reverted_at = NULL WHERE id = ?")
+            .bind(batch_id)
+            .execute(&state.db)
+            .await
+            .map_err(|e| format!("Failed to release batch {}: {}", batch_id, e))?;
+    }
+    applied
+}
+
+/// Mark a batch as reverted before its inverse runs, so two concurrent reverts can't both apply it
+async fn claim_revert(db: &Pool<Sqlite>, batch_id: i64, connection_id: i64) -> Result<(), String> {
+    let claimed = sqlx::query(
+        "UPDATE applied_batches SET reverted_at = CURRENT_TIMESTAMP
+         WHERE id = ? AND connection_id = ? AND reverted_at IS NULL",
+    )
+    .bind(batch_id)
+    .bind(connection_id)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Failed to mark batch as reverted: {}", e))?;
+
+    if claimed.rows_affected() != 1 {
+        return Err(format!("Batch {} has already been reverted", batch_id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_before_images_round_trip_as_hex() {
+        let value = BindValue::Bytes(b"0xab".to_vec());
+        assert_eq!(value_to_param("postgres", Some("bytea"), &value), Some(Some("0x30786162".to_string())));
+        let value = BindValue::Bytes(b"base64:AA==".to_vec());
+        let param = value_to_param("mysql", Some("blob"), &value).flatten().unwrap();
+        assert_eq!(bind_value("mysql", Some("blob"), Some(&param)), Ok(value));
+    }
+
+    #[test]
+    fn values_that_do_not_bind_back_are_not_revertible() {
+        // Text stored in a SQLite BLOB column would come back as bytes
+        assert_eq!(value_to_param("sqlite", Some("BLOB"), &BindValue::Text("0x41".to_string())), None);
+        // Bytes in a text column would come back as their hex text
+        assert_eq!(value_to_param("sqlite", Some("TEXT"), &BindValue::Bytes(vec![0x41])), None);
+    }
+
+    #[tokio::test]
+    async fn a_batch_is_claimed_for_revert_once() {
+        let db = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::create_schema(&db).await.unwrap();
+        sqlx::query("INSERT INTO connections (id, name, db_type, host, port) VALUES (1, 'local', 'sqlite', 'app.db', 0)")
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO applied_batches (id, connection_id, changes_json, inverse_json) VALUES (7, 1, '[]', '[]')")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        assert!(claim_revert(&db, 7, 2).await.is_err());
+        claim_revert(&db, 7, 1).await.unwrap();
+        assert_eq!(claim_revert(&db, 7, 1).await, Err("Batch 7 has already been reverted".to_string()));
+    }
+
+    #[tokio::test]
+    async fn generated_columns_are_not_captured() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, price REAL, total REAL GENERATED ALWAYS AS (price * 2) STORED)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let generated = generated_columns(&PoolWrapper::Sqlite(pool), "items").await;
+        assert_eq!(generated, HashSet::from(["total".to_string()]));
+    }
+
+    #[test]
+    fn typed_before_images() {
+        assert_eq!(value_to_param("mysql", Some("tinyint(1)"), &BindValue::Int(1)), Some(Some("1".to_string())));
+        assert_eq!(value_to_param("postgres", Some("numeric"), &BindValue::Text("5.10".to_string())), Some(Some("5.10".to_string())));
+        assert_eq!(value_to_param("postgres", Some("int4"), &BindValue::Null), Some(None));
+        let date = BindValue::Date(chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert_eq!(value_to_param("postgres", Some("date"), &date), Some(Some("2024-02-29".to_string())));
+    }
+}
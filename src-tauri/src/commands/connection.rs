@@ -1,51 +1,35 @@
-use crate::commands::credential::{build_connection_string, get_password_from_keyring};
-use crate::db::AppState;
-use crate::models::{Connection, Credential};
-use sqlx::{AnyConnection, Connection as SqlxConnection};
+use crate::db::{AppState, DbConnectOptions, TlsStatus};
+use crate::models::{Connection, PoolSettings, SshSettings, TlsSettings, CONNECTION_COLUMNS};
+use crate::commands::session::{close_connection_sessions, open_session, reconnect_connection_sessions, resolve_login};
+use crate::resolver::{load_connection, resolve_connection, resolve_connection_with_login, resolve_credential, resolve_ssh};
 use tauri::State;
 
+/// Verify unsaved connection settings. Returns the TLS state the server negotiated, if known.
 #[tauri::command]
 pub async fn verify_connection(
     state: State<'_, AppState>,
     connection_string: String,
-    credential_id: Option<String>
-) -> Result<(), String> {
-    let mut final_conn_string = connection_string;
+    credential_id: Option<String>,
+    tls: Option<TlsSettings>,
+    ssh: Option<SshSettings>,
+) -> Result<Option<TlsStatus>, String> {
+    let mut options = DbConnectOptions::parse(&connection_string)?.with_tls(&tls.unwrap_or_default())?;
 
-    // If credential_id is provided, fetch credentials and inject into connection string
+    // If credential_id is provided, connect with its login
     if let Some(cred_id) = credential_id {
-        // Parse ID (handling potential string wrapping)
-        let cred: Credential = sqlx::query_as::<_, Credential>(
-            "SELECT id, name, username, datetime(created_at) as created_at FROM credentials WHERE id = ?"
-        )
-        .bind(&cred_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|e| format!("Credential not found: {}", e))?;
-        
-        let password = get_password_from_keyring(&cred.id)?;
-        
-        if let Some(protocol_end) = final_conn_string.find("://") {
-             let split_idx = protocol_end + 3;
-             let (protocol_part, rest) = final_conn_string.split_at(split_idx);
-             
-             if rest.contains('@') {
-                 if let Some(at_idx) = rest.find('@') {
-                     let (user_part, host_part) = rest.split_at(at_idx);
-                     // host_part starts with '@'
-                     final_conn_string = format!("{}{}:{}{}", protocol_part, user_part, password, host_part);
-                 }
-             } else {
-                // No username in string? Just prepend username:password@
-                final_conn_string = format!("{}{}:{}@{}", protocol_part, cred.username, password, rest);
-             }
-        }
+        let (username, password) = resolve_credential(&state, &cred_id).await?;
+        options = options.with_login(&username, &password);
     }
 
-    <AnyConnection as SqlxConnection>::connect(&final_conn_string)
-        .await
-        .map_err(|e| format!("Failed to connect: {}", e))
-        .map(|_| ())
+    // Reach the database through the SSH tunnel being configured, if any
+    let mut _tunnel = None;
+    if let Some(ssh) = ssh.filter(|s| !s.host.trim().is_empty()) {
+        let (tunneled, tunnel) = crate::tunnel::connect_through(resolve_ssh(&state, &ssh).await?, options).await?;
+        options = tunneled;
+        _tunnel = Some(tunnel);
+    }
+
+    options.verify().await.map_err(|e| format!("Failed to connect: {}", e))
 }
 
 /// Verify connection using stored connection metadata and credentials
@@ -53,9 +37,9 @@ pub async fn verify_connection(
 pub async fn verify_connection_by_id(
     state: State<'_, AppState>,
     connection_id: i64,
-) -> Result<(), String> {
-    let connection_string = get_connection_string_internal(&state, connection_id, None).await?;
-    verify_connection(state, connection_string, None).await
+) -> Result<Option<TlsStatus>, String> {
+    let (options, _tunnel) = resolve_connection(&state, connection_id, None).await?.open_tunnel(&state).await?;
+    options.verify().await.map_err(|e| format!("Failed to connect: {}", e))
 }
 
 /// Verify connection using stored metadata but manual credentials
@@ -65,102 +49,40 @@ pub async fn verify_connection_manual(
     connection_id: i64,
     username: String,
     password: String,
-) -> Result<(), String> {
-    // Fetch connection metadata
-    let conn: Connection = sqlx::query_as::<_, Connection>(
-        "SELECT id, name, db_type, host, port, database_name, credential_id, ssl_mode, datetime(created_at) as created_at FROM connections WHERE id = ?"
-    )
-    .bind(connection_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| format!("Connection not found: {}", e))?;
-
-    let connection_string = build_connection_string(
-        &conn.db_type,
-        &conn.host,
-        conn.port,
-        conn.database_name.as_deref(),
-        Some(&username),
-        Some(&password),
-        conn.ssl_mode.as_deref(),
-    );
-
-    <AnyConnection as SqlxConnection>::connect(&connection_string)
-        .await
-        .map_err(|e| format!("Failed to connect: {}", e))
-        .map(|_| ())
-}
-
-/// Build connection string from connection ID by fetching metadata and credentials
-async fn get_connection_string_internal(state: &AppState, connection_id: i64, database_name: Option<String>) -> Result<String, String> {
-    // Fetch connection metadata
-    let conn: Connection = sqlx::query_as::<_, Connection>(
-        "SELECT id, name, db_type, host, port, database_name, credential_id, ssl_mode, datetime(created_at) as created_at FROM connections WHERE id = ?"
-    )
-    .bind(connection_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| format!("Connection not found: {}", e))?;
-    
-    // If credential_id is set, fetch credentials
-    let (username, password) = if let Some(cred_id) = &conn.credential_id {
-        // Fetch credential from DB
-        let cred: Credential = sqlx::query_as::<_, Credential>(
-            "SELECT id, name, username, datetime(created_at) as created_at FROM credentials WHERE id = ?"
-        )
-        .bind(cred_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|e| format!("Credential not found: {}", e))?;
-        
-        // Get password from keyring
-        let password = get_password_from_keyring(&cred.id)?;
-        (Some(cred.username), Some(password))
-    } else {
-        (None, None)
-    };
-    
-    Ok(build_connection_string(
-        &conn.db_type,
-        &conn.host,
-        conn.port,
-        database_name.or(conn.database_name).as_deref(),
-        username.as_deref(),
-        password.as_deref(),
-        conn.ssl_mode.as_deref(),
-    ))
+) -> Result<Option<TlsStatus>, String> {
+    let (options, _tunnel) = resolve_connection_with_login(&state, connection_id, None, username, password)
+        .await?
+        .open_tunnel(&state)
+        .await?;
+    options.verify().await.map_err(|e| format!("Failed to connect: {}", e))
 }
 
-/// Tauri command: Get connection string by connection ID
+/// Tauri command: Get a connection handle by connection ID. Returns an opaque session id
+/// (reusing an open session for the same database and login), never a URL with the password.
+/// `login` is a login token or session id, as for `create_session`.
 #[tauri::command]
 pub async fn get_connection_string(
+    window: tauri::Window,
     state: State<'_, AppState>,
     connection_id: i64,
     database_name: Option<String>,
-    username: Option<String>,
-    password: Option<String>,
+    login: Option<String>,
 ) -> Result<String, String> {
-    if let (Some(u), Some(p)) = (username, password) {
-        // Build with manual creds
-        let conn: Connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, name, db_type, host, port, database_name, credential_id, ssl_mode, datetime(created_at) as created_at FROM connections WHERE id = ?"
-        )
-        .bind(connection_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|e| format!("Connection not found: {}", e))?;
+    let login = resolve_login(&state, connection_id, login.as_deref())?;
+    let connection = load_connection(&state.db, connection_id).await?;
+    let database_name = database_name.or(connection.database_name);
 
-        return Ok(build_connection_string(
-            &conn.db_type,
-            &conn.host,
-            conn.port,
-            database_name.or(conn.database_name).as_deref(),
-            Some(&u),
-            Some(&p),
-            conn.ssl_mode.as_deref(),
-        ));
+    if let Some(session_id) = state.sessions.find_session(connection_id, database_name.as_deref(), login.as_ref(), Some(window.label())) {
+        return Ok(session_id);
+    }
+    open_session(&state, connection_id, database_name, login, Some(window.label())).await
+}
+
+fn check_environment(environment: Option<&str>) -> Result<(), String> {
+    match environment {
+        Some(e) if !crate::commands::guard::ENVIRONMENTS.contains(&e) => Err(format!("Unknown environment: {}", e)),
+        _ => Ok(()),
     }
-    get_connection_string_internal(&state, connection_id, database_name).await
 }
 
 #[tauri::command]
@@ -173,9 +95,23 @@ pub async fn save_connection(
     database_name: Option<String>,
     credential_id: Option<String>,
     ssl_mode: Option<String>,
+    ssl_ca_path: Option<String>,
+    ssl_client_cert_path: Option<String>,
+    ssl_client_key_path: Option<String>,
+    ssh: Option<SshSettings>,
+    pool: Option<PoolSettings>,
+    read_only: Option<bool>,
+    environment: Option<String>,
 ) -> Result<i64, String> {
+    check_environment(environment.as_deref())?;
+    let ssh = ssh.filter(|s| !s.host.trim().is_empty());
+    let pool = pool.unwrap_or_default();
     let result = sqlx::query(
-        "INSERT INTO connections (name, db_type, host, port, database_name, credential_id, ssl_mode) VALUES (?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO connections (name, db_type, host, port, database_name, credential_id, ssl_mode, \
+         ssl_ca_path, ssl_client_cert_path, ssl_client_key_path, \
+         ssh_host, ssh_port, ssh_user, ssh_auth_method, ssh_key_path, ssh_credential_id, \
+         pool_max_connections, pool_idle_timeout_secs, pool_statement_timeout_secs, pool_connect_timeout_secs, \
+         pool_test_before_acquire, read_only, environment) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&name)
     .bind(&db_type)
@@ -184,6 +120,22 @@ pub async fn save_connection(
     .bind(&database_name)
     .bind(&credential_id)
     .bind(&ssl_mode)
+    .bind(&ssl_ca_path)
+    .bind(&ssl_client_cert_path)
+    .bind(&ssl_client_key_path)
+    .bind(ssh.as_ref().map(|s| s.host.trim().to_string()))
+    .bind(ssh.as_ref().map(|s| s.port))
+    .bind(ssh.as_ref().map(|s| s.user.clone()))
+    .bind(ssh.as_ref().map(|s| s.auth_method.clone()))
+    .bind(ssh.as_ref().and_then(|s| s.key_path.clone()))
+    .bind(ssh.as_ref().and_then(|s| s.credential_id.clone()))
+    .bind(pool.max_connections.map(i64::from))
+    .bind(pool.idle_timeout_secs.map(|n| n as i64))
+    .bind(pool.statement_timeout_secs.map(|n| n as i64))
+    .bind(pool.connect_timeout_secs.map(|n| n as i64))
+    .bind(pool.test_before_acquire)
+    .bind(read_only.unwrap_or(false))
+    .bind(&environment)
     .execute(&state.db)
     .await
     .map_err(|e| format!("Failed to save connection: {}", e))?;
@@ -193,9 +145,7 @@ pub async fn save_connection(
 
 #[tauri::command]
 pub async fn list_connections(state: State<'_, AppState>) -> Result<Vec<Connection>, String> {
-    sqlx::query_as::<_, Connection>(
-        "SELECT id, name, db_type, host, port, database_name, credential_id, ssl_mode, datetime(created_at) as created_at FROM connections ORDER BY created_at DESC",
-    )
+    sqlx::query_as::<_, Connection>(&format!("SELECT {} FROM connections ORDER BY created_at DESC", CONNECTION_COLUMNS))
     .fetch_all(&state.db)
     .await
     .map_err(|e| format!("Failed to list connections: {}", e))
@@ -208,6 +158,7 @@ pub async fn delete_connection(state: State<'_, AppState>, id: i64) -> Result<()
         .execute(&state.db)
         .await
         .map_err(|e| format!("Failed to delete connection: {}", e))?;
+    close_connection_sessions(&state, id).await;
     Ok(())
 }
 
@@ -222,9 +173,23 @@ pub async fn update_connection(
     database_name: Option<String>,
     credential_id: Option<String>,
     ssl_mode: Option<String>,
+    ssl_ca_path: Option<String>,
+    ssl_client_cert_path: Option<String>,
+    ssl_client_key_path: Option<String>,
+    ssh: Option<SshSettings>,
+    pool: Option<PoolSettings>,
+    read_only: Option<bool>,
+    environment: Option<String>,
 ) -> Result<(), String> {
+    check_environment(environment.as_deref())?;
+    let ssh = ssh.filter(|s| !s.host.trim().is_empty());
+    let pool = pool.unwrap_or_default();
     sqlx::query(
-        "UPDATE connections SET name = ?, db_type = ?, host = ?, port = ?, database_name = ?, credential_id = ?, ssl_mode = ? WHERE id = ?"
+        "UPDATE connections SET name = ?, db_type = ?, host = ?, port = ?, database_name = ?, credential_id = ?, ssl_mode = ?, \
+         ssl_ca_path = ?, ssl_client_cert_path = ?, ssl_client_key_path = ?, \
+         ssh_host = ?, ssh_port = ?, ssh_user = ?, ssh_auth_method = ?, ssh_key_path = ?, ssh_credential_id = ?, \
+         pool_max_connections = ?, pool_idle_timeout_secs = ?, pool_statement_timeout_secs = ?, pool_connect_timeout_secs = ?, \
+         pool_test_before_acquire = ?, read_only = ?, environment = ? WHERE id = ?"
     )
     .bind(&name)
     .bind(&db_type)
@@ -233,9 +198,27 @@ pub async fn update_connection(
     .bind(&database_name)
     .bind(&credential_id)
     .bind(&ssl_mode)
+    .bind(&ssl_ca_path)
+    .bind(&ssl_client_cert_path)
+    .bind(&ssl_client_key_path)
+    .bind(ssh.as_ref().map(|s| s.host.trim().to_string()))
+    .bind(ssh.as_ref().map(|s| s.port))
+    .bind(ssh.as_ref().map(|s| s.user.clone()))
+    .bind(ssh.as_ref().map(|s| s.auth_method.clone()))
+    .bind(ssh.as_ref().and_then(|s| s.key_path.clone()))
+    .bind(ssh.as_ref().and_then(|s| s.credential_id.clone()))
+    .bind(pool.max_connections.map(i64::from))
+    .bind(pool.idle_timeout_secs.map(|n| n as i64))
+    .bind(pool.statement_timeout_secs.map(|n| n as i64))
+    .bind(pool.connect_timeout_secs.map(|n| n as i64))
+    .bind(pool.test_before_acquire)
+    .bind(read_only.unwrap_or(false))
+    .bind(&environment)
     .bind(id)
     .execute(&state.db)
     .await
     .map_err(|e| format!("Failed to update connection: {}", e))?;
+    // Open sessions were built from the old settings (read-only flag, pool, TLS, SSH)
+    reconnect_connection_sessions(&state, id).await;
     Ok(())
 }
@@ -1,11 +1,8 @@
 use crate::db::AppState;
 use crate::models::Credential;
-use keyring::Entry;
 use tauri::State;
 use uuid::Uuid;
 
-const SERVICE_NAME: &str = "com.dbplus.sqlclient";
-
 /// Store a credential in the OS keyring
 #[tauri::command]
 pub async fn save_credential(
@@ -18,12 +15,7 @@ pub async fn save_credential(
     let id = Uuid::new_v4().to_string();
     
     // Store password in OS keyring
-    let entry = Entry::new(SERVICE_NAME, &id)
-        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
-    entry
-        .set_password(&password)
-        .map_err(|e| format!("Failed to store password in keyring: {}", e))?;
+    state.secrets.set_password(&id, &password)?;
     
     // Store credential metadata in SQLite (not the password!)
     sqlx::query(
@@ -53,12 +45,8 @@ pub async fn list_credentials(state: State<'_, AppState>) -> Result<Vec<Credenti
 /// Delete a credential from both keyring and database
 #[tauri::command]
 pub async fn delete_credential(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    // Delete from keyring
-    let entry = Entry::new(SERVICE_NAME, &id)
-        .map_err(|e| format!("Failed to access keyring: {}", e))?;
-    
-    // Ignore error if password doesn't exist in keyring
-    let _ = entry.delete_credential();
+    // Delete from keyring (a missing password is not an error)
+    state.secrets.delete_password(&id)?;
     
     // Delete from database
     sqlx::query("DELETE FROM credentials WHERE id = ?")
@@ -81,12 +69,7 @@ pub async fn update_credential(
 ) -> Result<(), String> {
     // If password provided, update in keyring
     if let Some(pwd) = password {
-        let entry = Entry::new(SERVICE_NAME, &id)
-            .map_err(|e| format!("Failed to access keyring: {}", e))?;
-        
-        entry
-            .set_password(&pwd)
-            .map_err(|e| format!("Failed to update password: {}", e))?;
+        state.secrets.set_password(&id, &pwd)?;
     }
     
     // Update metadata in database
@@ -100,53 +83,3 @@ pub async fn update_credential(
     
     Ok(())
 }
-
-/// Get password from keyring (internal use only, not exposed to frontend directly)
-pub fn get_password_from_keyring(credential_id: &str) -> Result<String, String> {
-    let entry = Entry::new(SERVICE_NAME, credential_id)
-        .map_err(|e| format!("Failed to access keyring: {}", e))?;
-    
-    entry
-        .get_password()
-        .map_err(|e| format!("Failed to retrieve password: {}", e))
-}
-
-/// Build a connection string from connection metadata and credentials
-pub fn build_connection_string(
-    db_type: &str,
-    host: &str,
-    port: i32,
-    database_name: Option<&str>,
-    username: Option<&str>,
-    password: Option<&str>,
-    ssl_mode: Option<&str>,
-) -> String {
-    let mut url = match db_type {
-        "mysql" => format!("mysql://"),
-        "postgres" => format!("postgres://"),
-        "sqlite" => return format!("sqlite://{}", host), // For SQLite, host is the file path
-        _ => format!("{}://", db_type),
-    };
-    
-    // Add credentials if present
-    if let (Some(user), Some(pass)) = (username, password) {
-        url.push_str(&format!("{}:{}@", user, pass));
-    } else if let Some(user) = username {
-        url.push_str(&format!("{}@", user));
-    }
-    
-    // Add host and port
-    url.push_str(&format!("{}:{}", host, port));
-    
-    // Add database name if present
-    if let Some(db) = database_name {
-        url.push_str(&format!("/{}", db));
-    }
-    
-    // Add SSL mode if present
-    if let Some(ssl) = ssl_mode {
-        url.push_str(&format!("?sslmode={}", ssl));
-    }
-    
-    url
-}
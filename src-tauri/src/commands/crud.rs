@@ -1,8 +1,14 @@
 use crate::db::PoolWrapper;
 use crate::AppState;
+use crate::commands::binding::{
+    bind_args, bind_values, column_type, mysql_row_bind_values, postgres_row_bind_values, sqlite_row_bind_values, BindValue,
+};
+use crate::commands::changelog::{generated_columns, inverse_changes, record_batch, value_to_param};
+use crate::commands::db_ops::FilterCondition;
+use crate::utils::escape_identifier;
 use serde::{Deserialize, Serialize};
 use tauri::{State, command};
-use sqlx::Row;
+use sqlx::{Column, Row};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RowIdentifier {
@@ -23,6 +29,78 @@ pub struct BatchChange {
     pub identifier: Option<RowIdentifier>, // For UPDATE/DELETE
     pub updates: Option<Vec<CellUpdate>>,  // For UPDATE
     pub insert_values: Option<std::collections::HashMap<String, Option<String>>>, // For INSERT
+    pub original_values: Option<std::collections::HashMap<String, Option<String>>>, // Values as loaded, for conflict checks
+    pub expected_rows: Option<u64>, // Rows the statement must affect; defaults to 1
+}
+
+impl BatchChange {
+    pub(crate) fn expected_rows(&self) -> u64 {
+        self.expected_rows.unwrap_or(1)
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ApplyBatchOptions {
+    #[serde(default)]
+    pub check_conflicts: bool, // UPDATE/DELETE also match the original values; a change hitting 0 rows aborts the batch
+    pub version_column: Option<String>, // Row version column to match (from original_values) when checking conflicts
+    #[serde(default)]
+    pub savepoints: bool, // Run each change in its own savepoint and report every failure instead of stopping at the first
+    #[serde(default)]
+    pub commit_successful: bool, // With savepoints: commit the changes that succeeded even if others failed
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeResult {
+    pub index: usize,
+    pub operation: String,
+    pub table_name: String,
+    pub statement: String, // Statement with bound values inlined
+    pub status: String,    // "applied", "failed", "conflict", "row_count_mismatch" or "rolled_back"
+    pub rows_affected: u64,
+    pub error: Option<String>,
+    pub error_code: Option<String>, // Database error code (SQLSTATE / vendor code) when the database rejected the statement
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchApplyReport {
+    pub committed: bool,
+    pub total_affected: u64,
+    pub results: Vec<ChangeResult>,
+}
+
+/// `(column, value)` pairs in placeholder order; the column decides how the value is bound
+type QueryArgs = Vec<(String, Option<String>)>;
+
+/// Build the `col = $n AND ...` clause matching a row identifier, appending its bound values to `args`
+fn build_where_clause(
+    db_type: &str,
+    identifier: &RowIdentifier,
+    pg_types: Option<&std::collections::HashMap<String, String>>,
+    placeholder_idx: &mut usize,
+    args: &mut QueryArgs
+) -> String {
+    let q = if db_type == "mysql" { "`" } else { "\"" };
+    let mut where_clauses = Vec::new();
+
+    for (i, col) in identifier.columns.iter().enumerate() {
+        let val = &identifier.values[i];
+        if val.is_none() {
+            where_clauses.push(format!("{}{}{} IS NULL", q, col.trim(), q));
+        } else {
+            let placeholder = if db_type == "postgres" {
+                let cast = get_postgres_cast(col.trim(), pg_types);
+                format!("${}{}", placeholder_idx, cast)
+            } else {
+                "?".to_string()
+            };
+            *placeholder_idx += 1;
+            where_clauses.push(format!("{}{}{} = {}", q, col.trim(), q, placeholder));
+            args.push((col.trim().to_string(), val.clone()));
+        }
+    }
+
+    where_clauses.join(" AND ")
 }
 
 fn build_update_query(
@@ -31,7 +109,7 @@ fn build_update_query(
     identifier: &RowIdentifier, 
     updates: &Vec<CellUpdate>,
     pg_types: Option<&std::collections::HashMap<String, String>>
-) -> (String, Vec<Option<String>>) {
+) -> (String, QueryArgs) {
     let q = if db_type == "mysql" { "`" } else { "\"" };
     let mut set_clauses = Vec::new();
     let mut args = Vec::new();
@@ -46,32 +124,16 @@ fn build_update_query(
         };
         placeholder_idx += 1;
         set_clauses.push(format!("{}{}{} = {}", q, update.column.trim(), q, placeholder));
-        args.push(update.value.clone());
+        args.push((update.column.trim().to_string(), update.value.clone()));
     }
 
-    let mut where_clauses = Vec::new();
-    for (i, col) in identifier.columns.iter().enumerate() {
-        let val = &identifier.values[i];
-        if val.is_none() {
-            where_clauses.push(format!("{}{}{} IS NULL", q, col.trim(), q));
-        } else {
-            let placeholder = if db_type == "postgres" {
-                let cast = get_postgres_cast(col.trim(), pg_types);
-                format!("${}{}", placeholder_idx, cast)
-            } else {
-                "?".to_string()
-            };
-            placeholder_idx += 1;
-            where_clauses.push(format!("{}{}{} = {}", q, col.trim(), q, placeholder));
-            args.push(val.clone());
-        }
-    }
+    let where_clause = build_where_clause(db_type, identifier, pg_types, &mut placeholder_idx, &mut args);
 
     let sql = format!(
         "UPDATE {}{}{} SET {} WHERE {}",
         q, table, q,
         set_clauses.join(", "),
-        where_clauses.join(" AND ")
+        where_clause
     );
 
     (sql, args)
@@ -82,33 +144,41 @@ fn build_delete_query(
     table: &str,
     identifier: &RowIdentifier,
     pg_types: Option<&std::collections::HashMap<String, String>>
-) -> (String, Vec<Option<String>>) {
+) -> (String, QueryArgs) {
     let q = if db_type == "mysql" { "`" } else { "\"" };
-    let mut where_clauses = Vec::new();
     let mut args = Vec::new();
     let mut placeholder_idx = 1usize;
 
-    for (i, col) in identifier.columns.iter().enumerate() {
-        let val = &identifier.values[i];
-        if val.is_none() {
-            where_clauses.push(format!("{}{}{} IS NULL", q, col.trim(), q));
-        } else {
-            let placeholder = if db_type == "postgres" {
-                let cast = get_postgres_cast(col.trim(), pg_types);
-                format!("${}{}", placeholder_idx, cast)
-            } else {
-                "?".to_string()
-            };
-            placeholder_idx += 1;
-            where_clauses.push(format!("{}{}{} = {}", q, col.trim(), q, placeholder));
-            args.push(val.clone());
-        }
-    }
+    let where_clause = build_where_clause(db_type, identifier, pg_types, &mut placeholder_idx, &mut args);
 
     let sql = format!(
         "DELETE FROM {}{}{} WHERE {}",
         q, table, q,
-        where_clauses.join(" AND ")
+        where_clause
+    );
+
+    (sql, args)
+}
+
+/// `SELECT <select_list>` over the rows an UPDATE/DELETE with this identifier would match
+fn build_select_query(
+    db_type: &str,
+    select_list: &str,
+    table: &str,
+    identifier: &RowIdentifier,
+    pg_types: Option<&std::collections::HashMap<String, String>>
+) -> (String, QueryArgs) {
+    let q = if db_type == "mysql" { "`" } else { "\"" };
+    let mut args = Vec::new();
+    let mut placeholder_idx = 1usize;
+
+    let where_clause = build_where_clause(db_type, identifier, pg_types, &mut placeholder_idx, &mut args);
+
+    let sql = format!(
+        "SELECT {} FROM {}{}{} WHERE {}",
+        select_list,
+        q, table, q,
+        where_clause
     );
 
     (sql, args)
@@ -119,7 +189,7 @@ fn build_insert_query(
     table: &str,
     values: &std::collections::HashMap<String, Option<String>>,
     pg_types: Option<&std::collections::HashMap<String, String>>
-) -> (String, Vec<Option<String>>) {
+) -> (String, QueryArgs) {
     let q = if db_type == "mysql" { "`" } else { "\"" };
     let mut cols = Vec::new();
     let mut placeholders = Vec::new();
@@ -136,7 +206,7 @@ fn build_insert_query(
         };
         placeholder_idx += 1;
         placeholders.push(placeholder);
-        args.push(val.clone());
+        args.push((col.trim().to_string(), val.clone()));
     }
 
     let sql = format!(
@@ -149,6 +219,301 @@ fn build_insert_query(
     (sql, args)
 }
 
+/// Identifier extended with the original values of the checked columns, so the statement
+/// only matches the row if nobody changed it since it was loaded
+fn conflict_identifier(change: &BatchChange, options: &ApplyBatchOptions) -> Result<Option<RowIdentifier>, String> {
+    let Some(identifier) = &change.identifier else { return Ok(None) };
+    if !options.check_conflicts || change.operation == "INSERT" {
+        return Ok(None);
+    }
+
+    let original = change.original_values.as_ref();
+    let mut checked: Vec<String> = match change.operation.as_str() {
+        "UPDATE" => change.updates.iter().flatten().map(|u| u.column.trim().to_string()).collect(),
+        _ => {
+            let mut cols: Vec<String> = original.map(|o| o.keys().cloned().collect()).unwrap_or_default();
+            cols.sort();
+            cols
+        }
+    };
+    if let Some(version_column) = &options.version_column {
+        checked.retain(|c| c != version_column);
+        checked.push(version_column.clone());
+    }
+
+    let mut columns = identifier.columns.clone();
+    let mut values = identifier.values.clone();
+    for col in checked {
+        if columns.iter().any(|c| c.trim() == col) {
+            continue;
+        }
+        let value = original
+            .and_then(|o| o.get(&col))
+            .ok_or_else(|| format!("Missing original value of column {} for conflict check on table {}", col, change.table_name))?;
+        columns.push(col);
+        values.push(value.clone());
+    }
+
+    Ok(Some(RowIdentifier { columns, values }))
+}
+
+/// Build the statement for one batch change, validating that the payload fits the operation
+fn build_change_query(
+    db_type: &str,
+    change: &BatchChange,
+    options: &ApplyBatchOptions,
+    pg_types: Option<&std::collections::HashMap<String, String>>
+) -> Result<(String, QueryArgs), String> {
+    let checked = conflict_identifier(change, options)?;
+    let identifier = checked.as_ref().or(change.identifier.as_ref());
+
+    match change.operation.as_str() {
+        "UPDATE" => match (identifier, &change.updates) {
+            (Some(identifier), Some(updates)) => Ok(build_update_query(db_type, &change.table_name, identifier, updates, pg_types)),
+            _ => Err(format!("Invalid UPDATE payload for table {}", change.table_name)),
+        },
+        "DELETE" => match identifier {
+            Some(identifier) => Ok(build_delete_query(db_type, &change.table_name, identifier, pg_types)),
+            None => Err(format!("Invalid DELETE payload for table {}", change.table_name)),
+        },
+        "INSERT" => match &change.insert_values {
+            Some(values) => Ok(build_insert_query(db_type, &change.table_name, values, pg_types)),
+            None => Err(format!("Invalid INSERT payload for table {}", change.table_name)),
+        },
+        _ => Err(format!("Unknown operation: {}", change.operation)),
+    }
+}
+
+fn describe_identifier(identifier: &RowIdentifier) -> String {
+    identifier
+        .columns
+        .iter()
+        .zip(&identifier.values)
+        .map(|(c, v)| format!("{} = {}", c, v.as_deref().unwrap_or("NULL")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Inline bound values into `sql` for display. Placeholders (`?` or `$n`) inside quoted
+/// identifiers are left alone; values are rendered as literals of the type they are bound as.
+fn render_sql(sql: &str, values: &[BindValue], db_type: &str) -> String {
+    let literal = |value: Option<&BindValue>| value.map_or_else(|| "NULL".to_string(), |v| v.to_sql_literal(db_type));
+
+    let mut rendered = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut quote: Option<char> = None;
+    let mut next_arg = 0usize;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => {
+                quote = None;
+                rendered.push(c);
+            }
+            (Some(_), c) => rendered.push(c),
+            (None, '`' | '"' | '\'') => {
+                quote = Some(c);
+                rendered.push(c);
+            }
+            (None, '?') => {
+                rendered.push_str(&literal(values.get(next_arg)));
+                next_arg += 1;
+            }
+            (None, '$') if chars.peek().is_some_and(|d| d.is_ascii_digit()) => {
+                let mut digits = String::new();
+                while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(*d);
+                    chars.next();
+                }
+                let idx = digits.parse::<usize>().unwrap_or(0);
+                rendered.push_str(&literal(idx.checked_sub(1).and_then(|i| values.get(i))));
+            }
+            (None, c) => rendered.push(c),
+        }
+    }
+
+    rendered
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangePreview {
+    pub index: usize,
+    pub operation: String,
+    pub table_name: String,
+    pub sql: String,                 // Statement as executed, with placeholders
+    pub params: Vec<Option<String>>, // Values bound to the placeholders, in order
+    pub rendered_sql: String,        // Statement with the bound values inlined
+    pub matched_rows: Option<i64>,   // Rows the WHERE clause currently matches (UPDATE/DELETE)
+    pub expected_rows: u64,
+    pub error: Option<String>,
+}
+
+async fn count_rows(pool: &PoolWrapper, sql: &str, values: Vec<BindValue>) -> Result<i64, String> {
+    match pool {
+        PoolWrapper::Sqlite(p) => {
+            let query = bind_values!(sqlx::query_scalar::<_, i64>(sql), values);
+            query.fetch_one(p).await.map_err(|e| e.to_string())
+        },
+        PoolWrapper::Mysql(p) => {
+            let query = bind_values!(sqlx::query_scalar::<_, i64>(sql), values);
+            query.fetch_one(p).await.map_err(|e| e.to_string())
+        },
+        PoolWrapper::Postgres(p) => {
+            let query = bind_values!(sqlx::query_scalar::<_, i64>(sql), values);
+            query.fetch_one(p).await.map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Column types of every table touched by `changes`, keyed by table name. They decide how
+/// values are bound on every engine, and the placeholder casts on Postgres.
+async fn column_types_for(
+    pool: &PoolWrapper,
+    changes: &[BatchChange]
+) -> std::collections::HashMap<String, std::collections::HashMap<String, String>> {
+    // Cache for table schemas to minimize queries
+    let mut schema_cache = std::collections::HashMap::new();
+    for change in changes {
+        if !schema_cache.contains_key(&change.table_name) {
+            let map = get_column_types(pool, &change.table_name).await;
+            schema_cache.insert(change.table_name.clone(), map);
+        }
+    }
+    schema_cache
+}
+
+/// Dry run of `apply_batch_changes`: the exact statements that would run, with their bound
+/// values, and how many rows each UPDATE/DELETE currently matches. Nothing is modified.
+#[command]
+pub async fn preview_batch_changes(
+    state: State<'_, AppState>,
+    connection_string: String,
+    changes: Vec<BatchChange>,
+    options: Option<ApplyBatchOptions>
+) -> Result<Vec<ChangePreview>, String> {
+    let options = options.unwrap_or_default();
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Sqlite(_) => "sqlite",
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+    };
+
+    let schema_cache = column_types_for(&pool, &changes).await;
+    let mut previews = Vec::with_capacity(changes.len());
+
+    for (index, change) in changes.iter().enumerate() {
+        let column_types = schema_cache.get(&change.table_name);
+
+        let mut preview = ChangePreview {
+            index,
+            operation: change.operation.clone(),
+            table_name: change.table_name.clone(),
+            sql: String::new(),
+            params: vec![],
+            rendered_sql: String::new(),
+            matched_rows: None,
+            expected_rows: change.expected_rows(),
+            error: None,
+        };
+
+        let built = build_change_query(db_type, change, &options, column_types)
+            .and_then(|(sql, args)| Ok((bind_args(db_type, &args, column_types)?, sql, args)));
+        match built {
+            Ok((values, sql, args)) => {
+                preview.rendered_sql = render_sql(&sql, &values, db_type);
+                preview.sql = sql;
+                preview.params = args.into_iter().map(|(_, value)| value).collect();
+            }
+            Err(e) => {
+                preview.error = Some(e);
+                previews.push(preview);
+                continue;
+            }
+        }
+
+        if change.operation != "INSERT" {
+            // Count with the WHERE clause the statement runs with, including the conflict check
+            let checked = conflict_identifier(change, &options).ok().flatten();
+            if let Some(identifier) = checked.as_ref().or(change.identifier.as_ref()) {
+                let (count_sql, count_args) = build_select_query(db_type, "COUNT(*)", &change.table_name, identifier, column_types);
+                let counted = match bind_args(db_type, &count_args, column_types) {
+                    Ok(values) => count_rows(&pool, &count_sql, values).await,
+                    Err(e) => Err(e),
+                };
+                match counted {
+                    Ok(n) => preview.matched_rows = Some(n),
+                    Err(e) => preview.error = Some(format!("Failed to count matching rows: {}", e)),
+                }
+            }
+        }
+
+        previews.push(preview);
+    }
+
+    Ok(previews)
+}
+
+/// Render pending changes as a standalone SQL script for review: one transaction, a comment
+/// per change and all values inlined as escaped literals. Written to `file_path` when given.
+#[command]
+pub async fn export_batch_script(
+    state: State<'_, AppState>,
+    connection_string: String,
+    changes: Vec<BatchChange>,
+    options: Option<ApplyBatchOptions>,
+    file_path: Option<String>
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Sqlite(_) => "sqlite",
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+    };
+    let schema_cache = column_types_for(&pool, &changes).await;
+
+    let (begin, dialect) = match db_type {
+        "mysql" => ("START TRANSACTION;", "MySQL"),
+        "postgres" => ("BEGIN;", "PostgreSQL"),
+        _ => ("BEGIN TRANSACTION;", "SQLite"),
+    };
+    // Comments are single-line; keep user data from breaking out of them
+    let comment = |text: String| text.replace(['\r', '\n'], " ");
+
+    let mut script = vec![
+        format!("-- {} change(s) for {}", changes.len(), dialect),
+        format!("-- Generated {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")),
+        String::new(),
+        begin.to_string(),
+    ];
+
+    for (index, change) in changes.iter().enumerate() {
+        let column_types = schema_cache.get(&change.table_name);
+        let (sql, args) = build_change_query(db_type, change, &options, column_types)?;
+        let values = bind_args(db_type, &args, column_types)?;
+        let target = change.identifier.as_ref().map(|i| format!(" ({})", describe_identifier(i))).unwrap_or_default();
+
+        script.push(String::new());
+        script.push(comment(format!("-- #{} {} {}{}", index + 1, change.operation, change.table_name, target)));
+        if change.operation != "INSERT" {
+            script.push(format!("-- Expected to affect {} row(s)", change.expected_rows()));
+        }
+        script.push(format!("{};", render_sql(&sql, &values, db_type)));
+    }
+
+    script.push(String::new());
+    script.push("COMMIT;".to_string());
+    script.push(String::new());
+    let script = script.join("\n");
+
+    if let Some(path) = file_path {
+        std::fs::write(&path, &script).map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+
+    Ok(script)
+}
+
 #[command]
 pub async fn update_record(
     state: State<'_, AppState>,
@@ -157,6 +522,7 @@ pub async fn update_record(
     identifier: RowIdentifier,
     updates: Vec<CellUpdate>,
 ) -> Result<u64, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
     
     let db_type = match pool {
@@ -165,36 +531,24 @@ pub async fn update_record(
         PoolWrapper::Postgres(_) => "postgres",
     };
     
-    let pg_types = if let PoolWrapper::Postgres(p) = &pool {
-        Some(get_postgres_column_types(p, &table_name).await)
-    } else {
-        None
-    };
+    let column_types = get_column_types(&pool, &table_name).await;
 
-    let (sql, args) = build_update_query(db_type, &table_name, &identifier, &updates, pg_types.as_ref());
+    let (sql, args) = build_update_query(db_type, &table_name, &identifier, &updates, Some(&column_types));
+    let bound = bind_args(db_type, &args, Some(&column_types))?;
 
     let rows_affected = match pool {
         PoolWrapper::Sqlite(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args {
-                query = query.bind(arg);
-            }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         },
         PoolWrapper::Mysql(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args {
-                query = query.bind(arg);
-            }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         },
         PoolWrapper::Postgres(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args {
-                query = query.bind(arg);
-            }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         }
@@ -210,6 +564,7 @@ pub async fn delete_record(
     table_name: String,
     identifier: RowIdentifier,
 ) -> Result<u64, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
     let db_type = match pool {
         PoolWrapper::Sqlite(_) => "sqlite",
@@ -217,30 +572,24 @@ pub async fn delete_record(
         PoolWrapper::Postgres(_) => "postgres",
     };
     
-    let pg_types = if let PoolWrapper::Postgres(p) = &pool {
-        Some(get_postgres_column_types(p, &table_name).await)
-    } else {
-        None
-    };
+    let column_types = get_column_types(&pool, &table_name).await;
 
-    let (sql, args) = build_delete_query(db_type, &table_name, &identifier, pg_types.as_ref());
+    let (sql, args) = build_delete_query(db_type, &table_name, &identifier, Some(&column_types));
+    let bound = bind_args(db_type, &args, Some(&column_types))?;
 
     let rows_affected = match pool {
         PoolWrapper::Sqlite(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args { query = query.bind(arg); }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         },
         PoolWrapper::Mysql(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args { query = query.bind(arg); }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         },
         PoolWrapper::Postgres(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args { query = query.bind(arg); }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         }
@@ -256,6 +605,7 @@ pub async fn insert_record(
     table_name: String,
     values: std::collections::HashMap<String, Option<String>>,
 ) -> Result<u64, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
     let db_type = match pool {
         PoolWrapper::Sqlite(_) => "sqlite",
@@ -263,30 +613,24 @@ pub async fn insert_record(
         PoolWrapper::Postgres(_) => "postgres",
     };
 
-    let pg_types = if let PoolWrapper::Postgres(p) = &pool {
-        Some(get_postgres_column_types(p, &table_name).await)
-    } else {
-        None
-    };
+    let column_types = get_column_types(&pool, &table_name).await;
 
-    let (sql, args) = build_insert_query(db_type, &table_name, &values, pg_types.as_ref());
+    let (sql, args) = build_insert_query(db_type, &table_name, &values, Some(&column_types));
+    let bound = bind_args(db_type, &args, Some(&column_types))?;
 
     let rows_affected = match pool {
         PoolWrapper::Sqlite(p) => {
-             let mut query = sqlx::query(&sql);
-             for arg in args { query = query.bind(arg); }
+             let query = bind_values!(sqlx::query(&sql), bound);
              let res = query.execute(&p).await.map_err(|e| e.to_string())?;
              res.rows_affected()
         },
         PoolWrapper::Mysql(p) => {
-             let mut query = sqlx::query(&sql);
-             for arg in args { query = query.bind(arg); }
+             let query = bind_values!(sqlx::query(&sql), bound);
              let res = query.execute(&p).await.map_err(|e| e.to_string())?;
              res.rows_affected()
         },
         PoolWrapper::Postgres(p) => {
-            let mut query = sqlx::query(&sql);
-            for arg in args { query = query.bind(arg); }
+            let query = bind_values!(sqlx::query(&sql), bound);
             let res = query.execute(&p).await.map_err(|e| e.to_string())?;
             res.rows_affected()
         }
@@ -295,152 +639,549 @@ pub async fn insert_record(
     Ok(rows_affected)
 }
 
+// Runs every change of a batch in one transaction on `$pool` and evaluates to the
+// (report, inverse operations) pair. Without savepoints it returns early, rolling back,
+// on the first database error, or after the loop on conflicts and row count mismatches.
+// With savepoints each change runs in its own savepoint and failures are only recorded.
+macro_rules! apply_changes_in_tx {
+    ($pool:expr, $db_type:expr, $changes:expr, $options:expr, $schema_cache:expr, $generated:expr, $capture:expr, $row_values:path) => {{
+        let mut tx = $pool.begin().await.map_err(|e| e.to_string())?;
+        let mut total_affected = 0;
+        let mut conflicts: Vec<String> = Vec::new();
+        let mut mismatches: Vec<String> = Vec::new();
+        let mut results: Vec<ChangeResult> = Vec::with_capacity($changes.len());
+        let mut inverse: Option<Vec<BatchChange>> = Some(Vec::new());
+
+        for (index, change) in $changes.iter().enumerate() {
+            let column_types = $schema_cache.get(&change.table_name);
+            let generated = $generated.get(&change.table_name);
+            let mut result = ChangeResult {
+                index,
+                operation: change.operation.clone(),
+                table_name: change.table_name.clone(),
+                statement: String::new(),
+                status: "applied".to_string(),
+                rows_affected: 0,
+                error: None,
+                error_code: None,
+            };
+
+            let built = build_change_query($db_type, change, $options, column_types)
+                .and_then(|(sql, args)| Ok((sql, bind_args($db_type, &args, column_types)?)));
+            let (sql, values) = match built {
+                Ok(query) => query,
+                Err(e) if $options.savepoints => {
+                    result.status = "failed".to_string();
+                    result.error = Some(e);
+                    results.push(result);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            result.statement = render_sql(&sql, &values, $db_type);
+            let checked = conflict_identifier(change, $options)?;
+            // Before-image select and conflict count, bound the same way as the statement
+            let select = checked
+                .as_ref()
+                .or(change.identifier.as_ref())
+                .filter(|_| $capture && change.operation != "INSERT")
+                .map(|identifier| build_select_query($db_type, "*", &change.table_name, identifier, column_types))
+                .map(|(select_sql, select_args)| bind_args($db_type, &select_args, column_types).map(|v| (select_sql, v)))
+                .transpose()?;
+            let count = change
+                .identifier
+                .as_ref()
+                .filter(|_| $options.check_conflicts && change.operation != "INSERT")
+                .map(|identifier| build_select_query($db_type, "COUNT(*)", &change.table_name, identifier, column_types))
+                .map(|(count_sql, count_args)| bind_args($db_type, &count_args, column_types).map(|v| (count_sql, v)))
+                .transpose()?;
+
+            if $options.savepoints {
+                sqlx::query("SAVEPOINT batch_change").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+            }
+            let conn = &mut *tx;
+
+            // The before-image is None when a value cannot be bound back as it was read
+            let executed: Result<(u64, Option<Vec<std::collections::HashMap<String, Option<String>>>>), sqlx::Error> = async {
+                // Before-image of the rows this statement will touch, read in the same transaction
+                let mut before = Some(Vec::new());
+                if let Some((select_sql, select_values)) = select {
+                    let query = bind_values!(sqlx::query(&select_sql), select_values);
+                    for row in query.fetch_all(&mut *conn).await? {
+                        let image = row
+                            .columns()
+                            .iter()
+                            .zip($row_values(&row))
+                            .filter(|(c, _)| !generated.is_some_and(|g| g.contains(c.name())))
+                            .map(|(c, value)| {
+                                let param = value_to_param($db_type, column_type(column_types, c.name()), &value?)?;
+                                Some((c.name().to_string(), param))
+                            })
+                            .collect::<Option<_>>();
+                        match (before.as_mut(), image) {
+                            (Some(rows), Some(image)) => rows.push(image),
+                            _ => before = None,
+                        }
+                    }
+                }
+
+                let query = bind_values!(sqlx::query(&sql), values);
+                let res = query.execute(&mut *conn).await?;
+                Ok((res.rows_affected(), before))
+            }
+            .await;
+
+            // (status, message, database error code) when the change did not go through
+            let failure: Option<(&str, String, Option<String>)> = match executed {
+                Err(e) if !$options.savepoints => return Err(e.to_string()),
+                Err(e) => {
+                    let code = e.as_database_error().and_then(|d| d.code()).map(|c| c.to_string());
+                    Some(("failed", e.to_string(), code))
+                }
+                Ok((affected, before)) => {
+                    result.rows_affected = affected;
+                    let conflicted = count.filter(|_| affected == 0);
+                    if let Some((count_sql, count_values)) = conflicted {
+                        // Tell apart rows that were edited from rows that are gone
+                        let query = bind_values!(sqlx::query_scalar::<_, i64>(&count_sql), count_values);
+                        let reason = match query.fetch_one(&mut *conn).await {
+                            Ok(0) => "row no longer exists",
+                            Ok(_) => "row was modified",
+                            Err(_) => "row was modified or deleted",
+                        };
+                        Some(("conflict", reason.to_string(), None))
+                    } else if affected != change.expected_rows() {
+                        let message = format!("expected {} row(s), affected {}", change.expected_rows(), affected);
+                        Some(("row_count_mismatch", message, None))
+                    } else {
+                        if $capture {
+                            match (inverse.as_mut(), before.and_then(|before| inverse_changes(change, &before))) {
+                                (Some(list), Some(ops)) => list.extend(ops),
+                                _ => inverse = None,
+                            }
+                        }
+                        None
+                    }
+                }
+            };
+
+            match failure {
+                None => {
+                    total_affected += result.rows_affected;
+                    if $options.savepoints {
+                        sqlx::query("RELEASE SAVEPOINT batch_change").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+                    }
+                }
+                Some((status, message, code)) => {
+                    if $options.savepoints {
+                        sqlx::query("ROLLBACK TO SAVEPOINT batch_change").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+                        sqlx::query("RELEASE SAVEPOINT batch_change").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+                    } else {
+                        let target = change.identifier.as_ref().map(|i| format!(" ({})", describe_identifier(i))).unwrap_or_default();
+                        let line = format!("#{} {} {}{}: {}", index + 1, change.operation, change.table_name, target, message);
+                        if status == "conflict" {
+                            conflicts.push(line);
+                        } else {
+                            mismatches.push(line);
+                        }
+                    }
+                    result.status = status.to_string();
+                    result.error = Some(message);
+                    result.error_code = code;
+                }
+            }
+            results.push(result);
+        }
+
+        if !conflicts.is_empty() || !mismatches.is_empty() {
+            tx.rollback().await.map_err(|e: sqlx::Error| e.to_string())?;
+            let mut report = Vec::new();
+            if !conflicts.is_empty() {
+                report.push(format!("Conflict: {} row(s) changed since they were loaded:", conflicts.len()));
+                report.extend(conflicts);
+            }
+            if !mismatches.is_empty() {
+                report.push(format!("{} change(s) affected an unexpected number of rows:", mismatches.len()));
+                report.extend(mismatches);
+            }
+            report.push("The transaction was rolled back; no changes were applied.".to_string());
+            return Err(report.join("\n"));
+        }
+
+        let failed = results.iter().any(|r| r.status != "applied");
+        let committed = !failed || $options.commit_successful;
+        if committed {
+            tx.commit().await.map_err(|e: sqlx::Error| e.to_string())?;
+        } else {
+            tx.rollback().await.map_err(|e: sqlx::Error| e.to_string())?;
+            for r in results.iter_mut().filter(|r| r.status == "applied") {
+                r.status = "rolled_back".to_string();
+            }
+            total_affected = 0;
+        }
+
+        // Undo runs the inverse operations last-to-first
+        if let Some(list) = inverse.as_mut() {
+            list.reverse();
+        }
+        (BatchApplyReport { committed, total_affected, results }, inverse)
+    }};
+}
+
 #[command]
 pub async fn apply_batch_changes(
     state: State<'_, AppState>,
     connection_string: String,
-    changes: Vec<BatchChange>
+    changes: Vec<BatchChange>,
+    options: Option<ApplyBatchOptions>
 ) -> Result<u64, String> {
-    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    crate::db::ensure_writable(&state, &connection_string)?;
+    apply_changes(&state, &connection_string, changes, &options.unwrap_or_default(), None).await
+}
+
+/// Apply a batch and return the outcome of every change. With `savepoints` set, failing
+/// changes are rolled back individually; the rest are committed only if `commit_successful`
+/// is set, so a first run can report all failures and a second run keep the good subset.
+#[command]
+pub async fn apply_batch_changes_with_report(
+    state: State<'_, AppState>,
+    connection_string: String,
+    changes: Vec<BatchChange>,
+    options: Option<ApplyBatchOptions>
+) -> Result<BatchApplyReport, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
+    apply_changes_with_report(&state, &connection_string, changes, &options.unwrap_or_default(), None).await
+}
+
+/// Apply a batch, failing with a summary unless it was committed. Used where only the
+/// affected row count is returned.
+pub(crate) async fn apply_changes(
+    state: &State<'_, AppState>,
+    connection_string: &str,
+    changes: Vec<BatchChange>,
+    options: &ApplyBatchOptions,
+    reverts_batch_id: Option<i64>
+) -> Result<u64, String> {
+    let report = apply_changes_with_report(state, connection_string, changes, options, reverts_batch_id).await?;
+    if !report.committed {
+        let failures: Vec<String> = report
+            .results
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| format!("#{} {} {}: {}", r.index + 1, r.operation, r.table_name, e)))
+            .collect();
+        return Err(format!("{} change(s) failed; no changes were applied.\n{}", failures.len(), failures.join("\n")));
+    }
+    Ok(report.total_affected)
+}
+
+/// Apply a batch in one transaction. On saved connections the before-images are captured
+/// and the committed changes are recorded with their inverse so they can be reverted later.
+pub(crate) async fn apply_changes_with_report(
+    state: &State<'_, AppState>,
+    connection_string: &str,
+    changes: Vec<BatchChange>,
+    options: &ApplyBatchOptions,
+    reverts_batch_id: Option<i64>
+) -> Result<BatchApplyReport, String> {
+    let pool = crate::db::get_connection(state, connection_string).await.map_err(|e| e.to_string())?;
     let db_type = match pool {
         PoolWrapper::Sqlite(_) => "sqlite",
         PoolWrapper::Mysql(_) => "mysql",
         PoolWrapper::Postgres(_) => "postgres",
     };
+    let session = state.sessions.get_session(connection_string);
+    let capture = session.is_some();
+
+    let schema_cache = column_types_for(&pool, &changes).await;
+    // Rows deleted by the batch are re-inserted on revert, without the columns the database computes
+    let mut generated = std::collections::HashMap::new();
+    for change in changes.iter().filter(|c| capture && c.operation == "DELETE") {
+        if !generated.contains_key(&change.table_name) {
+            generated.insert(change.table_name.clone(), generated_columns(&pool, &change.table_name).await);
+        }
+    }
 
     // Transactional Execution
-    match pool {
-        PoolWrapper::Sqlite(p) => {
-            let mut tx = p.begin().await.map_err(|e| e.to_string())?;
-            let mut total_affected = 0;
-
-            for change in changes {
-                let (sql, args) = match change.operation.as_str() {
-                    "UPDATE" => {
-                        if change.identifier.is_none() || change.updates.is_none() {
-                             return Err(format!("Invalid UPDATE payload for table {}", change.table_name));
-                        }
-                        build_update_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), change.updates.as_ref().unwrap(), None)
-                    },
-                    "DELETE" => {
-                        if change.identifier.is_none() {
-                             return Err(format!("Invalid DELETE payload for table {}", change.table_name));
-                        }
-                        build_delete_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), None)
-                    },
-                    "INSERT" => {
-                        if change.insert_values.is_none() {
-                             return Err(format!("Invalid INSERT payload for table {}", change.table_name));
-                        }
-                        build_insert_query(db_type, &change.table_name, change.insert_values.as_ref().unwrap(), None)
-                    },
-                    _ => return Err(format!("Unknown operation: {}", change.operation))
-                };
+    let (report, inverse) = match pool {
+        PoolWrapper::Sqlite(p) => apply_changes_in_tx!(p, db_type, changes, options, schema_cache, generated, capture, sqlite_row_bind_values),
+        PoolWrapper::Mysql(p) => apply_changes_in_tx!(p, db_type, changes, options, schema_cache, generated, capture, mysql_row_bind_values),
+        PoolWrapper::Postgres(p) => apply_changes_in_tx!(p, db_type, changes, options, schema_cache, generated, capture, postgres_row_bind_values),
+    };
 
-                // Execute in transaction
-                let mut query = sqlx::query(&sql);
-                for arg in args {
-                    query = query.bind(arg);
-                }
-                
-                let res = query.execute(&mut *tx).await.map_err(|e| e.to_string())?;
-                total_affected += res.rows_affected();
+    if let Some(session) = session {
+        let applied: Vec<&BatchChange> = report
+            .results
+            .iter()
+            .filter(|r| r.status == "applied")
+            .map(|r| &changes[r.index])
+            .collect();
+        if report.committed && !applied.is_empty() {
+            if let Err(e) = record_batch(&state.db, &session, &applied, &inverse, reverts_batch_id).await {
+                log::warn!("Batch applied but could not be recorded for undo: {}", e);
             }
+        }
+    }
 
-            tx.commit().await.map_err(|e: sqlx::Error| e.to_string())?;
-            Ok(total_affected)
-        },
-        PoolWrapper::Mysql(p) => {
-            let mut tx = p.begin().await.map_err(|e| e.to_string())?;
-            let mut total_affected = 0;
-
-            for change in changes {
-                let (sql, args) = match change.operation.as_str() {
-                    "UPDATE" => {
-                        if change.identifier.is_none() || change.updates.is_none() {
-                             return Err(format!("Invalid UPDATE payload for table {}", change.table_name));
-                        }
-                         build_update_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), change.updates.as_ref().unwrap(), None)
-                    },
-                    "DELETE" => {
-                        if change.identifier.is_none() {
-                             return Err(format!("Invalid DELETE payload for table {}", change.table_name));
-                        }
-                        build_delete_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), None)
-                    },
-                    "INSERT" => {
-                         if change.insert_values.is_none() {
-                             return Err(format!("Invalid INSERT payload for table {}", change.table_name));
-                        }
-                        build_insert_query(db_type, &change.table_name, change.insert_values.as_ref().unwrap(), None)
-                    },
-                    _ => return Err(format!("Unknown operation: {}", change.operation))
-                };
+    Ok(report)
+}
 
-                let mut query = sqlx::query(&sql);
-                for arg in args {
-                    query = query.bind(arg);
-                }
-                
-                let res = query.execute(&mut *tx).await.map_err(|e| e.to_string())?;
-                total_affected += res.rows_affected();
-            }
+/// Above this many matched rows a bulk update needs the confirmed row count
+const BULK_CONFIRM_THRESHOLD: u64 = 100;
 
-            tx.commit().await.map_err(|e: sqlx::Error| e.to_string())?;
-            Ok(total_affected)
-        },
-        PoolWrapper::Postgres(p) => {
-            let mut tx = p.begin().await.map_err(|e| e.to_string())?;
-            let mut total_affected = 0;
-            
-            // Cache for table schemas to minimize queries
-            let mut schema_cache: std::collections::HashMap<String, std::collections::HashMap<String, String>> = std::collections::HashMap::new();
-
-            for change in changes {
-                // Fetch schema if not in cache
-                if !schema_cache.contains_key(&change.table_name) {
-                    let map = get_postgres_column_types(&p, &change.table_name).await;
-                    schema_cache.insert(change.table_name.clone(), map);
-                }
-                let pg_types = schema_cache.get(&change.table_name);
+#[derive(Debug, Deserialize)]
+pub struct BulkAssignment {
+    pub column: String,
+    pub value: Option<String>, // Literal bound by column type (None = NULL), or the SQL expression
+    #[serde(default)]
+    pub is_expression: bool, // `value` is SQL inserted as-is, e.g. `price * 1.1` or `UPPER(name)`
+}
 
-                let (sql, args) = match change.operation.as_str() {
-                    "UPDATE" => {
-                        if change.identifier.is_none() || change.updates.is_none() {
-                            return Err(format!("Invalid UPDATE payload for table {}", change.table_name));
-                        }
-                        build_update_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), change.updates.as_ref().unwrap(), pg_types)
-                    },
-                    "DELETE" => {
-                        if change.identifier.is_none() {
-                            return Err(format!("Invalid DELETE payload for table {}", change.table_name));
-                        }
-                        build_delete_query(db_type, &change.table_name, change.identifier.as_ref().unwrap(), pg_types)
-                    },
-                    "INSERT" => {
-                        if change.insert_values.is_none() {
-                            return Err(format!("Invalid INSERT payload for table {}", change.table_name));
-                        }
-                        build_insert_query(db_type, &change.table_name, change.insert_values.as_ref().unwrap(), pg_types)
-                    },
-                    _ => return Err(format!("Unknown operation: {}", change.operation))
-                };
+#[derive(Debug, Deserialize, Default)]
+pub struct BulkUpdateOptions {
+    pub confirm_threshold: Option<u64>, // Matched rows above which confirmation is required; defaults to 100
+    pub confirmed_rows: Option<u64>,    // Row count the user confirmed from the preview; must still match
+}
 
-                let mut query = sqlx::query(&sql);
-                for arg in args {
-                    query = query.bind(arg);
-                }
-                
-                let res = query.execute(&mut *tx).await.map_err(|e| e.to_string())?;
-                total_affected += res.rows_affected();
+#[derive(Debug, Serialize)]
+pub struct BulkUpdatePreview {
+    pub sql: String,
+    pub rendered_sql: String,
+    pub matched_rows: u64,
+    pub requires_confirmation: bool, // Unfiltered, or more rows than the threshold
+}
+
+/// A bulk update and the `SELECT COUNT(*)` of the rows it matches, each with its bound values
+struct BulkUpdateStatements {
+    sql: String,
+    values: Vec<BindValue>,
+    count_sql: String,
+    count_values: Vec<BindValue>,
+    filtered: bool, // At least one filter applies; otherwise every row matches
+}
+
+/// `WHERE <filters>` matching the same rows as `get_table_data`, but with escaped identifiers
+/// and bound values. Placeholders are numbered from `first_placeholder` (postgres).
+fn build_filter_clause(
+    db_type: &str,
+    filters: &[FilterCondition],
+    column_types: &std::collections::HashMap<String, String>,
+    first_placeholder: usize,
+) -> Result<(String, QueryArgs), String> {
+    let mut conditions = Vec::new();
+    let mut args: QueryArgs = Vec::new();
+
+    for filter in filters.iter().filter(|f| f.enabled && !f.column.is_empty()) {
+        let col = escape_identifier(&filter.column, db_type);
+        let comparison = match filter.operator.as_str() {
+            "equals" => Some("="),
+            "not_equals" => Some("!="),
+            "greater_than" => Some(">"),
+            "less_than" => Some("<"),
+            "greater_than_or_equal" => Some(">="),
+            "less_than_or_equal" => Some("<="),
+            _ => None,
+        };
+        let pattern = match filter.operator.as_str() {
+            "contains" | "not_contains" => Some(format!("%{}%", filter.value)),
+            "starts_with" => Some(format!("{}%", filter.value)),
+            "ends_with" => Some(format!("%{}", filter.value)),
+            _ => None,
+        };
+        let placeholder = |cast: String| {
+            if db_type == "postgres" {
+                format!("${}{}", first_placeholder + args.len(), cast)
+            } else {
+                "?".to_string()
             }
+        };
 
-            tx.commit().await.map_err(|e: sqlx::Error| e.to_string())?;
-            Ok(total_affected)
+        let condition = match (filter.operator.as_str(), comparison, pattern) {
+            ("is_null", _, _) => format!("{} IS NULL", col),
+            ("is_not_null", _, _) => format!("{} IS NOT NULL", col),
+            (_, Some(op), _) => {
+                let condition = format!("{} {} {}", col, op, placeholder(get_postgres_cast(&filter.column, Some(column_types))));
+                args.push((filter.column.clone(), Some(filter.value.clone())));
+                condition
+            }
+            (operator, _, Some(pattern)) => {
+                let like = if operator == "not_contains" { "NOT LIKE" } else { "LIKE" };
+                let condition = format!("{} {} {}", col, like, placeholder(String::new()));
+                // No column name: the pattern is bound as text whatever the column type
+                args.push((String::new(), Some(pattern)));
+                condition
+            }
+            (operator, _, _) => return Err(format!("Unknown filter operator: {}", operator)),
+        };
+        conditions.push(condition);
+    }
+
+    if conditions.is_empty() {
+        return Ok((String::new(), args));
+    }
+    Ok((format!("WHERE {}", conditions.join(" AND ")), args))
+}
+
+/// `UPDATE ... SET ... WHERE <filters>` and the matching `SELECT COUNT(*)`. Filters match the
+/// same rows as `get_table_data`; their values are bound after the SET values.
+fn build_bulk_update(
+    db_type: &str,
+    table: &str,
+    filters: &[FilterCondition],
+    assignments: &[BulkAssignment],
+    column_types: &std::collections::HashMap<String, String>
+) -> Result<BulkUpdateStatements, String> {
+    if assignments.is_empty() {
+        return Err("Bulk update needs at least one column assignment".to_string());
+    }
+    let mut set_clauses = Vec::new();
+    let mut args = Vec::new();
+
+    for assignment in assignments {
+        let column = assignment.column.trim();
+        if column.is_empty() {
+            return Err("Bulk update assignment is missing a column".to_string());
         }
+        let value = if assignment.is_expression {
+            match assignment.value.as_deref().map(str::trim) {
+                Some(expr) if !expr.is_empty() => expr.to_string(),
+                _ => return Err(format!("Empty expression for column {}", column)),
+            }
+        } else {
+            args.push((column.to_string(), assignment.value.clone()));
+            if db_type == "postgres" {
+                format!("${}{}", args.len(), get_postgres_cast(column, Some(column_types)))
+            } else {
+                "?".to_string()
+            }
+        };
+        set_clauses.push(format!("{} = {}", escape_identifier(column, db_type), value));
     }
+
+    let table = escape_identifier(table, db_type);
+    let (where_clause, filter_args) = build_filter_clause(db_type, filters, column_types, args.len() + 1)?;
+    let (count_where, count_args) = build_filter_clause(db_type, filters, column_types, 1)?;
+    args.extend(filter_args);
+
+    Ok(BulkUpdateStatements {
+        sql: format!("UPDATE {} SET {} {}", table, set_clauses.join(", "), where_clause).trim_end().to_string(),
+        values: bind_args(db_type, &args, Some(column_types))?,
+        count_sql: format!("SELECT COUNT(*) FROM {} {}", table, count_where).trim_end().to_string(),
+        count_values: bind_args(db_type, &count_args, Some(column_types))?,
+        filtered: !where_clause.is_empty(),
+    })
+}
+
+fn check_bulk_confirmation(matched: u64, filtered: bool, options: &BulkUpdateOptions) -> Result<(), String> {
+    let threshold = options.confirm_threshold.unwrap_or(BULK_CONFIRM_THRESHOLD);
+    match options.confirmed_rows {
+        Some(confirmed) if confirmed != matched => Err(format!(
+            "{} row(s) now match the filters but {} were confirmed; preview the update again",
+            matched, confirmed
+        )),
+        None if !filtered => Err(format!(
+            "Bulk update without filters would change all {} row(s); confirm the row count to proceed",
+            matched
+        )),
+        None if matched > threshold => Err(format!(
+            "Bulk update would change {} row(s); confirm the row count to proceed",
+            matched
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Count the rows a bulk update would change and show its statement. Nothing is modified.
+#[command]
+pub async fn preview_bulk_update(
+    state: State<'_, AppState>,
+    connection_string: String,
+    table_name: String,
+    filters: Vec<FilterCondition>,
+    assignments: Vec<BulkAssignment>,
+    options: Option<BulkUpdateOptions>
+) -> Result<BulkUpdatePreview, String> {
+    let options = options.unwrap_or_default();
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Sqlite(_) => "sqlite",
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+    };
+    let column_types = get_column_types(&pool, &table_name).await;
+    let statements = build_bulk_update(db_type, &table_name, &filters, &assignments, &column_types)?;
+
+    let matched = count_rows(&pool, &statements.count_sql, statements.count_values)
+        .await
+        .map_err(|e| format!("Failed to count matching rows: {}", e))? as u64;
+    let threshold = options.confirm_threshold.unwrap_or(BULK_CONFIRM_THRESHOLD);
+
+    Ok(BulkUpdatePreview {
+        rendered_sql: render_sql(&statements.sql, &statements.values, db_type),
+        sql: statements.sql,
+        matched_rows: matched,
+        requires_confirmation: !statements.filtered || matched > threshold,
+    })
+}
+
+// Counts the matching rows, checks the confirmation and runs the UPDATE in one transaction;
+// rolls back if the statement changed a different number of rows than were counted.
+macro_rules! bulk_update_in_tx {
+    ($pool:expr, $statements:expr, $options:expr) => {{
+        let statements: BulkUpdateStatements = $statements;
+        let mut tx = $pool.begin().await.map_err(|e| e.to_string())?;
+        let matched = bind_values!(sqlx::query_scalar::<_, i64>(&statements.count_sql), statements.count_values)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to count matching rows: {}", e))? as u64;
+        check_bulk_confirmation(matched, statements.filtered, $options)?;
+
+        let query = bind_values!(sqlx::query(&statements.sql), statements.values);
+        let affected = query.execute(&mut *tx).await.map_err(|e| format!("Bulk update failed: {}", e))?.rows_affected();
+        if affected != matched {
+            tx.rollback().await.map_err(|e| e.to_string())?;
+            return Err(format!(
+                "Bulk update affected {} row(s) but {} matched the filters; the transaction was rolled back",
+                affected, matched
+            ));
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+        affected
+    }};
+}
+
+/// Apply column assignments to every row matching the table filters with a single UPDATE.
+/// Unfiltered updates, and those matching more rows than the threshold, need `confirmed_rows`
+/// set to the count from `preview_bulk_update`.
+#[command]
+pub async fn bulk_update(
+    state: State<'_, AppState>,
+    connection_string: String,
+    table_name: String,
+    filters: Vec<FilterCondition>,
+    assignments: Vec<BulkAssignment>,
+    options: Option<BulkUpdateOptions>
+) -> Result<u64, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
+    let options = options.unwrap_or_default();
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Sqlite(_) => "sqlite",
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+    };
+    let column_types = get_column_types(&pool, &table_name).await;
+    let statements = build_bulk_update(db_type, &table_name, &filters, &assignments, &column_types)?;
+
+    let affected = match pool {
+        PoolWrapper::Sqlite(p) => bulk_update_in_tx!(p, statements, &options),
+        PoolWrapper::Mysql(p) => bulk_update_in_tx!(p, statements, &options),
+        PoolWrapper::Postgres(p) => bulk_update_in_tx!(p, statements, &options),
+    };
+
+    Ok(affected)
 }
-async fn get_postgres_column_types(pool: &sqlx::PgPool, table_name: &str) -> std::collections::HashMap<String, String> {
+
+pub(crate) async fn get_postgres_column_types(pool: &sqlx::PgPool, table_name: &str) -> std::collections::HashMap<String, String> {
     let (schema, table) = if let Some((s, t)) = table_name.split_once('.') {
         (s.trim_matches('"'), t.trim_matches('"'))
     } else {
@@ -464,7 +1205,42 @@ async fn get_postgres_column_types(pool: &sqlx::PgPool, table_name: &str) -> std
     map
 }
 
-fn get_postgres_cast(col_name: &str, type_map: Option<&std::collections::HashMap<String, String>>) -> String {
+/// Column name -> type of a table: `udt_name` on Postgres, `COLUMN_TYPE` on MySQL (which keeps
+/// `tinyint(1)` and `unsigned`) and the declared type on SQLite. Empty if the table can't be read.
+pub(crate) async fn get_column_types(pool: &PoolWrapper, table_name: &str) -> std::collections::HashMap<String, String> {
+    match pool {
+        PoolWrapper::Postgres(p) => get_postgres_column_types(p, table_name).await,
+        PoolWrapper::Mysql(p) => {
+            let (schema, table) = match table_name.split_once('.') {
+                Some((s, t)) => (Some(s.trim_matches('`')), t.trim_matches('`')),
+                None => (None, table_name.trim_matches('`')),
+            };
+            let q = "SELECT CAST(COLUMN_NAME AS CHAR), CAST(COLUMN_TYPE AS CHAR) FROM information_schema.COLUMNS
+                     WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?";
+            let rows = sqlx::query(q)
+                .bind(schema)
+                .bind(table)
+                .fetch_all(p)
+                .await
+                .unwrap_or_default();
+            rows.iter()
+                .filter_map(|row| Some((row.try_get::<String, _>(0).ok()?, row.try_get::<String, _>(1).ok()?)))
+                .collect()
+        }
+        PoolWrapper::Sqlite(p) => {
+            let rows = sqlx::query("SELECT name, type FROM pragma_table_info(?)")
+                .bind(table_name.trim_matches('"'))
+                .fetch_all(p)
+                .await
+                .unwrap_or_default();
+            rows.iter()
+                .filter_map(|row| Some((row.try_get::<String, _>(0).ok()?, row.try_get::<String, _>(1).ok()?)))
+                .collect()
+        }
+    }
+}
+
+pub(crate) fn get_postgres_cast(col_name: &str, type_map: Option<&std::collections::HashMap<String, String>>) -> String {
     if let Some(map) = type_map {
         if let Some(type_name) = map.get(col_name) {
             return match type_name.as_str() {
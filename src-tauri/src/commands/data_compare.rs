@@ -0,0 +1,484 @@
+use crate::commands::crud::{BatchChange, CellUpdate, RowIdentifier};
+use crate::commands::binding::{mysql_row_bind_values, postgres_row_bind_values, sqlite_row_bind_values, BindValue};
+use crate::commands::db_ops::get_table_schema;
+use crate::commands::schema_diff::merge_columns;
+use crate::db::{AppState, PoolWrapper};
+use crate::models::ColumnSchema;
+use crate::utils::{escape_identifier, escape_literal};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use tauri::State;
+
+const DEFAULT_BATCH_SIZE: i64 = 1000;
+const DEFAULT_MAX_DIFFERENCES: usize = 1000;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct DataCompareOptions {
+    pub key_columns: Option<Vec<String>>, // Defaults to the source primary key
+    pub batch_size: Option<i64>,
+    pub max_differences: Option<usize>,
+    #[serde(default)]
+    pub generate_sync: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ColumnValueDiff {
+    pub column: String,
+    pub source_value: Option<String>,
+    pub target_value: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChangedRow {
+    pub key: Vec<Option<String>>,
+    pub differences: Vec<ColumnValueDiff>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DataCompareResult {
+    pub key_columns: Vec<String>,
+    pub compared_columns: Vec<String>,
+    pub source_only_columns: Vec<String>,
+    pub target_only_columns: Vec<String>,
+    pub inserted_count: u64,  // Rows in source missing from target
+    pub deleted_count: u64,   // Rows in target missing from source
+    pub changed_count: u64,
+    pub unchanged_count: u64,
+    pub inserted: Vec<Vec<Option<String>>>, // Full source rows, in compared_columns order
+    pub deleted: Vec<Vec<Option<String>>>,  // Target keys, in key_columns order
+    pub changed: Vec<ChangedRow>,
+    pub truncated: bool, // Detail lists stopped at max_differences; counts are always complete
+    pub sync_changes: Option<Vec<BatchChange>>, // Changes that make target match source, for apply_batch_changes
+}
+
+/// How a key column is ordered, so the Rust-side merge agrees with the database's ORDER BY
+#[derive(Clone, Copy, PartialEq)]
+enum KeyKind {
+    Numeric,
+    Text,
+    Native,
+}
+
+fn key_kind(type_name: &str) -> KeyKind {
+    let t = type_name.to_lowercase();
+    if t.contains("int") || t.contains("serial") || t.contains("numeric") || t.contains("decimal")
+        || t.contains("float") || t.contains("double") || t.contains("real")
+    {
+        KeyKind::Numeric
+    } else if t.contains("char") || t.contains("text") || t.contains("clob") {
+        KeyKind::Text
+    } else {
+        KeyKind::Native
+    }
+}
+
+/// Sign, integer digits and fraction digits of a plain decimal, without redundant zeros
+fn decimal_digits(s: &str) -> Option<(bool, &str, &str)> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if (int.is_empty() && frac.is_empty()) || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (int, frac) = (int.trim_start_matches('0'), frac.trim_end_matches('0'));
+    Some((negative && !(int.is_empty() && frac.is_empty()), int, frac))
+}
+
+/// Exact order of two plain decimals of any length, so wide NUMERIC keys aren't rounded
+fn compare_decimals(a: &str, b: &str) -> Option<Ordering> {
+    let (a_neg, a_int, a_frac) = decimal_digits(a)?;
+    let (b_neg, b_int, b_frac) = decimal_digits(b)?;
+    let magnitude = a_int.len().cmp(&b_int.len()).then_with(|| a_int.cmp(b_int)).then_with(|| a_frac.cmp(b_frac));
+    Some(match (a_neg, b_neg) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    })
+}
+
+fn compare_key_value(a: &str, b: &str, kind: KeyKind) -> Ordering {
+    if kind == KeyKind::Numeric {
+        if let (Ok(x), Ok(y)) = (a.parse::<i128>(), b.parse::<i128>()) {
+            return x.cmp(&y);
+        }
+        if let Some(ordering) = compare_decimals(a, b) {
+            return ordering;
+        }
+        // Floating point values in exponent notation
+        if let (Ok(x), Ok(y)) = (a.parse::<f64>(), b.parse::<f64>()) {
+            return x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+        }
+    }
+    // Byte order, matching the binary collation used in the ORDER BY
+    a.cmp(b)
+}
+
+struct CompareSide {
+    connection_string: String,
+    db_type: &'static str,
+    table: String,
+}
+
+impl CompareSide {
+    /// The table, with a `schema.` prefix quoted as its own identifier
+    fn table_ref(&self) -> String {
+        let quote = |name: &str| escape_identifier(name.trim_matches(|c| c == '"' || c == '`'), self.db_type);
+        match self.table.split_once('.') {
+            Some((schema, table)) => format!("{}.{}", quote(schema), quote(table)),
+            None => quote(&self.table),
+        }
+    }
+
+    fn order_expr(&self, column: &str, kind: KeyKind) -> String {
+        let col = escape_identifier(column, self.db_type);
+        match (kind, self.db_type) {
+            (KeyKind::Text, "postgres") => format!("{} COLLATE \"C\"", col),
+            (KeyKind::Text, "mysql") => format!("CAST({} AS BINARY)", col),
+            (KeyKind::Text, _) => format!("{} COLLATE BINARY", col),
+            _ => col,
+        }
+    }
+
+    fn literal(&self, value: &str, kind: KeyKind) -> String {
+        if kind == KeyKind::Numeric && (value.parse::<i128>().is_ok() || value.parse::<f64>().is_ok()) {
+            return value.to_string();
+        }
+        escape_literal(value, self.db_type)
+    }
+
+    /// Fetch the next batch of rows with keys strictly after `after`, ordered by key
+    async fn fetch_batch(
+        &self,
+        state: &State<'_, AppState>,
+        columns: &[String],
+        keys: &[(String, KeyKind)],
+        after: Option<&[String]>,
+        limit: i64,
+    ) -> Result<Vec<Vec<Option<String>>>, String> {
+        let select_list: Vec<String> = columns.iter().map(|c| escape_identifier(c, self.db_type)).collect();
+
+        // Keyset pagination can't step over NULL keys, so those rows are left out of the comparison
+        let mut conditions: Vec<String> = keys
+            .iter()
+            .map(|(k, _)| format!("{} IS NOT NULL", escape_identifier(k, self.db_type)))
+            .collect();
+
+        if let Some(last) = after {
+            let mut branches = Vec::new();
+            for i in 0..keys.len() {
+                let mut parts: Vec<String> = (0..i)
+                    .map(|j| format!("{} = {}", self.order_expr(&keys[j].0, keys[j].1), self.literal(&last[j], keys[j].1)))
+                    .collect();
+                parts.push(format!("{} > {}", self.order_expr(&keys[i].0, keys[i].1), self.literal(&last[i], keys[i].1)));
+                branches.push(format!("({})", parts.join(" AND ")));
+            }
+            conditions.push(format!("({})", branches.join(" OR ")));
+        }
+
+        let order_by: Vec<String> = keys.iter().map(|(k, kind)| self.order_expr(k, *kind)).collect();
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT {}",
+            select_list.join(", "),
+            self.table_ref(),
+            conditions.join(" AND "),
+            order_by.join(", "),
+            limit
+        );
+
+        // Typed values, as text that binds back to the column (binary as `0x` hex), so the
+        // differences and sync changes hold the real values rather than display strings
+        let pool = crate::db::get_connection(state, &self.connection_string).await.map_err(|e| e.to_string())?;
+        let rows: Vec<Vec<Option<BindValue>>> = match &pool {
+            PoolWrapper::Mysql(p) => sqlx::query(&sql).fetch_all(p).await.map(|rows| rows.iter().map(mysql_row_bind_values).collect()),
+            PoolWrapper::Postgres(p) => {
+                sqlx::query(&sql).fetch_all(p).await.map(|rows| rows.iter().map(postgres_row_bind_values).collect())
+            }
+            PoolWrapper::Sqlite(p) => sqlx::query(&sql).fetch_all(p).await.map(|rows| rows.iter().map(sqlite_row_bind_values).collect()),
+        }
+        .map_err(|e| format!("Failed to read {}: {}", self.table, e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(columns)
+                    .map(|(value, column)| {
+                        value
+                            .map(|v| v.to_param())
+                            .ok_or_else(|| format!("Column {} of {} has a type that cannot be compared", column, self.table))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Streams one side of the comparison in key order, one batch at a time
+struct RowStream<'a> {
+    side: &'a CompareSide,
+    buffer: VecDeque<Vec<Option<String>>>,
+    last_key: Option<Vec<String>>,
+    done: bool,
+}
+
+impl<'a> RowStream<'a> {
+    fn new(side: &'a CompareSide) -> Self {
+        Self { side, buffer: VecDeque::new(), last_key: None, done: false }
+    }
+
+    async fn peek(
+        &mut self,
+        state: &State<'_, AppState>,
+        columns: &[String],
+        keys: &[(String, KeyKind)],
+        key_positions: &[usize],
+        batch_size: i64,
+    ) -> Result<Option<&Vec<Option<String>>>, String> {
+        if self.buffer.is_empty() && !self.done {
+            let batch = self.side.fetch_batch(state, columns, keys, self.last_key.as_deref(), batch_size).await?;
+            self.done = (batch.len() as i64) < batch_size;
+            if let Some(last) = batch.last() {
+                self.last_key = Some(key_positions.iter().map(|&i| last[i].clone().unwrap_or_default()).collect());
+            }
+            self.buffer.extend(batch);
+        }
+        Ok(self.buffer.front())
+    }
+}
+
+async fn side_for(state: &State<'_, AppState>, connection_string: &str, table: &str) -> Result<CompareSide, String> {
+    let pool = crate::db::get_connection(state, connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+    Ok(CompareSide {
+        connection_string: connection_string.to_string(),
+        db_type,
+        table: table.to_string(),
+    })
+}
+
+/// Compare rows of `source_table` against `target_table` (defaults to the same name), matched
+/// by primary key or `options.key_columns`. Both sides are read in key order in batches.
+#[tauri::command]
+pub async fn compare_table_data(
+    state: State<'_, AppState>,
+    source_connection_string: String,
+    target_connection_string: String,
+    source_table: String,
+    target_table: Option<String>,
+    options: Option<DataCompareOptions>,
+) -> Result<DataCompareResult, String> {
+    let options = options.unwrap_or_default();
+    let target_table = target_table.unwrap_or_else(|| source_table.clone());
+    let batch_size = options.batch_size.filter(|b| *b > 0).unwrap_or(DEFAULT_BATCH_SIZE);
+    let max_differences = options.max_differences.unwrap_or(DEFAULT_MAX_DIFFERENCES);
+    let generate_sync = options.generate_sync;
+
+    let source_schema: Vec<ColumnSchema> = merge_columns(
+        get_table_schema(state.clone(), source_connection_string.clone(), source_table.clone()).await?,
+    );
+    let target_schema: Vec<ColumnSchema> = merge_columns(
+        get_table_schema(state.clone(), target_connection_string.clone(), target_table.clone()).await?,
+    );
+    if source_schema.is_empty() {
+        return Err(format!("Table not found in source: {}", source_table));
+    }
+    if target_schema.is_empty() {
+        return Err(format!("Table not found in target: {}", target_table));
+    }
+
+    let key_names: Vec<String> = match options.key_columns {
+        Some(k) if !k.is_empty() => k,
+        _ => source_schema.iter().filter(|c| c.is_primary_key).map(|c| c.name.clone()).collect(),
+    };
+    if key_names.is_empty() {
+        return Err(format!("Table {} has no primary key; choose key columns to match rows", source_table));
+    }
+
+    let compared_columns: Vec<String> = source_schema
+        .iter()
+        .filter(|c| target_schema.iter().any(|t| t.name == c.name))
+        .map(|c| c.name.clone())
+        .collect();
+
+    let mut keys = Vec::new();
+    for name in &key_names {
+        let col = source_schema
+            .iter()
+            .find(|c| &c.name == name)
+            .filter(|_| compared_columns.contains(name))
+            .ok_or_else(|| format!("Key column {} must exist in both tables", name))?;
+        keys.push((name.clone(), key_kind(&col.type_name)));
+    }
+    let key_positions: Vec<usize> = key_names
+        .iter()
+        .map(|k| compared_columns.iter().position(|c| c == k).unwrap_or(0))
+        .collect();
+
+    let mut result = DataCompareResult {
+        key_columns: key_names.clone(),
+        compared_columns: compared_columns.clone(),
+        source_only_columns: source_schema.iter().filter(|c| !compared_columns.contains(&c.name)).map(|c| c.name.clone()).collect(),
+        target_only_columns: target_schema.iter().filter(|c| !compared_columns.contains(&c.name)).map(|c| c.name.clone()).collect(),
+        ..Default::default()
+    };
+    let mut sync: Vec<BatchChange> = Vec::new();
+
+    let source_side = side_for(&state, &source_connection_string, &source_table).await?;
+    let target_side = side_for(&state, &target_connection_string, &target_table).await?;
+    let mut source_rows = RowStream::new(&source_side);
+    let mut target_rows = RowStream::new(&target_side);
+
+    let key_of = |row: &Vec<Option<String>>| -> Vec<Option<String>> { key_positions.iter().map(|&i| row[i].clone()).collect() };
+    let identifier_for = |key: Vec<Option<String>>| RowIdentifier { columns: key_names.clone(), values: key };
+
+    loop {
+        let src = source_rows.peek(&state, &compared_columns, &keys, &key_positions, batch_size).await?.cloned();
+        let tgt = target_rows.peek(&state, &compared_columns, &keys, &key_positions, batch_size).await?.cloned();
+
+        let ordering = match (&src, &tgt) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(s), Some(t)) => keys
+                .iter()
+                .zip(&key_positions)
+                .map(|((_, kind), &i)| {
+                    compare_key_value(s[i].as_deref().unwrap_or_default(), t[i].as_deref().unwrap_or_default(), *kind)
+                })
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal),
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let row = source_rows.buffer.pop_front().unwrap_or_default();
+                result.inserted_count += 1;
+                if generate_sync {
+                    let values: HashMap<String, Option<String>> =
+                        compared_columns.iter().cloned().zip(row.iter().cloned()).collect();
+                    sync.push(BatchChange {
+                        operation: "INSERT".to_string(),
+                        table_name: target_table.clone(),
+                        identifier: None,
+                        updates: None,
+                        insert_values: Some(values),
+                        original_values: None,
+                        expected_rows: None,
+                    });
+                }
+                if result.inserted.len() < max_differences {
+                    result.inserted.push(row);
+                } else {
+                    result.truncated = true;
+                }
+            }
+            Ordering::Greater => {
+                let row = target_rows.buffer.pop_front().unwrap_or_default();
+                let key = key_of(&row);
+                result.deleted_count += 1;
+                if generate_sync {
+                    sync.push(BatchChange {
+                        operation: "DELETE".to_string(),
+                        table_name: target_table.clone(),
+                        identifier: Some(identifier_for(key.clone())),
+                        updates: None,
+                        insert_values: None,
+                        original_values: None,
+                        expected_rows: None,
+                    });
+                }
+                if result.deleted.len() < max_differences {
+                    result.deleted.push(key);
+                } else {
+                    result.truncated = true;
+                }
+            }
+            Ordering::Equal => {
+                let s = source_rows.buffer.pop_front().unwrap_or_default();
+                let t = target_rows.buffer.pop_front().unwrap_or_default();
+                let differences: Vec<ColumnValueDiff> = compared_columns
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| s[*i] != t[*i])
+                    .map(|(i, col)| ColumnValueDiff {
+                        column: col.clone(),
+                        source_value: s[i].clone(),
+                        target_value: t[i].clone(),
+                    })
+                    .collect();
+
+                if differences.is_empty() {
+                    result.unchanged_count += 1;
+                    continue;
+                }
+
+                result.changed_count += 1;
+                let key = key_of(&t);
+                if generate_sync {
+                    sync.push(BatchChange {
+                        operation: "UPDATE".to_string(),
+                        table_name: target_table.clone(),
+                        identifier: Some(identifier_for(key.clone())),
+                        updates: Some(
+                            differences
+                                .iter()
+                                .map(|d| CellUpdate { column: d.column.clone(), value: d.source_value.clone() })
+                                .collect(),
+                        ),
+                        insert_values: None,
+                        original_values: Some(
+                            differences.iter().map(|d| (d.column.clone(), d.target_value.clone())).collect(),
+                        ),
+                        expected_rows: None,
+                    });
+                }
+                if result.changed.len() < max_differences {
+                    result.changed.push(ChangedRow { key, differences });
+                } else {
+                    result.truncated = true;
+                }
+            }
+        }
+    }
+
+    if generate_sync {
+        result.sync_changes = Some(sync);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_numeric_keys_compare_exactly() {
+        // Equal as f64, but distinct keys
+        let a = "123456789012345678901234567890123456789012.5";
+        let b = "123456789012345678901234567890123456789012.6";
+        assert_eq!(compare_key_value(a, b, KeyKind::Numeric), Ordering::Less);
+        assert_eq!(compare_key_value(b, a, KeyKind::Numeric), Ordering::Greater);
+        assert_eq!(compare_key_value("-1.50", "-1.5", KeyKind::Numeric), Ordering::Equal);
+        assert_eq!(compare_key_value("-2.5", "1", KeyKind::Numeric), Ordering::Less);
+        assert_eq!(compare_key_value("-0.0", "0", KeyKind::Numeric), Ordering::Equal);
+        assert_eq!(compare_key_value("99", "100.25", KeyKind::Numeric), Ordering::Less);
+        assert_eq!(compare_key_value("1e3", "999", KeyKind::Numeric), Ordering::Greater);
+    }
+
+    #[test]
+    fn schema_qualified_tables_quote_each_part() {
+        let side = CompareSide {
+            connection_string: String::new(),
+            db_type: "postgres",
+            table: "sales.orders".to_string(),
+        };
+        assert_eq!(side.table_ref(), "\"sales\".\"orders\"");
+    }
+}
@@ -1,5 +1,5 @@
-use crate::models::{ColumnSchema, QueryResult};
-use crate::utils::{escape_identifier, split_sql_statements};
+use crate::models::{ColumnSchema, IndexSchema, QueryResult};
+use crate::utils::{escape_identifier, is_read_only_statement, split_sql_statements};
 use sqlx::{Column, Row, TypeInfo};
 use serde::{Deserialize, Serialize};
 use tauri::{self, State};
@@ -92,6 +92,7 @@ pub async fn import_schema(
     connection_string: String, 
     directory_path: String
 ) -> Result<String, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     use std::fs;
     use std::path::Path;
 
@@ -297,60 +298,72 @@ macro_rules! process_rows_sqlite {
     }};
 }
 
+/// Run statements one after another on a single connection, converting each result with
+/// `$process`. With `$begin` set they run inside that read-only transaction, which is rolled
+/// back afterwards, so a statement cannot switch the session back to read-write for the rest.
+macro_rules! run_statements {
+    ($pool:expr, $stmts:expr, $begin:expr, $process:ident) => {{
+        let mut conn = $pool.acquire().await.map_err(|e| format!("Query failed: {}", e))?;
+        let begin: Option<&str> = $begin;
+        if let Some(begin) = begin {
+            sqlx::query(begin)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to start read-only transaction: {}", e))?;
+        }
+
+        let mut results = Vec::new();
+        let mut failure = None;
+        for stmt in $stmts {
+            match sqlx::query(&stmt).fetch_all(&mut *conn).await {
+                Ok(rows) => results.push($process!(rows)),
+                Err(e) => {
+                    failure = Some(format!("Query failed: {}", e));
+                    break;
+                }
+            }
+        }
+
+        if begin.is_some() && sqlx::query("ROLLBACK").execute(&mut *conn).await.is_err() {
+            // Never hand a connection with an open transaction back to the pool
+            drop(conn.detach());
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(results),
+        }
+    }};
+}
+
 #[tauri::command]
 pub async fn execute_query(
     state: State<'_, AppState>,
     connection_string: String,
     query: String,
+    acknowledgement: Option<String>,
 ) -> Result<Vec<QueryResult>, String> {
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let backslash_escapes = matches!(pool, PoolWrapper::Mysql(_));
+    let stmts = split_sql_statements(&query, backslash_escapes);
+
+    let read_only = crate::db::is_read_only(&state, &connection_string);
+    if read_only {
+        if let Some(stmt) = stmts.iter().find(|s| !is_read_only_statement(s, backslash_escapes)) {
+            let preview: String = stmt.chars().take(80).collect();
+            return Err(format!("This connection is read-only; refusing to run: {}", preview));
+        }
+    }
+    crate::commands::guard::ensure_acknowledged(&state, &connection_string, &pool, &query, acknowledgement.as_deref()).await?;
 
     match pool {
         PoolWrapper::Mysql(p) => {
-            let stmts = split_sql_statements(&query, true);
-            let mut results = Vec::new();
-
-            for stmt in stmts {
-                let rows = sqlx::query(&stmt)
-                    .fetch_all(&p)
-                    .await
-                    .map_err(|e| format!("Query failed: {}", e))?;
-
-                let res = process_rows_with_decimal!(rows);
-                results.push(res);
-            }
-            Ok(results)
-        },
+            run_statements!(p, stmts, read_only.then_some("START TRANSACTION READ ONLY"), process_rows_with_decimal)
+        }
         PoolWrapper::Postgres(p) => {
-            let stmts = split_sql_statements(&query, false);
-            let mut results = Vec::new();
-
-            for stmt in stmts {
-                let rows = sqlx::query(&stmt)
-                    .fetch_all(&p)
-                    .await
-                    .map_err(|e| format!("Query failed: {}", e))?;
-
-                let res = process_rows_with_decimal!(rows);
-                results.push(res);
-            }
-            Ok(results)
-        },
-        PoolWrapper::Sqlite(p) => {
-            let stmts = split_sql_statements(&query, false);
-            let mut results = Vec::new();
-
-            for stmt in stmts {
-                let rows = sqlx::query(&stmt)
-                    .fetch_all(&p)
-                    .await
-                    .map_err(|e| format!("Query failed: {}", e))?;
-
-                let res = process_rows_sqlite!(rows);
-                results.push(res);
-            }
-            Ok(results)
+            run_statements!(p, stmts, read_only.then_some("BEGIN READ ONLY"), process_rows_with_decimal)
         }
+        // query_only already refuses writes; the transaction keeps the statements together
+        PoolWrapper::Sqlite(p) => run_statements!(p, stmts, read_only.then_some("BEGIN"), process_rows_sqlite),
     }
 }
 
@@ -463,7 +476,9 @@ pub async fn get_table_schema(
                 CASE WHEN tc.constraint_type = 'PRIMARY KEY' THEN 'PRI' ELSE '' END as column_key,
                 CASE WHEN c.column_default LIKE 'nextval%%' THEN 'auto_increment' ELSE '' END as extra,
                 ccu.table_name::TEXT AS referenced_table,
-                ccu.column_name::TEXT AS referenced_column
+                ccu.column_name::TEXT AS referenced_column,
+                rc.delete_rule::TEXT AS on_delete,
+                rc.update_rule::TEXT AS on_update
             FROM 
                 information_schema.columns c
             LEFT JOIN 
@@ -472,6 +487,8 @@ pub async fn get_table_schema(
                 information_schema.table_constraints tc ON kcu.constraint_name = tc.constraint_name AND kcu.table_schema = tc.table_schema
             LEFT JOIN 
                 information_schema.constraint_column_usage ccu ON tc.constraint_name = ccu.constraint_name AND tc.table_schema = ccu.table_schema AND tc.constraint_type = 'FOREIGN KEY'
+            LEFT JOIN 
+                information_schema.referential_constraints rc ON tc.constraint_name = rc.constraint_name AND tc.table_schema = rc.constraint_schema AND tc.constraint_type = 'FOREIGN KEY'
             WHERE 
                 c.table_name = '{}' AND c.table_schema = 'public'
             ORDER BY 
@@ -490,7 +507,14 @@ pub async fn get_table_schema(
                  let ref_col: Option<String> = row.try_get(7).ok();
                  
                  let foreign_key = if let (Some(t), Some(c)) = (ref_table, ref_col) {
-                      if !t.is_empty() && !c.is_empty() { Some(crate::models::ForeignKey { referenced_table: t, referenced_column: c }) } else { None }
+                      if !t.is_empty() && !c.is_empty() {
+                          Some(crate::models::ForeignKey {
+                              referenced_table: t,
+                              referenced_column: c,
+                              on_delete: row.try_get(8).ok().flatten(),
+                              on_update: row.try_get(9).ok().flatten(),
+                          })
+                      } else { None }
                  } else { None };
 
                  ColumnSchema {
@@ -516,11 +540,15 @@ pub async fn get_table_schema(
                 c.COLUMN_KEY, 
                 c.EXTRA,
                 k.REFERENCED_TABLE_NAME,
-                k.REFERENCED_COLUMN_NAME
+                k.REFERENCED_COLUMN_NAME,
+                r.DELETE_RULE,
+                r.UPDATE_RULE
             FROM 
                 information_schema.COLUMNS c
             LEFT JOIN 
                 information_schema.KEY_COLUMN_USAGE k ON c.TABLE_NAME = k.TABLE_NAME AND c.COLUMN_NAME = k.COLUMN_NAME AND c.TABLE_SCHEMA = k.TABLE_SCHEMA AND k.REFERENCED_TABLE_NAME IS NOT NULL
+            LEFT JOIN 
+                information_schema.REFERENTIAL_CONSTRAINTS r ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
             WHERE 
                 c.TABLE_NAME = '{}' AND c.TABLE_SCHEMA = DATABASE()
             ORDER BY 
@@ -539,7 +567,14 @@ pub async fn get_table_schema(
                  let ref_col: Option<String> = row.try_get(7).ok();
                  
                  let foreign_key = if let (Some(t), Some(c)) = (ref_table, ref_col) {
-                      if !t.is_empty() && !c.is_empty() { Some(crate::models::ForeignKey { referenced_table: t, referenced_column: c }) } else { None }
+                      if !t.is_empty() && !c.is_empty() {
+                          Some(crate::models::ForeignKey {
+                              referenced_table: t,
+                              referenced_column: c,
+                              on_delete: row.try_get(8).ok().flatten(),
+                              on_update: row.try_get(9).ok().flatten(),
+                          })
+                      } else { None }
                  } else { None };
                  
                  ColumnSchema {
@@ -573,6 +608,8 @@ pub async fn get_table_schema(
                         col.foreign_key = Some(crate::models::ForeignKey {
                             referenced_table: to_table,
                             referenced_column: to_col,
+                            on_update: fk_row.try_get(5).ok(),
+                            on_delete: fk_row.try_get(6).ok(),
                         });
                     }
                 }
@@ -582,12 +619,102 @@ pub async fn get_table_schema(
     }
 }
 
+/// Fold (index_name, column_name, is_unique) rows, already ordered by index and position, into indexes
+fn group_index_rows(rows: Vec<(String, String, bool)>) -> Vec<IndexSchema> {
+    let mut indexes: Vec<IndexSchema> = Vec::new();
+    for (name, column, is_unique) in rows {
+        match indexes.last_mut() {
+            Some(idx) if idx.name == name => idx.columns.push(column),
+            _ => indexes.push(IndexSchema { name, columns: vec![column], is_unique }),
+        }
+    }
+    indexes
+}
+
+#[tauri::command]
+pub async fn get_table_indexes(
+    state: State<'_, AppState>,
+    connection_string: String,
+    table_name: String,
+) -> Result<Vec<IndexSchema>, String> {
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+
+    match pool {
+        PoolWrapper::Mysql(p) => {
+             let rows = sqlx::query(
+                 "SELECT INDEX_NAME, COLUMN_NAME, CAST(NON_UNIQUE AS SIGNED) FROM information_schema.STATISTICS
+                  WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND INDEX_NAME != 'PRIMARY'
+                  ORDER BY INDEX_NAME, SEQ_IN_INDEX"
+             )
+             .bind(&table_name)
+             .fetch_all(&p).await.map_err(|e| format!("Failed to fetch indexes: {}", e))?;
+
+             // A single-column UNIQUE index is the column's unique constraint, reported as is_unique by the schema
+             Ok(group_index_rows(rows.iter().map(|row| (
+                 row.try_get(0).unwrap_or_default(),
+                 row.try_get(1).unwrap_or_default(),
+                 row.try_get::<i64, _>(2).unwrap_or(1) == 0,
+             )).collect())
+             .into_iter()
+             .filter(|idx| !(idx.is_unique && idx.columns.len() == 1))
+             .collect())
+        },
+        PoolWrapper::Postgres(p) => {
+             let rows = sqlx::query(
+                 "SELECT i.relname::TEXT, a.attname::TEXT, ix.indisunique
+                  FROM pg_index ix
+                  JOIN pg_class t ON t.oid = ix.indrelid
+                  JOIN pg_class i ON i.oid = ix.indexrelid
+                  JOIN pg_namespace n ON n.oid = t.relnamespace
+                  JOIN LATERAL unnest(ix.indkey) WITH ORDINALITY AS k(attnum, ord) ON true
+                  JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+                  WHERE n.nspname = 'public' AND t.relname = $1 AND NOT ix.indisprimary
+                    AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = ix.indexrelid AND c.conrelid = t.oid AND c.contype IN ('u', 'x'))
+                  ORDER BY i.relname, k.ord"
+             )
+             .bind(&table_name)
+             .fetch_all(&p).await.map_err(|e| format!("Failed to fetch indexes: {}", e))?;
+
+             Ok(group_index_rows(rows.iter().map(|row| (
+                 row.try_get(0).unwrap_or_default(),
+                 row.try_get(1).unwrap_or_default(),
+                 row.try_get(2).unwrap_or(false),
+             )).collect()))
+        },
+        PoolWrapper::Sqlite(p) => {
+             // PRAGMA index_list returns: seq, name, unique, origin, partial
+             // Only explicit CREATE INDEX indexes (origin 'c'); PK/UNIQUE autoindexes can't be recreated by name
+             let list_query = format!("PRAGMA index_list({})", escape_identifier(&table_name, "sqlite"));
+             let list_rows = sqlx::query(&list_query).fetch_all(&p).await.map_err(|e| format!("Failed to fetch indexes: {}", e))?;
+
+             let mut indexes = Vec::new();
+             for row in list_rows {
+                 let origin: String = row.try_get(3).unwrap_or_default();
+                 if origin != "c" {
+                     continue;
+                 }
+                 let name: String = row.try_get(1).unwrap_or_default();
+                 let is_unique = row.try_get::<i32, _>(2).unwrap_or(0) == 1;
+
+                 // PRAGMA index_info returns: seqno, cid, name
+                 let info_query = format!("PRAGMA index_info({})", escape_identifier(&name, "sqlite"));
+                 let info_rows = sqlx::query(&info_query).fetch_all(&p).await.map_err(|e| format!("Failed to fetch indexes: {}", e))?;
+                 let columns = info_rows.iter().map(|r| r.try_get::<String, _>(2).unwrap_or_default()).collect();
+
+                 indexes.push(IndexSchema { name, columns, is_unique });
+             }
+             Ok(indexes)
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn truncate_table(
     state: State<'_, AppState>,
     connection_string: String,
     table_name: String
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -615,6 +742,7 @@ pub async fn drop_table(
     connection_string: String,
     table_name: String
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -644,6 +772,7 @@ pub async fn duplicate_table(
     new_table: String,
     include_data: bool,
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -707,7 +836,8 @@ pub async fn get_databases(
              Ok(dbs)
         },
         PoolWrapper::Sqlite(p) => {
-             let rows = sqlx::query("SELECT file FROM pragma_database_list WHERE name='main'")
+             // Main database is listed by file path, attached databases by schema name
+             let rows = sqlx::query("SELECT CASE WHEN name = 'main' THEN file ELSE name END FROM pragma_database_list WHERE name != 'temp'")
                  .fetch_all(&p).await.map_err(|e| format!("Failed to fetch databases: {}", e))?;
              let dbs: Vec<String> = rows.iter().map(|row| row.try_get(0).unwrap_or_default()).collect();
              Ok(dbs)
@@ -715,65 +845,78 @@ pub async fn get_databases(
     }
 }
 
-fn generate_create_table_sql(table_name: &str, columns: &[ColumnSchema], foreign_keys: &[ForeignKeyInput], db_type: &str) -> String {
-    let cols_sql: Vec<String> = columns.iter().map(|col| {
-        // Handle PostgreSQL SERIAL types
-        let is_postgres_auto = db_type == "postgres" && (col.is_auto_increment || col.default_value.as_deref() == Some("AUTO_INCREMENT"));
-        
-        // Correctly handling type names for Postgres SERIAL/BIGSERIAL
-        let type_str = if is_postgres_auto {
-            match col.type_name.to_lowercase().as_str() {
-                "integer" | "int" | "int4" => "SERIAL".to_string(),
-                "bigint" | "int8" => "BIGSERIAL".to_string(),
-                "smallint" | "int2" => "SMALLSERIAL".to_string(),
-                "serial" => "SERIAL".to_string(),
-                "bigserial" => "BIGSERIAL".to_string(),
-                _ => col.type_name.clone(),
-            }
-        } else {
-            col.type_name.clone()
-        };
+/// Build the column definition used inside CREATE TABLE / ALTER TABLE ADD COLUMN
+pub(crate) fn column_definition_sql(col: &ColumnSchema, db_type: &str) -> String {
+    // Handle PostgreSQL SERIAL types
+    let is_postgres_auto = db_type == "postgres" && (col.is_auto_increment || col.default_value.as_deref() == Some("AUTO_INCREMENT"));
+    
+    // Correctly handling type names for Postgres SERIAL/BIGSERIAL
+    let type_str = if is_postgres_auto {
+        match col.type_name.to_lowercase().as_str() {
+            "integer" | "int" | "int4" => "SERIAL".to_string(),
+            "bigint" | "int8" => "BIGSERIAL".to_string(),
+            "smallint" | "int2" => "SMALLSERIAL".to_string(),
+            "serial" => "SERIAL".to_string(),
+            "bigserial" => "BIGSERIAL".to_string(),
+            _ => col.type_name.clone(),
+        }
+    } else {
+        col.type_name.clone()
+    };
 
-        let col_name = escape_identifier(&col.name, db_type);
-        let mut line = format!("{} {}", col_name, type_str);
+    let col_name = escape_identifier(&col.name, db_type);
+    let mut line = format!("{} {}", col_name, type_str);
 
-        // For SERIAL types, don't add NOT NULL (it's implicit) or DEFAULT
-        if !col.is_nullable && !is_postgres_auto {
-            line.push_str(" NOT NULL");
-        }
-        
-        if !is_postgres_auto {
-            if let Some(def) = &col.default_value {
-                if !def.is_empty() && def != "AUTO_INCREMENT" && !def.starts_with("nextval") && !def.contains("::") { 
-                    // Handle special defaults
-                    let def_val = if def == "CURRENT_TIMESTAMP" || def == "NULL" || def == "TRUE" || def == "FALSE" {
-                        def.clone()
-                    } else if def.chars().all(|c| c.is_numeric() || c == '.') {
-                        def.clone()
-                    } else {
-                        format!("'{}'", def)
-                    };
-                    line.push_str(&format!(" DEFAULT {}", def_val));
-                }
+    // For SERIAL types, don't add NOT NULL (it's implicit) or DEFAULT
+    if !col.is_nullable && !is_postgres_auto {
+        line.push_str(" NOT NULL");
+    }
+    
+    if !is_postgres_auto {
+        if let Some(def) = &col.default_value {
+            if !def.is_empty() && def != "AUTO_INCREMENT" && !def.starts_with("nextval") && !def.contains("::") { 
+                // Handle special defaults
+                let def_val = if def == "CURRENT_TIMESTAMP" || def == "NULL" || def == "TRUE" || def == "FALSE" {
+                    def.clone()
+                } else if def.chars().all(|c| c.is_numeric() || c == '.') {
+                    def.clone()
+                } else {
+                    format!("'{}'", def)
+                };
+                line.push_str(&format!(" DEFAULT {}", def_val));
             }
         }
-        
-        if col.is_primary_key {
-            line.push_str(" PRIMARY KEY");
-        }
-        
-        if col.is_auto_increment && db_type == "mysql" {
-             line.push_str(" AUTO_INCREMENT");
-        }
-        
-        if col.is_unique && !col.is_primary_key {
-            line.push_str(" UNIQUE");
-        }
-        
-        line
-    }).collect();
+    }
+    
+    if col.is_primary_key {
+        line.push_str(" PRIMARY KEY");
+    }
+    
+    if col.is_auto_increment && db_type == "mysql" {
+         line.push_str(" AUTO_INCREMENT");
+    }
+    
+    if col.is_unique && !col.is_primary_key {
+        line.push_str(" UNIQUE");
+    }
+    
+    line
+}
 
-    let mut defs = cols_sql;
+pub(crate) fn generate_create_table_sql(table_name: &str, columns: &[ColumnSchema], foreign_keys: &[ForeignKeyInput], db_type: &str) -> String {
+    // A composite primary key can only be declared at table level
+    let pk_columns: Vec<&ColumnSchema> = columns.iter().filter(|c| c.is_primary_key).collect();
+    let mut defs: Vec<String> = if pk_columns.len() > 1 {
+        let mut defs: Vec<String> = columns
+            .iter()
+            .map(|col| column_definition_sql(&ColumnSchema { is_primary_key: false, ..col.clone() }, db_type))
+            .collect();
+        let keys: Vec<String> = pk_columns.iter().map(|c| escape_identifier(&c.name, db_type)).collect();
+        defs.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+        defs
+    } else {
+        columns.iter().map(|col| column_definition_sql(col, db_type)).collect()
+    };
 
     // Add Foreign Keys
     for fk in foreign_keys {
@@ -798,6 +941,7 @@ pub async fn create_database(
     connection_string: String, 
     database_name: String
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -811,8 +955,8 @@ pub async fn create_database(
              sqlx::query(&query).execute(&p).await.map_err(|e| format!("Failed to create database: {}", e))?;
              Ok(())
         },
-        PoolWrapper::Sqlite(_) => {
-             return Err("SQLite does not support creating databases via SQL".to_string());
+        PoolWrapper::Sqlite(p) => {
+             crate::commands::sqlite::create_database_file(&p, &database_name).await
         }
     }
 }
@@ -824,6 +968,7 @@ pub async fn duplicate_database(
     source_database: String,
     target_database: String,
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -855,9 +1000,8 @@ pub async fn duplicate_database(
              sqlx::query(&query).execute(&p).await.map_err(|e| format!("Failed to duplicate database: {}", e))?;
              Ok(())
         },
-        PoolWrapper::Sqlite(_) => {
-             // Copy file? 
-             Err("Duplicate database not supported for SQLite in this mode".to_string())
+        PoolWrapper::Sqlite(p) => {
+             crate::commands::sqlite::duplicate_database_file(&p, &source_database, &target_database).await
         }
     }
 }
@@ -870,6 +1014,7 @@ pub async fn create_table(
     columns: Vec<ColumnSchema>,
     foreign_keys: Vec<ForeignKeyInput>
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     let db_type = match pool {
@@ -900,8 +1045,10 @@ pub async fn create_table(
 pub async fn delete_database(
     state: State<'_, AppState>,
     connection_string: String, 
-    database_name: String
+    database_name: String,
+    confirm_name: Option<String>,
 ) -> Result<(), String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
     let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
 
     match pool {
@@ -915,14 +1062,14 @@ pub async fn delete_database(
              sqlx::query(&query).execute(&p).await.map_err(|e| format!("Failed to delete database: {}", e))?;
              Ok(())
         },
-        PoolWrapper::Sqlite(_) => {
-             Err("SQLite does not support deleting databases via SQL".to_string())
+        PoolWrapper::Sqlite(p) => {
+             crate::commands::sqlite::delete_database_file(&p, &database_name, confirm_name.as_deref()).await
         }
     }
 }
 
 /// Build SQL WHERE clause from filter conditions
-fn build_where_clause(filters: &[FilterCondition], quote_char: &str) -> String {
+pub(crate) fn build_where_clause(filters: &[FilterCondition], quote_char: &str) -> String {
     let enabled_filters: Vec<&FilterCondition> = filters.iter()
         .filter(|f| f.enabled && !f.column.is_empty())
         .collect();
@@ -0,0 +1,128 @@
+use crate::commands::crud::BatchChange;
+use crate::db::AppState;
+use serde::Serialize;
+use sqlx::Row;
+use tauri::State;
+
+#[derive(Debug, Serialize)]
+pub struct PendingChangesSummary {
+    pub connection_id: i64,
+    pub database_name: String,
+    pub table_name: String,
+    pub change_count: i64,
+    pub updated_at: String,
+}
+
+/// Save the staged changes for a table, replacing any previous draft. An empty list discards the draft.
+#[tauri::command]
+pub async fn save_pending_changes(
+    state: State<'_, AppState>,
+    connection_id: i64,
+    database_name: String,
+    table_name: String,
+    changes: Vec<BatchChange>,
+) -> Result<(), String> {
+    if changes.is_empty() {
+        return discard_pending_changes(state, connection_id, database_name, table_name).await;
+    }
+
+    let changes_json = serde_json::to_string(&changes)
+        .map_err(|e| format!("Failed to serialize pending changes: {}", e))?;
+
+    sqlx::query(
+        "INSERT INTO pending_changes (connection_id, database_name, table_name, changes_json, change_count, updated_at)
+         VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(connection_id, database_name, table_name)
+         DO UPDATE SET changes_json = excluded.changes_json, change_count = excluded.change_count, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(connection_id)
+    .bind(&database_name)
+    .bind(&table_name)
+    .bind(&changes_json)
+    .bind(changes.len() as i64)
+    .execute(&state.db)
+    .await
+    .map_err(|e| format!("Failed to save pending changes: {}", e))?;
+
+    Ok(())
+}
+
+/// Load the staged changes for a table
+#[tauri::command]
+pub async fn load_pending_changes(
+    state: State<'_, AppState>,
+    connection_id: i64,
+    database_name: String,
+    table_name: String,
+) -> Result<Vec<BatchChange>, String> {
+    let row = sqlx::query(
+        "SELECT changes_json FROM pending_changes
+         WHERE connection_id = ? AND database_name = ? AND table_name = ?"
+    )
+    .bind(connection_id)
+    .bind(&database_name)
+    .bind(&table_name)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| format!("Failed to load pending changes: {}", e))?;
+
+    match row {
+        Some(row) => {
+            let changes_json: String = row.get("changes_json");
+            serde_json::from_str(&changes_json).map_err(|e| format!("Failed to parse pending changes: {}", e))
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// List saved drafts, optionally for one connection, most recently edited first
+#[tauri::command]
+pub async fn list_pending_changes(
+    state: State<'_, AppState>,
+    connection_id: Option<i64>,
+) -> Result<Vec<PendingChangesSummary>, String> {
+    let rows = sqlx::query(
+        "SELECT connection_id, database_name, table_name, change_count, CAST(updated_at AS TEXT) AS updated_at
+         FROM pending_changes
+         WHERE ? IS NULL OR connection_id = ?
+         ORDER BY updated_at DESC"
+    )
+    .bind(connection_id)
+    .bind(connection_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| format!("Failed to list pending changes: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| PendingChangesSummary {
+            connection_id: row.get("connection_id"),
+            database_name: row.get("database_name"),
+            table_name: row.get("table_name"),
+            change_count: row.get("change_count"),
+            updated_at: row.get("updated_at"),
+        })
+        .collect())
+}
+
+/// Discard the staged changes for a table
+#[tauri::command]
+pub async fn discard_pending_changes(
+    state: State<'_, AppState>,
+    connection_id: i64,
+    database_name: String,
+    table_name: String,
+) -> Result<(), String> {
+    sqlx::query(
+        "DELETE FROM pending_changes
+         WHERE connection_id = ? AND database_name = ? AND table_name = ?"
+    )
+    .bind(connection_id)
+    .bind(&database_name)
+    .bind(&table_name)
+    .execute(&state.db)
+    .await
+    .map_err(|e| format!("Failed to discard pending changes: {}", e))?;
+
+    Ok(())
+}
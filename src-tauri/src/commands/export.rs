@@ -0,0 +1,952 @@
+use crate::commands::crud::get_column_types;
+use crate::db::{AppState, PoolWrapper};
+use crate::utils::{classify_statement, escape_identifier, is_read_only_statement, split_sql_statements};
+use arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, StringArray,
+    Time64MicrosecondArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use futures_util::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::{Format, Workbook};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Column, Executor, Row, TypeInfo, ValueRef};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use tauri::State;
+
+const PARQUET_BATCH_ROWS: usize = 8192;
+const PARQUET_ROW_GROUP_ROWS: usize = 128 * 1024;
+/// Widest decimal Parquet's Decimal128 holds; wider declared types are written as text
+const DECIMAL_PRECISION: u8 = 38;
+/// BIGINT UNSIGNED holds up to 20 digits
+const UNSIGNED_BIGINT_DECIMAL: (u8, u32) = (20, 0);
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+const XLSX_MAX_COLUMNS: usize = 16_384;
+const XLSX_MAX_STRING_CHARS: usize = 32_767;
+
+/// What to export: a whole table, or the result(s) of a query
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExportSource {
+    pub table_name: Option<String>,
+    pub query: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExportSummary {
+    pub file_path: String,
+    pub rows_written: u64,
+    pub result_sets: usize,
+}
+
+/// Column of an exported result set, with the database type name reported by the driver
+#[derive(Debug, Clone)]
+pub(crate) struct ExportColumn {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// Destination for streamed rows. Each statement produces one begin/write_row*/end_result sequence.
+pub(crate) trait RowSink: Send {
+    fn begin(&mut self, columns: &[ExportColumn]) -> Result<(), String>;
+    fn write_row(&mut self, values: Vec<Value>) -> Result<(), String>;
+    fn end_result(&mut self) -> Result<(), String>;
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+impl ExportSource {
+    pub(crate) fn statements(&self, db_type: &str) -> Result<Vec<String>, String> {
+        match (&self.table_name, &self.query) {
+            (Some(table), None) => Ok(vec![format!("SELECT * FROM {}", escape_identifier(table, db_type))]),
+            (None, Some(query)) => {
+                let backslash_escapes = db_type == "mysql";
+                let stmts = split_sql_statements(query, backslash_escapes);
+                if stmts.is_empty() {
+                    return Err("Query is empty".to_string());
+                }
+                // Exports only read, so they never need the confirmation execute_query asks for
+                if let Some(stmt) = stmts.iter().find(|s| {
+                    !is_read_only_statement(s, backslash_escapes) || classify_statement(s, backslash_escapes).is_some()
+                }) {
+                    let preview: String = stmt.chars().take(80).collect();
+                    return Err(format!("Only read-only queries can be exported: {}", preview));
+                }
+                Ok(stmts)
+            }
+            _ => Err("Provide either a table name or a query to export".to_string()),
+        }
+    }
+}
+
+/// Binary that isn't UTF-8 becomes `0x`-prefixed hex, the form binary columns accept when edited
+fn binary_to_value(bytes: Vec<u8>) -> Value {
+    match String::from_utf8(bytes) {
+        Ok(s) => Value::String(s),
+        Err(e) => Value::String(format!("0x{}", e.as_bytes().iter().map(|b| format!("{:02x}", b)).collect::<String>())),
+    }
+}
+
+/// Decimals become JSON numbers when that is lossless, otherwise strings
+fn decimal_to_value(d: Decimal) -> Value {
+    let s = d.to_string();
+    match s.parse::<serde_json::Number>() {
+        Ok(n) if n.to_string() == s => Value::Number(n),
+        _ => Value::String(s),
+    }
+}
+
+// Typed row conversion for MySQL and Postgres (supports rust_decimal and JSON columns)
+macro_rules! json_values_with_decimal {
+    ($row:expr) => {{
+        let row = &$row;
+        (0..row.columns().len())
+            .map(|i| {
+                if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+                    Value::Null
+                } else if let Ok(v) = row.try_get::<String, _>(i) {
+                    Value::String(v)
+                } else if let Ok(v) = row.try_get::<i64, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<i32, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<i16, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<f64, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<f32, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<bool, _>(i) {
+                    Value::Bool(v)
+                } else if let Ok(v) = row.try_get::<Decimal, _>(i) {
+                    decimal_to_value(v)
+                } else if let Ok(v) = row.try_get::<chrono::NaiveDate, _>(i) {
+                    Value::String(v.to_string())
+                } else if let Ok(v) = row.try_get::<chrono::NaiveDateTime, _>(i) {
+                    Value::String(v.to_string())
+                } else if let Ok(v) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
+                    Value::String(v.to_rfc3339())
+                } else if let Ok(v) = row.try_get::<chrono::NaiveTime, _>(i) {
+                    Value::String(v.to_string())
+                } else if let Ok(v) = row.try_get::<Value, _>(i) {
+                    v
+                } else if let Ok(v) = row.try_get::<Vec<u8>, _>(i) {
+                    binary_to_value(v)
+                } else {
+                    Value::String(format!("ERR[{}]", row.column(i).type_info().name()))
+                }
+            })
+            .collect::<Vec<Value>>()
+    }};
+}
+
+macro_rules! json_values_sqlite {
+    ($row:expr) => {{
+        let row = &$row;
+        (0..row.columns().len())
+            .map(|i| {
+                if row.try_get_raw(i).map(|v| v.is_null()).unwrap_or(true) {
+                    Value::Null
+                } else if let Ok(v) = row.try_get::<String, _>(i) {
+                    Value::String(v)
+                } else if let Ok(v) = row.try_get::<i64, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<f64, _>(i) {
+                    Value::from(v)
+                } else if let Ok(v) = row.try_get::<bool, _>(i) {
+                    Value::Bool(v)
+                } else if let Ok(v) = row.try_get::<Vec<u8>, _>(i) {
+                    binary_to_value(v)
+                } else {
+                    Value::String(format!("ERR[{}]", row.column(i).type_info().name()))
+                }
+            })
+            .collect::<Vec<Value>>()
+    }};
+}
+
+// Streams one statement's rows into the sink without collecting them; returns the row count
+macro_rules! stream_into_sink {
+    ($pool:expr, $sql:expr, $sink:expr, $to_values:ident) => {{
+        let mut started = false;
+        let mut count = 0u64;
+        {
+            let mut rows = sqlx::query($sql).fetch($pool);
+            while let Some(row) = rows.try_next().await.map_err(|e| format!("Query failed: {}", e))? {
+                if !started {
+                    let columns: Vec<ExportColumn> = row
+                        .columns()
+                        .iter()
+                        .map(|c| ExportColumn { name: c.name().to_string(), type_name: c.type_info().name().to_string() })
+                        .collect();
+                    $sink.begin(&columns)?;
+                    started = true;
+                }
+                $sink.write_row($to_values!(row))?;
+                count += 1;
+            }
+        }
+        if !started {
+            // Empty result: take the column list from the prepared statement
+            let columns: Vec<ExportColumn> = match $pool.describe($sql).await {
+                Ok(d) => d
+                    .columns()
+                    .iter()
+                    .map(|c| ExportColumn { name: c.name().to_string(), type_name: c.type_info().name().to_string() })
+                    .collect(),
+                Err(_) => vec![],
+            };
+            $sink.begin(&columns)?;
+        }
+        $sink.end_result()?;
+        count
+    }};
+}
+
+/// Stream each statement into the sink on one connection, inside the `$begin` transaction if
+/// given. The transaction is always rolled back.
+macro_rules! stream_statements {
+    ($pool:expr, $stmts:expr, $sink:expr, $begin:expr, $to_values:ident) => {{
+        let mut conn = $pool.acquire().await.map_err(|e| format!("Query failed: {}", e))?;
+        let begin: Option<&str> = $begin;
+        if let Some(begin) = begin {
+            sqlx::query(begin)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to start read-only transaction: {}", e))?;
+        }
+
+        let streamed: Result<u64, String> = async {
+            let mut total = 0u64;
+            for stmt in $stmts {
+                total += stream_into_sink!(&mut *conn, stmt.as_str(), $sink, $to_values);
+            }
+            Ok(total)
+        }
+        .await;
+
+        if begin.is_some() && sqlx::query("ROLLBACK").execute(&mut *conn).await.is_err() {
+            // Never hand a connection with an open transaction back to the pool
+            drop(conn.detach());
+        }
+        streamed?
+    }};
+}
+
+/// Run every statement of `source` and stream the results into `sink`
+pub(crate) async fn export_to_sink(
+    state: &State<'_, AppState>,
+    connection_string: &str,
+    source: &ExportSource,
+    sink: &mut dyn RowSink,
+    single_result: bool,
+) -> Result<(u64, usize), String> {
+    let pool = crate::db::get_connection(state, connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+
+    let statements = source.statements(db_type)?;
+    if single_result && statements.len() > 1 {
+        return Err("This format holds a single result set; export one statement at a time".to_string());
+    }
+
+    // Read-only connections export inside a read-only transaction, as execute_query runs them
+    let read_only = crate::db::is_read_only(state, connection_string);
+    let total = match &pool {
+        PoolWrapper::Mysql(p) => stream_statements!(
+            p, &statements, sink, read_only.then_some("START TRANSACTION READ ONLY"), json_values_with_decimal
+        ),
+        PoolWrapper::Postgres(p) => {
+            stream_statements!(p, &statements, sink, read_only.then_some("BEGIN READ ONLY"), json_values_with_decimal)
+        }
+        PoolWrapper::Sqlite(p) => stream_statements!(p, &statements, sink, read_only.then_some("BEGIN"), json_values_sqlite),
+    };
+    sink.finish()?;
+    Ok((total, statements.len()))
+}
+
+/// Writes a JSON array of objects, or one object per line for NDJSON
+struct JsonSink {
+    writer: BufWriter<File>,
+    ndjson: bool,
+    keys: Vec<String>, // Column names pre-encoded as JSON strings
+    rows: u64,
+}
+
+impl RowSink for JsonSink {
+    fn begin(&mut self, columns: &[ExportColumn]) -> Result<(), String> {
+        self.keys = columns
+            .iter()
+            .map(|c| serde_json::to_string(&c.name).unwrap_or_default())
+            .collect();
+        if !self.ndjson {
+            self.writer.write_all(b"[").map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, values: Vec<Value>) -> Result<(), String> {
+        // Objects are written by hand so keys keep the column order
+        let fields: Vec<String> = self
+            .keys
+            .iter()
+            .zip(values.iter())
+            .map(|(k, v)| format!("{}:{}", k, v))
+            .collect();
+        let separator: &[u8] = match (self.ndjson, self.rows) {
+            (true, _) => b"",
+            (false, 0) => b"\n  ",
+            (false, _) => b",\n  ",
+        };
+        self.writer.write_all(separator).map_err(|e| e.to_string())?;
+        write!(self.writer, "{{{}}}", fields.join(",")).map_err(|e| e.to_string())?;
+        if self.ndjson {
+            self.writer.write_all(b"\n").map_err(|e| e.to_string())?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn end_result(&mut self) -> Result<(), String> {
+        if !self.ndjson {
+            let close: &[u8] = if self.rows == 0 { b"]\n" } else { b"\n]\n" };
+            self.writer.write_all(close).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|e| format!("Failed to write file: {}", e))
+    }
+}
+
+/// Export a table or query result to a JSON array (`format` = "json", default) or NDJSON ("ndjson")
+#[tauri::command]
+pub async fn export_json(
+    state: State<'_, AppState>,
+    connection_string: String,
+    source: ExportSource,
+    file_path: String,
+    format: Option<String>,
+) -> Result<ExportSummary, String> {
+    let ndjson = match format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "ndjson" | "jsonl" => true,
+        other => return Err(format!("Unsupported JSON format: {}", other)),
+    };
+
+    let file = File::create(&file_path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut sink = JsonSink { writer: BufWriter::new(file), ndjson, keys: vec![], rows: 0 };
+
+    let (rows_written, result_sets) = export_to_sink(&state, &connection_string, &source, &mut sink, true).await?;
+    Ok(ExportSummary { file_path, rows_written, result_sets })
+}
+
+/// Logical type of an exported column, derived from the driver's type name
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnKind {
+    Integer,
+    Float,
+    Decimal,
+    Boolean,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Text,
+}
+
+impl ColumnKind {
+    fn from_type_name(type_name: &str) -> Self {
+        let t = type_name.to_uppercase();
+        if t == "BOOL" || t == "BOOLEAN" {
+            ColumnKind::Boolean
+        } else if t.starts_with("BIGINT") && t.contains("UNSIGNED") {
+            // Does not fit in i64
+            ColumnKind::Decimal
+        } else if t.contains("INT") && !t.contains("INTERVAL") && !t.contains("POINT") {
+            ColumnKind::Integer
+        } else if t.contains("FLOAT") || t.contains("DOUBLE") || t == "REAL" {
+            ColumnKind::Float
+        } else if t.contains("DECIMAL") || t == "NUMERIC" {
+            ColumnKind::Decimal
+        } else if t == "DATE" {
+            ColumnKind::Date
+        } else if t == "TIME" {
+            ColumnKind::Time
+        } else if t == "TIMESTAMPTZ" {
+            ColumnKind::TimestampTz
+        } else if t == "TIMESTAMP" || t == "DATETIME" {
+            ColumnKind::Timestamp
+        } else {
+            ColumnKind::Text
+        }
+    }
+}
+
+fn value_as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64)),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Bool(b) => Some(*b as i64),
+        _ => None,
+    }
+}
+
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+fn value_as_decimal(value: &Value) -> Option<Decimal> {
+    let s = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    Decimal::from_str(&s).or_else(|_| Decimal::from_scientific(&s)).ok()
+}
+
+/// Unscaled value of `d` at `scale`, or None when that would drop digits or exceed `precision`
+fn decimal_mantissa(d: Decimal, precision: u8, scale: u32) -> Option<i128> {
+    let d = if d.scale() > scale { d.normalize() } else { d };
+    let factor = 10i128.checked_pow(scale.checked_sub(d.scale())?)?;
+    let mantissa = d.mantissa().checked_mul(factor)?;
+    (mantissa.unsigned_abs() < 10u128.checked_pow(precision as u32)?).then_some(mantissa)
+}
+
+/// Precision and scale of a declared `DECIMAL(p, s)` / `NUMERIC(p)` type; None when unconstrained
+fn parse_decimal_type(type_name: &str) -> Option<(u8, u32)> {
+    let t = type_name.trim().to_lowercase();
+    if !(t.starts_with("decimal") || t.starts_with("numeric")) {
+        return None;
+    }
+    let (_, args) = t.split_once('(')?;
+    let (args, _) = args.split_once(')')?;
+    let mut parts = args.split(',').map(|p| p.trim().parse::<u32>());
+    let precision = parts.next()?.ok()?;
+    let scale = match parts.next() {
+        Some(s) => s.ok()?,
+        None => 0,
+    };
+    Some((precision.try_into().ok()?, scale))
+}
+
+/// Column name -> declared (precision, scale) of the fixed-point columns of a table
+async fn declared_decimals(pool: &PoolWrapper, table_name: &str) -> HashMap<String, (u8, u32)> {
+    let PoolWrapper::Postgres(p) = pool else {
+        // COLUMN_TYPE on MySQL and the declared type on SQLite keep the precision and scale
+        return get_column_types(pool, table_name)
+            .await
+            .into_iter()
+            .filter_map(|(name, t)| Some((name, parse_decimal_type(&t)?)))
+            .collect();
+    };
+    let (schema, table) = match table_name.split_once('.') {
+        Some((s, t)) => (s.trim_matches('"'), t.trim_matches('"')),
+        None => ("public", table_name.trim_matches('"')),
+    };
+    let q = "SELECT column_name::TEXT, numeric_precision::INT8, numeric_scale::INT8 FROM information_schema.columns
+             WHERE table_schema = $1 AND table_name = $2 AND data_type = 'numeric'
+               AND numeric_precision IS NOT NULL AND numeric_scale IS NOT NULL";
+    let rows = sqlx::query(q).bind(schema).bind(table).fetch_all(p).await.unwrap_or_default();
+    rows.iter()
+        .filter_map(|row| {
+            let precision: i64 = row.try_get(1).ok()?;
+            let scale: i64 = row.try_get(2).ok()?;
+            Some((row.try_get(0).ok()?, (precision.try_into().ok()?, scale.try_into().ok()?)))
+        })
+        .collect()
+}
+
+fn value_as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) => n.as_f64().map(|f| f != 0.0),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "true" | "t" | "1" | "yes" | "y" => Some(true),
+            "false" | "f" | "0" | "no" | "n" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn value_as_date(value: &Value) -> Option<NaiveDate> {
+    let s = value.as_str()?.trim();
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .or_else(|| value_as_datetime(value).map(|dt| dt.date()))
+}
+
+fn value_as_time(value: &Value) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.as_str()?.trim(), "%H:%M:%S%.f").ok()
+}
+
+/// Timestamps as produced by the row converters: RFC 3339 (normalized to UTC) or naive date-times
+fn value_as_datetime(value: &Value) -> Option<NaiveDateTime> {
+    let s = value.as_str()?.trim();
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f"))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().map(|d| d.and_time(NaiveTime::MIN)))
+}
+
+fn value_as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Writes a single result set as a Parquet file with typed columns, in record batches
+struct ParquetSink {
+    file: Option<File>,
+    writer: Option<ArrowWriter<File>>,
+    schema: Option<Arc<Schema>>,
+    columns: Vec<ExportColumn>,
+    kinds: Vec<ColumnKind>,
+    /// Declared (precision, scale) of the source table's fixed-point columns
+    declared_decimals: HashMap<String, (u8, u32)>,
+    decimals: Vec<(u8, u32)>,
+    buffer: Vec<Vec<Value>>,
+}
+
+impl ParquetSink {
+    fn data_type(&self, i: usize) -> DataType {
+        match self.kinds[i] {
+            ColumnKind::Integer => DataType::Int64,
+            ColumnKind::Float => DataType::Float64,
+            ColumnKind::Decimal => DataType::Decimal128(self.decimals[i].0, self.decimals[i].1 as i8),
+            ColumnKind::Boolean => DataType::Boolean,
+            ColumnKind::Date => DataType::Date32,
+            ColumnKind::Time => DataType::Time64(TimeUnit::Microsecond),
+            ColumnKind::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ColumnKind::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            ColumnKind::Text => DataType::Utf8,
+        }
+    }
+
+    /// Convert one buffered column, failing on values that do not fit the column type
+    fn build_array(&self, i: usize) -> Result<ArrayRef, String> {
+        fn convert<T>(
+            rows: &[Vec<Value>],
+            i: usize,
+            column: &ExportColumn,
+            f: impl Fn(&Value) -> Option<T>,
+        ) -> Result<Vec<Option<T>>, String> {
+            rows.iter()
+                .map(|r| match &r[i] {
+                    Value::Null => Ok(None),
+                    v => f(v).map(Some).ok_or_else(|| {
+                        format!("Cannot write value {} in column '{}' as {}", v, column.name, column.type_name)
+                    }),
+                })
+                .collect()
+        }
+
+        let rows = &self.buffer;
+        let column = &self.columns[i];
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+        let array: ArrayRef = match self.kinds[i] {
+            ColumnKind::Integer => Arc::new(Int64Array::from(convert(rows, i, column, value_as_i64)?)),
+            ColumnKind::Float => Arc::new(Float64Array::from(convert(rows, i, column, value_as_f64)?)),
+            ColumnKind::Decimal => {
+                let (precision, scale) = self.decimals[i];
+                let values = rows
+                    .iter()
+                    .map(|r| match &r[i] {
+                        Value::Null => Ok(None),
+                        v => {
+                            let d = value_as_decimal(v).ok_or_else(|| {
+                                format!("Cannot write value {} in column '{}' as {}", v, column.name, column.type_name)
+                            })?;
+                            decimal_mantissa(d, precision, scale).map(Some).ok_or_else(|| {
+                                format!(
+                                    "Value {} in column '{}' does not fit DECIMAL({}, {})",
+                                    d, column.name, precision, scale
+                                )
+                            })
+                        }
+                    })
+                    .collect::<Result<Vec<Option<i128>>, String>>()?;
+                Arc::new(
+                    Decimal128Array::from(values)
+                        .with_precision_and_scale(precision, scale as i8)
+                        .map_err(|e| e.to_string())?,
+                )
+            }
+            ColumnKind::Boolean => Arc::new(BooleanArray::from(convert(rows, i, column, value_as_bool)?)),
+            ColumnKind::Date => Arc::new(Date32Array::from(convert(rows, i, column, |v| {
+                value_as_date(v).map(|d| (d - epoch).num_days() as i32)
+            })?)),
+            ColumnKind::Time => Arc::new(Time64MicrosecondArray::from(convert(rows, i, column, |v| {
+                value_as_time(v).map(|t| t.num_seconds_from_midnight() as i64 * 1_000_000 + (t.nanosecond() / 1_000) as i64)
+            })?)),
+            ColumnKind::Timestamp | ColumnKind::TimestampTz => {
+                let values = convert(rows, i, column, |v| value_as_datetime(v).map(|dt| dt.and_utc().timestamp_micros()))?;
+                let array = TimestampMicrosecondArray::from(values);
+                if self.kinds[i] == ColumnKind::TimestampTz {
+                    Arc::new(array.with_timezone("UTC"))
+                } else {
+                    Arc::new(array)
+                }
+            }
+            ColumnKind::Text => Arc::new(StringArray::from(
+                rows.iter()
+                    .map(|r| if r[i].is_null() { None } else { Some(value_as_text(&r[i])) })
+                    .collect::<Vec<Option<String>>>(),
+            )),
+        };
+        Ok(array)
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        if self.writer.is_none() {
+            let fields: Vec<Field> = self
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Field::new(&c.name, self.data_type(i), true))
+                .collect();
+            let props = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .set_max_row_group_size(PARQUET_ROW_GROUP_ROWS)
+                .build();
+            let schema = Arc::new(Schema::new(fields));
+            let file = self.file.take().ok_or("Parquet file already closed")?;
+            self.writer = Some(
+                ArrowWriter::try_new(file, schema.clone(), Some(props))
+                    .map_err(|e| format!("Failed to create Parquet writer: {}", e))?,
+            );
+            self.schema = Some(schema);
+        }
+
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let arrays = (0..self.columns.len())
+            .map(|i| self.build_array(i))
+            .collect::<Result<Vec<ArrayRef>, String>>()?;
+        let schema = self.schema.clone().ok_or("Parquet writer not initialized")?;
+        let writer = self.writer.as_mut().ok_or("Parquet writer not initialized")?;
+        let batch = RecordBatch::try_new(schema, arrays).map_err(|e| format!("Failed to build record batch: {}", e))?;
+        writer.write(&batch).map_err(|e| format!("Failed to write Parquet: {}", e))?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl RowSink for ParquetSink {
+    fn begin(&mut self, columns: &[ExportColumn]) -> Result<(), String> {
+        if columns.is_empty() {
+            return Err("Result has no columns to export".to_string());
+        }
+        self.kinds = columns.iter().map(|c| ColumnKind::from_type_name(&c.type_name)).collect();
+        self.decimals = vec![(0, 0); columns.len()];
+        for (i, c) in columns.iter().enumerate() {
+            if self.kinds[i] != ColumnKind::Decimal {
+                continue;
+            }
+            let upper = c.type_name.to_uppercase();
+            let declared = if upper.starts_with("BIGINT") && upper.contains("UNSIGNED") {
+                Some(UNSIGNED_BIGINT_DECIMAL)
+            } else {
+                self.declared_decimals.get(&c.name).copied()
+            };
+            // Unconstrained or too wide for Decimal128: keep the exact digits as text rather than guess a scale
+            match declared.filter(|&(p, s)| p > 0 && p <= DECIMAL_PRECISION && s <= p as u32) {
+                Some(decimal) => self.decimals[i] = decimal,
+                None => self.kinds[i] = ColumnKind::Text,
+            }
+        }
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn write_row(&mut self, values: Vec<Value>) -> Result<(), String> {
+        self.buffer.push(values);
+        if self.buffer.len() >= PARQUET_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn end_result(&mut self) -> Result<(), String> {
+        self.flush()
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        match self.writer.take() {
+            Some(writer) => writer.close().map(|_| ()).map_err(|e| format!("Failed to write Parquet: {}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Excel forbids []:*?/\ in sheet names and limits them to 31 characters
+fn sanitize_sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .collect();
+    let trimmed = cleaned.trim_matches('\'');
+    let trimmed = if trimmed.is_empty() { "Sheet" } else { trimmed };
+    trimmed.chars().take(31).collect()
+}
+
+/// Writes each result set to its own worksheet with a header row and typed cells.
+/// Results longer than Excel's row limit continue on additional sheets.
+struct XlsxSink {
+    workbook: Workbook,
+    file_path: String,
+    base_name: Option<String>,
+    header_format: Format,
+    date_format: Format,
+    time_format: Format,
+    datetime_format: Format,
+    columns: Vec<ExportColumn>,
+    kinds: Vec<ColumnKind>,
+    result_index: usize,
+    part: usize,
+    sheet_count: usize,
+    row: u32,
+}
+
+impl XlsxSink {
+    fn new(file_path: String, base_name: Option<String>) -> Self {
+        XlsxSink {
+            workbook: Workbook::new(),
+            file_path,
+            base_name,
+            header_format: Format::new().set_bold(),
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            time_format: Format::new().set_num_format("hh:mm:ss"),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            columns: vec![],
+            kinds: vec![],
+            result_index: 0,
+            part: 0,
+            sheet_count: 0,
+            row: 0,
+        }
+    }
+
+    fn start_sheet(&mut self) -> Result<(), String> {
+        let base = self.base_name.clone().unwrap_or_else(|| format!("Result {}", self.result_index));
+        let name = if self.part == 0 {
+            sanitize_sheet_name(&base)
+        } else {
+            let suffix = format!(" ({})", self.part + 1);
+            let base: String = sanitize_sheet_name(&base).chars().take(31 - suffix.len()).collect();
+            format!("{}{}", base, suffix)
+        };
+
+        let worksheet = self.workbook.add_worksheet_with_constant_memory();
+        worksheet.set_name(name).map_err(|e| e.to_string())?;
+        for (col, column) in self.columns.iter().enumerate() {
+            worksheet
+                .write_string_with_format(0, col as u16, &column.name, &self.header_format)
+                .map_err(|e| e.to_string())?;
+        }
+        worksheet.set_freeze_panes(1, 0).map_err(|e| e.to_string())?;
+
+        self.sheet_count += 1;
+        self.row = 1;
+        Ok(())
+    }
+}
+
+impl RowSink for XlsxSink {
+    fn begin(&mut self, columns: &[ExportColumn]) -> Result<(), String> {
+        if columns.len() > XLSX_MAX_COLUMNS {
+            return Err(format!("Excel supports at most {} columns", XLSX_MAX_COLUMNS));
+        }
+        self.kinds = columns.iter().map(|c| ColumnKind::from_type_name(&c.type_name)).collect();
+        self.columns = columns.to_vec();
+        self.result_index += 1;
+        self.part = 0;
+        self.start_sheet()
+    }
+
+    fn write_row(&mut self, values: Vec<Value>) -> Result<(), String> {
+        if self.row >= XLSX_MAX_ROWS {
+            self.part += 1;
+            self.start_sheet()?;
+        }
+
+        let row = self.row;
+        let worksheet = self.workbook.worksheet_from_index(self.sheet_count - 1).map_err(|e| e.to_string())?;
+        for (i, value) in values.iter().enumerate() {
+            let col = i as u16;
+            let written = match (self.kinds[i], value) {
+                (_, Value::Null) => continue,
+                (_, Value::Bool(b)) => worksheet.write_boolean(row, col, *b),
+                // Integers beyond 2^53 would lose digits as Excel numbers
+                (_, Value::Number(n)) if n.as_i64().is_some_and(|v| v.unsigned_abs() > 1 << 53) => {
+                    worksheet.write_string(row, col, n.to_string())
+                }
+                (_, Value::Number(n)) => worksheet.write_number(row, col, n.as_f64().unwrap_or_default()),
+                (ColumnKind::Date, v) if value_as_date(v).is_some() => {
+                    worksheet.write_datetime_with_format(row, col, value_as_date(v).unwrap_or_default(), &self.date_format)
+                }
+                (ColumnKind::Time, v) if value_as_time(v).is_some() => {
+                    worksheet.write_datetime_with_format(row, col, value_as_time(v).unwrap_or_default(), &self.time_format)
+                }
+                (ColumnKind::Timestamp | ColumnKind::TimestampTz, v) if value_as_datetime(v).is_some() => worksheet
+                    .write_datetime_with_format(row, col, value_as_datetime(v).unwrap_or_default(), &self.datetime_format),
+                (ColumnKind::Integer | ColumnKind::Float | ColumnKind::Decimal, v) if value_as_f64(v).is_some() => {
+                    worksheet.write_number(row, col, value_as_f64(v).unwrap_or_default())
+                }
+                (ColumnKind::Boolean, v) if value_as_bool(v).is_some() => {
+                    worksheet.write_boolean(row, col, value_as_bool(v).unwrap_or_default())
+                }
+                (_, v) => {
+                    let text: String = value_as_text(v).chars().take(XLSX_MAX_STRING_CHARS).collect();
+                    worksheet.write_string(row, col, text)
+                }
+            };
+            written.map_err(|e| format!("Failed to write cell: {}", e))?;
+        }
+        self.row += 1;
+        Ok(())
+    }
+
+    fn end_result(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.workbook
+            .save(&self.file_path)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+}
+
+/// Export a table or single query result to a Parquet file with typed columns
+#[tauri::command]
+pub async fn export_parquet(
+    state: State<'_, AppState>,
+    connection_string: String,
+    source: ExportSource,
+    file_path: String,
+) -> Result<ExportSummary, String> {
+    // Query results carry no declared precision, so their decimal columns are written as text
+    let declared_decimals = match &source.table_name {
+        Some(table) => {
+            let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+            declared_decimals(&pool, table).await
+        }
+        None => HashMap::new(),
+    };
+    let file = File::create(&file_path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut sink = ParquetSink {
+        file: Some(file),
+        writer: None,
+        schema: None,
+        columns: vec![],
+        kinds: vec![],
+        declared_decimals,
+        decimals: vec![],
+        buffer: Vec::with_capacity(PARQUET_BATCH_ROWS),
+    };
+
+    let (rows_written, result_sets) = export_to_sink(&state, &connection_string, &source, &mut sink, true).await?;
+    Ok(ExportSummary { file_path, rows_written, result_sets })
+}
+
+/// Export a table or query to an Excel workbook; each statement of a multi-statement query gets its own sheet
+#[tauri::command]
+pub async fn export_xlsx(
+    state: State<'_, AppState>,
+    connection_string: String,
+    source: ExportSource,
+    file_path: String,
+) -> Result<ExportSummary, String> {
+    let mut sink = XlsxSink::new(file_path.clone(), source.table_name.clone());
+
+    let (rows_written, result_sets) = export_to_sink(&state, &connection_string, &source, &mut sink, false).await?;
+    Ok(ExportSummary { file_path, rows_written, result_sets })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_mantissa_widens_without_rounding() {
+        let d = Decimal::from_str("12.5").unwrap();
+        assert_eq!(decimal_mantissa(d, 10, 3), Some(12_500));
+        assert_eq!(decimal_mantissa(Decimal::from_str("-0.25").unwrap(), 10, 2), Some(-25));
+        // Trailing zeros are not lost digits
+        assert_eq!(decimal_mantissa(Decimal::from_str("1.2500").unwrap(), 10, 2), Some(125));
+    }
+
+    #[test]
+    fn decimal_mantissa_refuses_lossy_values() {
+        assert_eq!(decimal_mantissa(Decimal::from_str("1.255").unwrap(), 10, 2), None);
+        assert_eq!(decimal_mantissa(Decimal::from_str("1000.5").unwrap(), 5, 2), None);
+        // 28 integer digits at scale 11 would need 39 digits of precision
+        let big = Decimal::from_str("9999999999999999999999999999").unwrap();
+        assert_eq!(decimal_mantissa(big, DECIMAL_PRECISION, 11), None);
+        assert!(decimal_mantissa(big, DECIMAL_PRECISION, 10).is_some());
+    }
+
+    #[test]
+    fn parse_decimal_type_reads_declared_precision() {
+        assert_eq!(parse_decimal_type("decimal(10,2)"), Some((10, 2)));
+        assert_eq!(parse_decimal_type("decimal(12, 4) unsigned"), Some((12, 4)));
+        assert_eq!(parse_decimal_type("NUMERIC(8)"), Some((8, 0)));
+        assert_eq!(parse_decimal_type("NUMERIC"), None);
+        assert_eq!(parse_decimal_type("varchar(10)"), None);
+    }
+
+    #[test]
+    fn parquet_decimals_follow_the_declared_type() {
+        let path = std::env::temp_dir().join(format!("export-decimals-{}.parquet", std::process::id()));
+        let mut sink = ParquetSink {
+            file: Some(File::create(&path).unwrap()),
+            writer: None,
+            schema: None,
+            columns: vec![],
+            kinds: vec![],
+            declared_decimals: HashMap::from([("price".to_string(), (10, 2))]),
+            decimals: vec![],
+            buffer: vec![],
+        };
+        let column = |name: &str, type_name: &str| ExportColumn { name: name.into(), type_name: type_name.into() };
+        sink.begin(&[column("price", "NUMERIC"), column("ratio", "NUMERIC"), column("id", "BIGINT UNSIGNED")])
+            .unwrap();
+        assert_eq!(sink.data_type(0), DataType::Decimal128(10, 2));
+        // Unconstrained NUMERIC keeps its digits as text
+        assert_eq!(sink.data_type(1), DataType::Utf8);
+        assert_eq!(sink.data_type(2), DataType::Decimal128(20, 0));
+
+        // The scale no longer depends on the first rows
+        sink.write_row(vec![Value::from(1), Value::from("0.5"), Value::from(1)]).unwrap();
+        sink.write_row(vec![Value::from("2.25"), Value::from("0.333333333333"), Value::from("18446744073709551615")])
+            .unwrap();
+        sink.end_result().unwrap();
+        sink.write_row(vec![Value::from("2.255"), Value::Null, Value::Null]).unwrap();
+        assert!(sink.end_result().unwrap_err().contains("does not fit DECIMAL(10, 2)"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
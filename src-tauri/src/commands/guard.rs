@@ -0,0 +1,135 @@
+use crate::db::{AppState, PoolWrapper};
+use crate::utils::{classify_statement, split_sql_statements};
+use serde::Serialize;
+use tauri::{State, command};
+
+/// Environments a connection can be tagged with
+pub const ENVIRONMENTS: [&str; 3] = ["development", "staging", "production"];
+
+#[derive(Serialize)]
+pub struct EnvironmentGuard {
+    pub environment: String,
+    pub confirm_destructive: bool,
+}
+
+#[derive(Serialize)]
+pub struct FlaggedStatement {
+    pub index: usize, // Position among the query's statements
+    pub statement: String,
+    pub risk: String, // "unbounded_update", "unbounded_delete", "merge" or "ddl"
+    pub reason: String,
+}
+
+/// What running a query would need: the statements that need confirmation on this connection
+/// and, if there are any, the token that acknowledges them
+#[derive(Serialize)]
+pub struct QueryReview {
+    pub environment: Option<String>,
+    pub statements: Vec<FlaggedStatement>,
+    pub acknowledgement: Option<String>,
+}
+
+/// The environment of the connection behind a session, if it requires confirmation of
+/// destructive statements. Raw connection strings are not guarded.
+async fn guarded_environment(state: &AppState, connection_string: &str) -> Result<Option<String>, String> {
+    let Some(session) = state.sessions.get_session(connection_string) else {
+        return Ok(None);
+    };
+    let guard: Option<(String, bool)> = sqlx::query_as(
+        "SELECT COALESCE(c.environment, 'development'), COALESCE(g.confirm_destructive, 0) FROM connections c \
+         LEFT JOIN environment_guards g ON g.environment = COALESCE(c.environment, 'development') WHERE c.id = ?"
+    )
+    .bind(session.connection_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| format!("Failed to read environment guard: {}", e))?;
+    Ok(guard.filter(|(_, confirm)| *confirm).map(|(environment, _)| environment))
+}
+
+fn flag_statements(query: &str, support_backslash_escape: bool) -> Vec<FlaggedStatement> {
+    split_sql_statements(query, support_backslash_escape)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, statement)| {
+            classify_statement(&statement, support_backslash_escape).map(|risk| FlaggedStatement {
+                index,
+                statement,
+                risk: risk.as_str().to_string(),
+                reason: risk.describe().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Refuse a query with flagged statements on a guarded connection unless `acknowledgement`
+/// is the token `review_query` issued for this exact query
+pub async fn ensure_acknowledged(
+    state: &AppState,
+    connection_string: &str,
+    pool: &PoolWrapper,
+    query: &str,
+    acknowledgement: Option<&str>,
+) -> Result<(), String> {
+    let Some(environment) = guarded_environment(state, connection_string).await? else {
+        return Ok(());
+    };
+    let flagged = flag_statements(query, matches!(pool, PoolWrapper::Mysql(_)));
+    let Some(first) = flagged.first() else {
+        return Ok(());
+    };
+    if acknowledgement.is_some_and(|token| state.sessions.take_confirmation(token, connection_string, query)) {
+        log::warn!("Running {} acknowledged destructive statement(s) on a {} connection", flagged.len(), environment);
+        return Ok(());
+    }
+    let preview: String = first.statement.chars().take(80).collect();
+    Err(format!(
+        "Refusing to run on a {} connection without confirmation: {} ({})",
+        environment, preview, first.reason
+    ))
+}
+
+#[command]
+pub async fn review_query(state: State<'_, AppState>, connection_string: String, query: String) -> Result<QueryReview, String> {
+    let Some(environment) = guarded_environment(&state, &connection_string).await? else {
+        return Ok(QueryReview { environment: None, statements: Vec::new(), acknowledgement: None });
+    };
+    let pool = crate::db::get_connection(&state, &connection_string).await?;
+    let statements = flag_statements(&query, matches!(pool, PoolWrapper::Mysql(_)));
+    let acknowledgement = (!statements.is_empty()).then(|| state.sessions.create_confirmation(&connection_string, &query));
+    Ok(QueryReview { environment: Some(environment), statements, acknowledgement })
+}
+
+#[command]
+pub async fn list_environment_guards(state: State<'_, AppState>) -> Result<Vec<EnvironmentGuard>, String> {
+    let rows: Vec<(String, bool)> = sqlx::query_as("SELECT environment, confirm_destructive FROM environment_guards")
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| format!("Failed to list environment guards: {}", e))?;
+    let mut guards: Vec<EnvironmentGuard> = rows
+        .into_iter()
+        .map(|(environment, confirm_destructive)| EnvironmentGuard { environment, confirm_destructive })
+        .collect();
+    guards.sort_by_key(|g| ENVIRONMENTS.iter().position(|e| *e == g.environment));
+    Ok(guards)
+}
+
+#[command]
+pub async fn set_environment_guard(
+    state: State<'_, AppState>,
+    environment: String,
+    confirm_destructive: bool,
+) -> Result<(), String> {
+    if !ENVIRONMENTS.contains(&environment.as_str()) {
+        return Err(format!("Unknown environment: {}", environment));
+    }
+    sqlx::query(
+        "INSERT INTO environment_guards (environment, confirm_destructive) VALUES (?, ?) \
+         ON CONFLICT(environment) DO UPDATE SET confirm_destructive = excluded.confirm_destructive"
+    )
+    .bind(&environment)
+    .bind(confirm_destructive)
+    .execute(&state.db)
+    .await
+    .map_err(|e| format!("Failed to update environment guard: {}", e))?;
+    Ok(())
+}
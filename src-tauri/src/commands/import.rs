@@ -0,0 +1,796 @@
+use crate::commands::binding::{bind_args, bind_values};
+use crate::commands::crud::{get_column_types, get_postgres_cast};
+use crate::commands::db_ops::{create_table, get_table_schema};
+use crate::db::{AppState, PoolWrapper};
+use crate::models::ColumnSchema;
+use crate::utils::escape_identifier;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use tauri::State;
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+const MAX_REPORTED_ERRORS: usize = 1000;
+const INFERENCE_SAMPLE_ROWS: usize = 1000;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CsvImportOptions {
+    pub delimiter: Option<String>,   // Single character, defaults to ","
+    pub quote: Option<String>,       // Single character, defaults to '"'
+    pub has_header: Option<bool>,    // Defaults to true
+    pub encoding: Option<String>,    // WHATWG label, e.g. "utf-8", "windows-1252", "utf-16le"
+    pub empty_as_null: Option<bool>, // Defaults to true
+    pub batch_size: Option<usize>,
+    #[serde(default)]
+    pub create_table: bool, // Create `table_name` from inferred types instead of importing into an existing table
+    pub column_mapping: Option<HashMap<String, String>>, // CSV header -> table column; unmapped headers are skipped
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct JsonImportOptions {
+    pub format: Option<String>, // "json" (array of objects) or "ndjson"; detected from the content when omitted
+    pub column_mapping: Option<HashMap<String, String>>, // JSON key -> table column; unmapped keys are skipped
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportResult {
+    pub inserted_rows: u64,
+    pub failed_rows: u64,
+    pub errors: Vec<ImportRowError>, // Capped at MAX_REPORTED_ERRORS; failed_rows is the full count
+    pub imported_columns: Vec<String>,
+    pub skipped_columns: Vec<String>,
+    pub created_table: bool,
+}
+
+impl ImportResult {
+    pub(crate) fn push_error(&mut self, line: u64, message: String) {
+        self.failed_rows += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(ImportRowError { line, message });
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct CsvPreview {
+    pub headers: Vec<String>,
+    pub inferred_types: Vec<String>, // One of "integer", "float", "boolean", "date", "datetime", "text"
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Parsed CSV records with their source line, or the parse error for that line
+type CsvRecords = Vec<(u64, Result<Vec<String>, String>)>;
+
+fn single_byte(value: &Option<String>, default: u8, name: &str) -> Result<u8, String> {
+    match value.as_deref() {
+        None | Some("") => Ok(default),
+        Some("\\t") => Ok(b'\t'),
+        Some(s) if s.len() == 1 => Ok(s.as_bytes()[0]),
+        Some(s) => Err(format!("{} must be a single ASCII character, got '{}'", name, s)),
+    }
+}
+
+/// Decodes a byte stream from a text encoding into UTF-8 as it is read, so a file is never held in memory whole
+struct DecodeReader<R> {
+    inner: R,
+    encoding: &'static encoding_rs::Encoding,
+    decoder: encoding_rs::Decoder,
+    decoded: String,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    fn new(inner: R, encoding: &'static encoding_rs::Encoding) -> Self {
+        // Like Encoding::decode, a byte order mark overrides the encoding and is removed
+        Self { inner, encoding, decoder: encoding.new_decoder(), decoded: String::new(), pos: 0, done: false }
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut chunk = [0u8; 8192];
+        while self.pos == self.decoded.len() && !self.done {
+            let n = self.inner.read(&mut chunk)?;
+            let last = n == 0;
+            self.decoded.clear();
+            self.pos = 0;
+            let mut src = &chunk[..n];
+            loop {
+                let needed = self.decoder.max_utf8_buffer_length_without_replacement(src.len()).unwrap_or(src.len() * 3 + 16);
+                self.decoded.reserve(needed);
+                let (result, read) = self.decoder.decode_to_string_without_replacement(src, &mut self.decoded, last);
+                src = &src[read..];
+                match result {
+                    encoding_rs::DecoderResult::InputEmpty => break,
+                    encoding_rs::DecoderResult::OutputFull => continue,
+                    encoding_rs::DecoderResult::Malformed(..) => {
+                        self.done = true;
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("File is not valid {}", self.encoding.name()),
+                        ));
+                    }
+                }
+            }
+            self.done = last;
+        }
+        let n = buf.len().min(self.decoded.len() - self.pos);
+        buf[..n].copy_from_slice(&self.decoded.as_bytes()[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Stream the file, decoding it from the requested encoding, and parse up to `max_records` records
+/// (all when None) with their line numbers
+fn read_csv(file_path: &str, options: &CsvImportOptions, max_records: Option<usize>) -> Result<(Vec<String>, CsvRecords), String> {
+    let file = File::open(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let label = options.encoding.as_deref().unwrap_or("utf-8");
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| format!("Unsupported encoding: {}", label))?;
+
+    let has_header = options.has_header.unwrap_or(true);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(single_byte(&options.delimiter, b',', "Delimiter")?)
+        .quote(single_byte(&options.quote, b'"', "Quote")?)
+        .has_headers(has_header)
+        .flexible(true)
+        .from_reader(DecodeReader::new(BufReader::new(file), encoding));
+
+    let mut records = Vec::new();
+    for record in reader.records().take(max_records.unwrap_or(usize::MAX)) {
+        match record {
+            Ok(r) => {
+                let line = r.position().map(|p| p.line()).unwrap_or(0);
+                records.push((line, Ok(r.iter().map(|v| v.to_string()).collect::<Vec<String>>())));
+            }
+            // Undecodable or unreadable input fails the whole file rather than one record
+            Err(e) if e.is_io_error() => {
+                let undecodable = matches!(e.kind(), csv::ErrorKind::Io(io) if io.kind() == std::io::ErrorKind::InvalidData);
+                return Err(if undecodable { e.to_string() } else { format!("Failed to read file: {}", e) });
+            }
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or(0);
+                records.push((line, Err(format!("Malformed CSV: {}", e))));
+            }
+        }
+    }
+
+    let headers: Vec<String> = if has_header {
+        reader
+            .headers()
+            .map_err(|e| format!("Failed to read header: {}", e))?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect()
+    } else {
+        let width = records.iter().filter_map(|(_, r)| r.as_ref().ok()).map(|r| r.len()).max().unwrap_or(0);
+        (1..=width).map(|i| format!("column_{}", i)).collect()
+    };
+
+    Ok((headers, records))
+}
+
+fn infer_value_type(value: &str) -> &'static str {
+    if value.parse::<i64>().is_ok() {
+        "integer"
+    } else if value.parse::<f64>().is_ok() {
+        "float"
+    } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        "boolean"
+    } else if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        "date"
+    } else if chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").is_ok()
+        || chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+        || chrono::DateTime::parse_from_rfc3339(value).is_ok()
+    {
+        "datetime"
+    } else {
+        "text"
+    }
+}
+
+/// Narrowest type that fits every non-empty sampled value of each column
+fn infer_column_types(width: usize, rows: &[&Vec<String>]) -> Vec<String> {
+    (0..width)
+        .map(|i| {
+            let mut inferred: Option<&'static str> = None;
+            for row in rows.iter().take(INFERENCE_SAMPLE_ROWS) {
+                let value = row.get(i).map(|v| v.trim()).unwrap_or("");
+                if value.is_empty() {
+                    continue;
+                }
+                let t = infer_value_type(value);
+                inferred = Some(match (inferred, t) {
+                    (None, t) => t,
+                    (Some(a), b) if a == b => a,
+                    (Some("integer"), "float") | (Some("float"), "integer") => "float",
+                    (Some("date"), "datetime") | (Some("datetime"), "date") => "datetime",
+                    _ => "text",
+                });
+            }
+            inferred.unwrap_or("text").to_string()
+        })
+        .collect()
+}
+
+/// Map an inferred type onto the column type used by each dialect
+pub(crate) fn sql_type_for(inferred: &str, db_type: &str) -> String {
+    match (inferred, db_type) {
+        ("integer", "sqlite") => "INTEGER",
+        ("integer", _) => "BIGINT",
+        ("float", "postgres") => "DOUBLE PRECISION",
+        ("float", "mysql") => "DOUBLE",
+        ("float", _) => "REAL",
+        ("boolean", _) => "BOOLEAN",
+        ("date", _) => "DATE",
+        ("datetime", "postgres") => "TIMESTAMP",
+        ("datetime", _) => "DATETIME",
+        ("json", "postgres") => "JSONB",
+        ("json", "mysql") => "JSON",
+        _ => "TEXT",
+    }
+    .to_string()
+}
+
+// Inserts each row inside its own savepoint so a bad row is reported without aborting the batch
+macro_rules! insert_rows_in_batches {
+    ($pool:expr, $db_type:expr, $columns:expr, $column_types:expr, $sql:expr, $rows:expr, $batch_size:expr, $result:expr) => {{
+        for chunk in $rows.chunks($batch_size) {
+            let mut tx = $pool.begin().await.map_err(|e| e.to_string())?;
+            for (line, values) in chunk {
+                let args: Vec<(String, Option<String>)> = $columns.iter().cloned().zip(values.iter().cloned()).collect();
+                let values = match bind_args($db_type, &args, Some($column_types)) {
+                    Ok(values) => values,
+                    Err(message) => {
+                        $result.push_error(*line, message);
+                        continue;
+                    }
+                };
+                let mut savepoint = sqlx::Connection::begin(&mut *tx).await.map_err(|e| e.to_string())?;
+                let query = bind_values!(sqlx::query($sql), values);
+                match query.execute(&mut *savepoint).await {
+                    Ok(_) => {
+                        savepoint.commit().await.map_err(|e| e.to_string())?;
+                        $result.inserted_rows += 1;
+                    }
+                    Err(e) => {
+                        savepoint.rollback().await.map_err(|e| e.to_string())?;
+                        $result.push_error(*line, e.to_string());
+                    }
+                }
+            }
+            tx.commit().await.map_err(|e: sqlx::Error| e.to_string())?;
+        }
+    }};
+}
+
+/// Insert `rows` (line number + values in `columns` order) into `table_name` in batched transactions
+pub(crate) async fn insert_rows(
+    pool: &PoolWrapper,
+    table_name: &str,
+    columns: &[String],
+    rows: &[(u64, Vec<Option<String>>)],
+    batch_size: usize,
+    result: &mut ImportResult,
+) -> Result<(), String> {
+    let db_type = match pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+
+    // Values are bound typed, so booleans, numbers and dates reach strict columns as such
+    let column_types = get_column_types(pool, table_name).await;
+
+    let placeholders: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            if db_type == "postgres" {
+                format!("${}{}", i + 1, get_postgres_cast(col, Some(&column_types)))
+            } else {
+                "?".to_string()
+            }
+        })
+        .collect();
+    let quoted_cols: Vec<String> = columns.iter().map(|c| escape_identifier(c, db_type)).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        escape_identifier(table_name, db_type),
+        quoted_cols.join(", "),
+        placeholders.join(", ")
+    );
+
+    let batch_size = batch_size.max(1);
+    match pool {
+        PoolWrapper::Mysql(p) => insert_rows_in_batches!(p, db_type, columns, &column_types, &sql, rows, batch_size, result),
+        PoolWrapper::Postgres(p) => insert_rows_in_batches!(p, db_type, columns, &column_types, &sql, rows, batch_size, result),
+        PoolWrapper::Sqlite(p) => insert_rows_in_batches!(p, db_type, columns, &column_types, &sql, rows, batch_size, result),
+    }
+    Ok(())
+}
+
+/// Parse the first rows of a CSV file and infer column types, for building the import mapping
+#[tauri::command]
+pub async fn preview_csv(
+    file_path: String,
+    options: CsvImportOptions,
+    limit: Option<usize>,
+) -> Result<CsvPreview, String> {
+    let limit = limit.unwrap_or(100);
+    let (headers, records) = read_csv(&file_path, &options, Some(limit.max(INFERENCE_SAMPLE_ROWS)))?;
+    let parsed: Vec<&Vec<String>> = records.iter().filter_map(|(_, r)| r.as_ref().ok()).collect();
+
+    Ok(CsvPreview {
+        inferred_types: infer_column_types(headers.len(), &parsed),
+        rows: parsed.iter().take(limit).map(|r| (*r).clone()).collect(),
+        headers,
+    })
+}
+
+#[tauri::command]
+pub async fn import_csv(
+    state: State<'_, AppState>,
+    connection_string: String,
+    file_path: String,
+    table_name: String,
+    options: CsvImportOptions,
+) -> Result<ImportResult, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+
+    let (headers, records) = read_csv(&file_path, &options, None)?;
+    let mut result = ImportResult::default();
+
+    if options.create_table {
+        let parsed: Vec<&Vec<String>> = records.iter().filter_map(|(_, r)| r.as_ref().ok()).collect();
+        let types = infer_column_types(headers.len(), &parsed);
+        let columns: Vec<ColumnSchema> = headers
+            .iter()
+            .zip(&types)
+            .map(|(h, t)| ColumnSchema {
+                name: options.column_mapping.as_ref().and_then(|m| m.get(h)).cloned().unwrap_or_else(|| h.clone()),
+                type_name: sql_type_for(t, db_type),
+                is_nullable: true,
+                ..Default::default()
+            })
+            .collect();
+        create_table(state.clone(), connection_string.clone(), table_name.clone(), columns, vec![]).await?;
+        result.created_table = true;
+    }
+
+    let table_columns: Vec<String> = get_table_schema(state.clone(), connection_string.clone(), table_name.clone())
+        .await?
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    if table_columns.is_empty() {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    // Resolve which CSV field feeds which table column
+    let mut targets: Vec<(usize, String)> = Vec::new();
+    for (i, header) in headers.iter().enumerate() {
+        let target = match &options.column_mapping {
+            Some(mapping) => mapping.get(header).cloned(),
+            None if options.has_header.unwrap_or(true) || options.create_table => {
+                table_columns.iter().find(|c| c.eq_ignore_ascii_case(header)).cloned()
+            }
+            // Headerless files without a mapping are matched to table columns by position
+            None => table_columns.get(i).cloned(),
+        };
+        match target {
+            Some(col) if table_columns.contains(&col) && !targets.iter().any(|(_, c)| c == &col) => targets.push((i, col)),
+            _ => result.skipped_columns.push(header.clone()),
+        }
+    }
+    if targets.is_empty() {
+        return Err("No CSV columns match columns of the target table".to_string());
+    }
+
+    let empty_as_null = options.empty_as_null.unwrap_or(true);
+    let mut rows = Vec::with_capacity(records.len());
+    for (line, record) in records {
+        match record {
+            Ok(fields) => rows.push((
+                line,
+                targets
+                    .iter()
+                    .map(|(i, _)| {
+                        let value = fields.get(*i).cloned().unwrap_or_default();
+                        if empty_as_null && value.is_empty() { None } else { Some(value) }
+                    })
+                    .collect(),
+            )),
+            Err(message) => result.push_error(line, message),
+        }
+    }
+
+    result.imported_columns = targets.into_iter().map(|(_, c)| c).collect();
+    insert_rows(
+        &pool,
+        &table_name,
+        &result.imported_columns.clone(),
+        &rows,
+        options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        &mut result,
+    )
+    .await?;
+
+    Ok(result)
+}
+
+/// Source line and values of one row to insert
+type ImportRow = (u64, Vec<Option<String>>);
+
+/// One JSON record with its line (NDJSON) or element number (JSON array), or the parse error
+type JsonRecord = (u64, Result<serde_json::Map<String, Value>, String>);
+
+fn as_object(value: Value) -> Result<serde_json::Map<String, Value>, String> {
+    match value {
+        Value::Object(map) => Ok(map),
+        other => Err(format!("Expected a JSON object, found: {}", other)),
+    }
+}
+
+/// Hands the elements of a JSON array to a callback one at a time instead of collecting them
+struct JsonArrayVisitor<F>(F);
+
+impl<'de, F: FnMut(JsonRecord) -> bool> serde::de::Visitor<'de> for JsonArrayVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a JSON array of objects")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        let mut n = 0;
+        while let Some(value) = seq.next_element::<Value>()? {
+            n += 1;
+            if !(self.0)((n, as_object(value))) {
+                return Err(serde::de::Error::custom("import cancelled"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stream a JSON array of objects or an NDJSON file, passing each record to `emit` until it returns false
+fn read_json(file_path: &str, format: Option<&str>, mut emit: impl FnMut(JsonRecord) -> bool) -> Result<(), String> {
+    let file = File::open(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut reader = BufReader::new(file);
+
+    // Skip a byte order mark and detect the format from the first character
+    let buf = reader.fill_buf().map_err(|e| format!("Failed to read file: {}", e))?;
+    let bom = if buf.starts_with(b"\xEF\xBB\xBF") { 3 } else { 0 };
+    let is_array = buf[bom..].iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+    reader.consume(bom);
+
+    let ndjson = match format {
+        Some("json") => false,
+        Some("ndjson") | Some("jsonl") => true,
+        Some(other) => return Err(format!("Unsupported JSON format: {}", other)),
+        None => !is_array,
+    };
+
+    if ndjson {
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read file: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<Value>(&line).map_err(|e| e.to_string()).and_then(as_object);
+            if !emit((i as u64 + 1, record)) {
+                break;
+            }
+        }
+        Ok(())
+    } else {
+        let mut de = serde_json::Deserializer::from_reader(reader);
+        serde::Deserializer::deserialize_seq(&mut de, JsonArrayVisitor(emit))
+            .and_then(|_| de.end())
+            .map_err(|e| format!("Invalid JSON: {}", e))
+    }
+}
+
+/// Convert a JSON value into the text bound for a column of type `type_name`.
+/// Nested objects and arrays are serialized, but only into JSON or text columns. Strings are
+/// bound as they are, so a string holding a JSON document fills a JSON column with that document.
+fn coerce_json_value(value: &Value, column: &str, type_name: &str, db_type: &str) -> Result<Option<String>, String> {
+    let t = type_name.to_lowercase();
+    let is_json = t.contains("json");
+    let is_text = t.contains("char") || t.contains("text") || t.contains("clob") || t.is_empty();
+    let is_integer = (t.contains("int") && !t.contains("interval") && !t.contains("point")) || t == "bit";
+
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(b) => {
+            // SQLite has no boolean type and MySQL booleans are TINYINT(1)
+            if (t.contains("bool") && db_type == "sqlite") || is_integer {
+                Ok(Some(if *b { "1" } else { "0" }.to_string()))
+            } else {
+                Ok(Some(b.to_string()))
+            }
+        }
+        Value::Number(n) => {
+            if is_integer && !n.is_i64() && !n.is_u64() {
+                return Err(format!("Column '{}' expects an integer, got {}", column, n));
+            }
+            Ok(Some(n.to_string()))
+        }
+        Value::String(s) => Ok(Some(s.clone())),
+        Value::Array(_) | Value::Object(_) => {
+            if is_json || is_text {
+                Ok(Some(value.to_string()))
+            } else {
+                Err(format!("Cannot store a nested value in column '{}' of type {}", column, type_name))
+            }
+        }
+    }
+}
+
+/// Import a JSON array of objects or an NDJSON file. Keys map to columns by `column_mapping`
+/// or by (case-insensitive) name; values are coerced to the table's column types.
+#[tauri::command]
+pub async fn import_json(
+    state: State<'_, AppState>,
+    connection_string: String,
+    file_path: String,
+    table_name: String,
+    options: Option<JsonImportOptions>,
+) -> Result<ImportResult, String> {
+    crate::db::ensure_writable(&state, &connection_string)?;
+    let options = options.unwrap_or_default();
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+
+    let schema = crate::commands::schema_diff::merge_columns(
+        get_table_schema(state.clone(), connection_string.clone(), table_name.clone()).await?,
+    );
+    if schema.is_empty() {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    // The file is parsed on a blocking thread and its records inserted as batches fill up
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<JsonRecord>(batch_size);
+    let format = options.format.clone();
+    let reader = tokio::task::spawn_blocking(move || {
+        read_json(&file_path, format.as_deref(), |record| tx.blocking_send(record).is_ok())
+    });
+
+    let mut result = ImportResult::default();
+    let mut key_targets: HashMap<String, Option<usize>> = HashMap::new();
+    let mut last_line = 0;
+
+    // Rows are grouped by the set of columns they provide, so absent keys fall back to column defaults
+    let mut groups: BTreeMap<Vec<usize>, Vec<ImportRow>> = BTreeMap::new();
+    let mut used: Vec<usize> = Vec::new();
+    while let Some((line, record)) = rx.recv().await {
+        last_line = line;
+        let object = match record {
+            Ok(object) => object,
+            Err(message) => {
+                result.push_error(line, message);
+                continue;
+            }
+        };
+
+        let mut fields: Vec<(usize, Option<String>)> = Vec::new();
+        let mut row_error = None;
+        for (key, value) in &object {
+            let target = *key_targets.entry(key.clone()).or_insert_with(|| {
+                let column = match &options.column_mapping {
+                    Some(mapping) => mapping.get(key).cloned(),
+                    None => Some(key.clone()),
+                };
+                column.and_then(|c| {
+                    schema
+                        .iter()
+                        .position(|s| s.name == c)
+                        .or_else(|| schema.iter().position(|s| s.name.eq_ignore_ascii_case(&c)))
+                })
+            });
+            let Some(idx) = target else { continue };
+            if fields.iter().any(|(i, _)| *i == idx) {
+                continue;
+            }
+            let column = &schema[idx];
+            match coerce_json_value(value, &column.name, &column.type_name, db_type) {
+                Ok(v) => fields.push((idx, v)),
+                Err(e) => {
+                    row_error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        if let Some(message) = row_error {
+            result.push_error(line, message);
+            continue;
+        }
+        if fields.is_empty() {
+            result.push_error(line, "Object has no keys matching columns of the target table".to_string());
+            continue;
+        }
+        fields.sort_by_key(|(i, _)| *i);
+        let (indexes, values): (Vec<usize>, Vec<Option<String>>) = fields.into_iter().unzip();
+        for i in &indexes {
+            if !used.contains(i) {
+                used.push(*i);
+            }
+        }
+        let group = groups.entry(indexes.clone()).or_default();
+        group.push((line, values));
+        if group.len() >= batch_size {
+            let rows = std::mem::take(group);
+            let columns: Vec<String> = indexes.iter().map(|i| schema[*i].name.clone()).collect();
+            insert_rows(&pool, &table_name, &columns, &rows, batch_size, &mut result).await?;
+        }
+    }
+
+    // A file that cannot be read at all fails the import; invalid JSON after some records only fails the rest
+    if let Err(message) = reader.await.map_err(|e| e.to_string())? {
+        if last_line == 0 {
+            return Err(message);
+        }
+        result.push_error(last_line + 1, message);
+    }
+
+    for (indexes, rows) in groups.into_iter().filter(|(_, rows)| !rows.is_empty()) {
+        let columns: Vec<String> = indexes.iter().map(|i| schema[*i].name.clone()).collect();
+        insert_rows(&pool, &table_name, &columns, &rows, batch_size, &mut result).await?;
+    }
+
+    used.sort_unstable();
+    result.imported_columns = used.iter().map(|i| schema[*i].name.clone()).collect();
+    result.skipped_columns = key_targets.iter().filter(|(_, t)| t.is_none()).map(|(k, _)| k.clone()).collect();
+    result.skipped_columns.sort();
+
+    result.errors.sort_by_key(|e| e.line);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_file(contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("import-{}", uuid::Uuid::new_v4()));
+        File::create(&path).unwrap().write_all(contents).unwrap();
+        path
+    }
+
+    fn csv_options(encoding: &str) -> CsvImportOptions {
+        CsvImportOptions {
+            delimiter: None,
+            quote: None,
+            has_header: None,
+            encoding: Some(encoding.to_string()),
+            empty_as_null: None,
+            batch_size: None,
+            create_table: false,
+            column_mapping: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn imports_an_inferred_boolean_column_typed() {
+        let path = temp_file(b"name,active\nada,true\nbob,FALSE\ncy,maybe\n");
+        let (headers, records) = read_csv(path.to_str().unwrap(), &csv_options("utf-8"), None).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let parsed: Vec<&Vec<String>> = records.iter().filter_map(|(_, r)| r.as_ref().ok()).collect();
+        let types = infer_column_types(headers.len(), &parsed[..2]);
+        assert_eq!(types, vec!["text", "boolean"]);
+
+        // The table import_csv creates with `create_table`
+        let options = "sqlite::memory:".parse::<sqlx::sqlite::SqliteConnectOptions>().unwrap();
+        let db = sqlx::sqlite::SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        let columns: Vec<ColumnSchema> = headers
+            .iter()
+            .zip(&types)
+            .map(|(h, t)| ColumnSchema { name: h.clone(), type_name: sql_type_for(t, "sqlite"), is_nullable: true, ..Default::default() })
+            .collect();
+        let create = crate::commands::db_ops::generate_create_table_sql("people", &columns, &[], "sqlite");
+        sqlx::query(&create).execute(&db).await.unwrap();
+
+        let rows: Vec<ImportRow> = records
+            .into_iter()
+            .map(|(line, r)| (line, r.unwrap().into_iter().map(Some).collect()))
+            .collect();
+        let mut result = ImportResult::default();
+        insert_rows(&PoolWrapper::Sqlite(db.clone()), "people", &headers, &rows, 2, &mut result).await.unwrap();
+        assert_eq!(result.inserted_rows, 3);
+
+        let stored: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT name, CAST(active AS TEXT), typeof(active) FROM people ORDER BY rowid")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(stored[0], ("ada".to_string(), "1".to_string(), "integer".to_string()));
+        assert_eq!(stored[1], ("bob".to_string(), "0".to_string(), "integer".to_string()));
+        // SQLite keeps what does not parse as text, like any other SQLite column
+        assert_eq!(stored[2], ("cy".to_string(), "maybe".to_string(), "text".to_string()));
+    }
+
+    #[test]
+    fn json_strings_are_not_encoded_again() {
+        let doc = Value::String("{\"a\":1}".to_string());
+        assert_eq!(coerce_json_value(&doc, "data", "json", "mysql").unwrap().as_deref(), Some("{\"a\":1}"));
+        let nested = serde_json::json!({"a": [1, 2]});
+        assert_eq!(coerce_json_value(&nested, "data", "jsonb", "postgres").unwrap().as_deref(), Some("{\"a\":[1,2]}"));
+        assert_eq!(coerce_json_value(&Value::Bool(true), "data", "json", "mysql").unwrap().as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn csv_is_decoded_while_streaming_and_limited() {
+        // Each row is longer than the decoder's read chunk, so characters straddle chunk boundaries
+        let mut text = String::from("name,note\n");
+        for i in 0..50 {
+            text.push_str(&format!("row{},{}\n", i, "é".repeat(400)));
+        }
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(&text);
+        let path = temp_file(&bytes);
+
+        let (headers, records) = read_csv(path.to_str().unwrap(), &csv_options("windows-1252"), None).unwrap();
+        assert_eq!(headers, vec!["name", "note"]);
+        assert_eq!(records.len(), 50);
+        assert_eq!(records[49].1.as_ref().unwrap()[1], "é".repeat(400));
+
+        let (_, records) = read_csv(path.to_str().unwrap(), &csv_options("windows-1252"), Some(3)).unwrap();
+        assert_eq!(records.len(), 3);
+
+        let err = read_csv(path.to_str().unwrap(), &csv_options("utf-8"), None).unwrap_err();
+        assert_eq!(err, "File is not valid UTF-8");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn json_records_are_streamed_with_their_positions() {
+        let collect = |contents: &[u8], format: Option<&str>| {
+            let path = temp_file(contents);
+            let mut records = Vec::new();
+            let outcome = read_json(path.to_str().unwrap(), format, |r| {
+                records.push((r.0, r.1.is_ok()));
+                true
+            });
+            let _ = std::fs::remove_file(&path);
+            (outcome, records)
+        };
+
+        let (outcome, records) = collect(b"\xEF\xBB\xBF [{\"a\": 1}, 2, {\"b\": 3}]", None);
+        assert_eq!(outcome, Ok(()));
+        assert_eq!(records, vec![(1, true), (2, false), (3, true)]);
+
+        let (outcome, records) = collect(b"{\"a\": 1}\n\n{\"a\": \n{\"a\": 3}\n", None);
+        assert_eq!(outcome, Ok(()));
+        assert_eq!(records, vec![(1, true), (3, false), (4, true)]);
+
+        // Records before a syntax error are still delivered
+        let (outcome, records) = collect(b"[{\"a\": 1}, {\"a\": ]", Some("json"));
+        assert!(outcome.unwrap_err().starts_with("Invalid JSON"));
+        assert_eq!(records, vec![(1, true)]);
+    }
+}
@@ -1,10 +1,20 @@
+pub mod binding;
+pub mod changelog;
 pub mod connection;
 pub mod credential;
 pub mod crud;
+pub mod data_compare;
 pub mod db_ops;
+pub mod drafts;
+pub mod export;
 pub mod filter;
+pub mod guard;
+pub mod import;
 pub mod saved;
+pub mod schema_diff;
 pub mod sidebar;
+pub mod sqlite;
 pub mod tag;
+pub mod vault;
 pub mod window;
 pub mod session;
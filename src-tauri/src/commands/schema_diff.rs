@@ -0,0 +1,485 @@
+use crate::commands::db_ops::{
+    column_definition_sql, generate_create_table_sql, get_table_indexes, get_table_schema, get_tables, ForeignKeyInput,
+};
+use crate::db::{AppState, PoolWrapper};
+use crate::models::{ColumnSchema, IndexSchema};
+use crate::utils::escape_identifier;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::State;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ColumnDiff {
+    pub column: String,
+    pub source: ColumnSchema,
+    pub target: ColumnSchema,
+    pub differences: Vec<String>, // "type", "nullable", "default", "primary_key", "unique", "auto_increment", "foreign_key"
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct TableDiff {
+    pub table_name: String,
+    pub missing_columns: Vec<ColumnSchema>, // In source, not in target
+    pub extra_columns: Vec<ColumnSchema>,   // In target, not in source
+    pub changed_columns: Vec<ColumnDiff>,
+    pub missing_indexes: Vec<IndexSchema>,
+    pub extra_indexes: Vec<IndexSchema>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SchemaDiff {
+    pub missing_tables: Vec<String>,
+    pub extra_tables: Vec<String>,
+    pub changed_tables: Vec<TableDiff>,
+    pub migration_sql: String, // Brings the target in line with the source, in the target's dialect
+}
+
+struct TableSnapshot {
+    columns: Vec<ColumnSchema>,
+    indexes: Vec<IndexSchema>,
+}
+
+/// Collapse duplicate rows for the same column (the Postgres constraint joins can return one per constraint)
+pub(crate) fn merge_columns(columns: Vec<ColumnSchema>) -> Vec<ColumnSchema> {
+    let mut merged: Vec<ColumnSchema> = Vec::new();
+    for col in columns {
+        if let Some(existing) = merged.iter_mut().find(|c| c.name == col.name) {
+            existing.is_primary_key |= col.is_primary_key;
+            existing.is_unique |= col.is_unique;
+            existing.is_auto_increment |= col.is_auto_increment;
+            if existing.foreign_key.is_none() {
+                existing.foreign_key = col.foreign_key;
+            }
+        } else {
+            merged.push(col);
+        }
+    }
+    merged
+}
+
+async fn snapshot_schema(
+    state: &State<'_, AppState>,
+    connection_string: &str,
+    tables: Option<&[String]>,
+) -> Result<BTreeMap<String, TableSnapshot>, String> {
+    let all_tables = get_tables(state.clone(), connection_string.to_string()).await?;
+
+    let mut snapshot = BTreeMap::new();
+    for table in all_tables {
+        if table.starts_with("sqlite_") || tables.is_some_and(|t| !t.contains(&table)) {
+            continue;
+        }
+        let columns = get_table_schema(state.clone(), connection_string.to_string(), table.clone()).await?;
+        let indexes = get_table_indexes(state.clone(), connection_string.to_string(), table.clone()).await?;
+        snapshot.insert(table, TableSnapshot { columns: merge_columns(columns), indexes });
+    }
+    Ok(snapshot)
+}
+
+fn foreign_key_target(col: &ColumnSchema) -> Option<(&str, &str)> {
+    col.foreign_key
+        .as_ref()
+        .map(|fk| (fk.referenced_table.as_str(), fk.referenced_column.as_str()))
+}
+
+fn column_differences(source: &ColumnSchema, target: &ColumnSchema) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if !source.type_name.eq_ignore_ascii_case(&target.type_name) {
+        diffs.push("type".to_string());
+    }
+    if source.is_nullable != target.is_nullable {
+        diffs.push("nullable".to_string());
+    }
+    if source.default_value != target.default_value {
+        diffs.push("default".to_string());
+    }
+    if source.is_primary_key != target.is_primary_key {
+        diffs.push("primary_key".to_string());
+    }
+    if source.is_unique != target.is_unique {
+        diffs.push("unique".to_string());
+    }
+    if source.is_auto_increment != target.is_auto_increment {
+        diffs.push("auto_increment".to_string());
+    }
+    if foreign_key_target(source) != foreign_key_target(target) {
+        diffs.push("foreign_key".to_string());
+    }
+    diffs
+}
+
+fn diff_table(table_name: &str, source: &TableSnapshot, target: &TableSnapshot) -> TableDiff {
+    let mut diff = TableDiff {
+        table_name: table_name.to_string(),
+        ..Default::default()
+    };
+
+    for src_col in &source.columns {
+        match target.columns.iter().find(|c| c.name == src_col.name) {
+            None => diff.missing_columns.push(src_col.clone()),
+            Some(tgt_col) => {
+                let differences = column_differences(src_col, tgt_col);
+                if !differences.is_empty() {
+                    diff.changed_columns.push(ColumnDiff {
+                        column: src_col.name.clone(),
+                        source: src_col.clone(),
+                        target: tgt_col.clone(),
+                        differences,
+                    });
+                }
+            }
+        }
+    }
+    diff.extra_columns = target
+        .columns
+        .iter()
+        .filter(|c| !source.columns.iter().any(|s| s.name == c.name))
+        .cloned()
+        .collect();
+
+    // An index with the same name but different definition is reported as dropped and re-created
+    diff.missing_indexes = source
+        .indexes
+        .iter()
+        .filter(|idx| !target.indexes.contains(idx))
+        .cloned()
+        .collect();
+    diff.extra_indexes = target
+        .indexes
+        .iter()
+        .filter(|idx| !source.indexes.contains(idx))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+fn is_table_diff_empty(diff: &TableDiff) -> bool {
+    diff.missing_columns.is_empty()
+        && diff.extra_columns.is_empty()
+        && diff.changed_columns.is_empty()
+        && diff.missing_indexes.is_empty()
+        && diff.extra_indexes.is_empty()
+}
+
+fn create_index_sql(table: &str, idx: &IndexSchema, db_type: &str) -> String {
+    let cols: Vec<String> = idx.columns.iter().map(|c| escape_identifier(c, db_type)).collect();
+    format!(
+        "CREATE {}INDEX {} ON {} ({});",
+        if idx.is_unique { "UNIQUE " } else { "" },
+        escape_identifier(&idx.name, db_type),
+        escape_identifier(table, db_type),
+        cols.join(", ")
+    )
+}
+
+fn drop_index_sql(table: &str, idx: &IndexSchema, db_type: &str) -> String {
+    match db_type {
+        "mysql" => format!("DROP INDEX {} ON {};", escape_identifier(&idx.name, db_type), escape_identifier(table, db_type)),
+        _ => format!("DROP INDEX {};", escape_identifier(&idx.name, db_type)),
+    }
+}
+
+/// A referential action reported by the source database, or NO ACTION when unknown
+fn referential_action(rule: &Option<String>) -> String {
+    match rule.as_deref().map(|r| r.trim().to_uppercase()) {
+        Some(r) if ["CASCADE", "SET NULL", "SET DEFAULT", "RESTRICT", "NO ACTION"].contains(&r.as_str()) => r,
+        _ => "NO ACTION".to_string(),
+    }
+}
+
+fn add_foreign_key_sql(table: &str, col: &ColumnSchema, db_type: &str) -> Option<String> {
+    let fk = col.foreign_key.as_ref()?;
+    Some(format!(
+        "ALTER TABLE {} ADD FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {};",
+        escape_identifier(table, db_type),
+        escape_identifier(&col.name, db_type),
+        escape_identifier(&fk.referenced_table, db_type),
+        escape_identifier(&fk.referenced_column, db_type),
+        referential_action(&fk.on_delete),
+        referential_action(&fk.on_update)
+    ))
+}
+
+fn alter_column_sql(table: &str, diff: &ColumnDiff, db_type: &str) -> Vec<String> {
+    let q_table = escape_identifier(table, db_type);
+    let q_col = escape_identifier(&diff.column, db_type);
+    let has = |d: &str| diff.differences.iter().any(|x| x == d);
+    let mut stmts = Vec::new();
+
+    match db_type {
+        "sqlite" => {
+            stmts.push(format!(
+                "-- SQLite cannot alter column {} ({}); rebuild table {} to apply",
+                q_col,
+                diff.differences.join(", "),
+                q_table
+            ));
+            return stmts;
+        }
+        "mysql" => {
+            if has("type") || has("nullable") || has("default") || has("auto_increment") {
+                // MODIFY restates the whole column; keys are handled separately to avoid duplicate PK errors
+                let mut col = diff.source.clone();
+                col.is_primary_key = false;
+                col.is_unique = false;
+                stmts.push(format!("ALTER TABLE {} MODIFY COLUMN {};", q_table, column_definition_sql(&col, db_type)));
+            }
+        }
+        _ => {
+            if has("type") {
+                stmts.push(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{};",
+                    q_table, q_col, diff.source.type_name, q_col, diff.source.type_name
+                ));
+            }
+            if has("nullable") {
+                let action = if diff.source.is_nullable { "DROP" } else { "SET" };
+                stmts.push(format!("ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;", q_table, q_col, action));
+            }
+            if has("default") {
+                // Postgres reports defaults as SQL expressions, so they can be reused verbatim
+                match &diff.source.default_value {
+                    Some(def) => stmts.push(format!("ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {};", q_table, q_col, def)),
+                    None => stmts.push(format!("ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;", q_table, q_col)),
+                }
+            }
+        }
+    }
+
+    if has("primary_key") {
+        stmts.push(format!("-- Primary key differs on {}.{}; review and adjust manually", q_table, q_col));
+    }
+    if has("unique") {
+        if diff.source.is_unique {
+            stmts.push(format!("ALTER TABLE {} ADD UNIQUE ({});", q_table, q_col));
+        } else {
+            stmts.push(format!("-- Drop the unique constraint on {}.{} (constraint name not introspected)", q_table, q_col));
+        }
+    }
+    if has("foreign_key") {
+        if diff.target.foreign_key.is_some() {
+            stmts.push(format!("-- Drop the existing foreign key on {}.{} (constraint name not introspected)", q_table, q_col));
+        }
+        if let Some(sql) = add_foreign_key_sql(table, &diff.source, db_type) {
+            stmts.push(sql);
+        }
+    }
+    stmts
+}
+
+fn generate_migration_sql(
+    source: &BTreeMap<String, TableSnapshot>,
+    missing_tables: &[String],
+    extra_tables: &[String],
+    changed_tables: &[TableDiff],
+    db_type: &str,
+) -> String {
+    let mut out = vec![format!("-- Schema migration generated by diff ({})", db_type)];
+
+    // SQLite resolves foreign keys lazily, so they can stay inline. Elsewhere a new table may
+    // reference another new table created after it, so foreign keys are added once all tables exist.
+    let inline_fks = db_type == "sqlite";
+    for table in missing_tables {
+        let snap = &source[table];
+        let fks: Vec<ForeignKeyInput> = snap
+            .columns
+            .iter()
+            .filter(|_| inline_fks)
+            .filter_map(|c| {
+                c.foreign_key.as_ref().map(|fk| ForeignKeyInput {
+                    column: c.name.clone(),
+                    ref_table: fk.referenced_table.clone(),
+                    ref_column: fk.referenced_column.clone(),
+                    on_delete: referential_action(&fk.on_delete),
+                    on_update: referential_action(&fk.on_update),
+                })
+            })
+            .collect();
+        out.push(String::new());
+        out.push(format!("-- Create table {}", table));
+        out.push(generate_create_table_sql(table, &snap.columns, &fks, db_type));
+        for idx in &snap.indexes {
+            out.push(create_index_sql(table, idx, db_type));
+        }
+    }
+    if !inline_fks {
+        let fks: Vec<String> = missing_tables
+            .iter()
+            .flat_map(|table| source[table].columns.iter().filter_map(move |c| add_foreign_key_sql(table, c, db_type)))
+            .collect();
+        if !fks.is_empty() {
+            out.push(String::new());
+            out.push("-- Foreign keys of the new tables".to_string());
+            out.extend(fks);
+        }
+    }
+
+    for diff in changed_tables {
+        let q_table = escape_identifier(&diff.table_name, db_type);
+        out.push(String::new());
+        out.push(format!("-- Alter table {}", diff.table_name));
+
+        for idx in &diff.extra_indexes {
+            out.push(drop_index_sql(&diff.table_name, idx, db_type));
+        }
+        for col in &diff.missing_columns {
+            let mut def = column_definition_sql(col, db_type);
+            // SQLite can only add a foreign key inline with the column
+            if db_type == "sqlite" {
+                if let Some(fk) = &col.foreign_key {
+                    def.push_str(&format!(
+                        " REFERENCES {} ({}) ON DELETE {} ON UPDATE {}",
+                        escape_identifier(&fk.referenced_table, db_type),
+                        escape_identifier(&fk.referenced_column, db_type),
+                        referential_action(&fk.on_delete),
+                        referential_action(&fk.on_update)
+                    ));
+                }
+            }
+            out.push(format!("ALTER TABLE {} ADD COLUMN {};", q_table, def));
+            if db_type != "sqlite" {
+                if let Some(sql) = add_foreign_key_sql(&diff.table_name, col, db_type) {
+                    out.push(sql);
+                }
+            }
+        }
+        for col in &diff.changed_columns {
+            out.extend(alter_column_sql(&diff.table_name, col, db_type));
+        }
+        for col in &diff.extra_columns {
+            out.push(format!("ALTER TABLE {} DROP COLUMN {};", q_table, escape_identifier(&col.name, db_type)));
+        }
+        for idx in &diff.missing_indexes {
+            out.push(create_index_sql(&diff.table_name, idx, db_type));
+        }
+    }
+
+    for table in extra_tables {
+        out.push(String::new());
+        out.push(format!("-- Drop table {} (not present in source)", table));
+        out.push(format!("DROP TABLE {};", escape_identifier(table, db_type)));
+    }
+
+    out.join("\n")
+}
+
+/// Compare the schema of `source_connection_string` against `target_connection_string`.
+/// Optionally restricted to `tables`.
+#[tauri::command]
+pub async fn diff_schema(
+    state: State<'_, AppState>,
+    source_connection_string: String,
+    target_connection_string: String,
+    tables: Option<Vec<String>>,
+) -> Result<SchemaDiff, String> {
+    let target_pool = crate::db::get_connection(&state, &target_connection_string).await.map_err(|e| e.to_string())?;
+    let db_type = match target_pool {
+        PoolWrapper::Mysql(_) => "mysql",
+        PoolWrapper::Postgres(_) => "postgres",
+        PoolWrapper::Sqlite(_) => "sqlite",
+    };
+
+    let source = snapshot_schema(&state, &source_connection_string, tables.as_deref()).await?;
+    let target = snapshot_schema(&state, &target_connection_string, tables.as_deref()).await?;
+
+    let missing_tables: Vec<String> = source.keys().filter(|t| !target.contains_key(*t)).cloned().collect();
+    let extra_tables: Vec<String> = target.keys().filter(|t| !source.contains_key(*t)).cloned().collect();
+
+    let changed_tables: Vec<TableDiff> = source
+        .iter()
+        .filter_map(|(name, src)| target.get(name).map(|tgt| diff_table(name, src, tgt)))
+        .filter(|diff| !is_table_diff_empty(diff))
+        .collect();
+
+    let migration_sql = generate_migration_sql(&source, &missing_tables, &extra_tables, &changed_tables, db_type);
+
+    Ok(SchemaDiff {
+        missing_tables,
+        extra_tables,
+        changed_tables,
+        migration_sql,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ForeignKey;
+
+    fn column(name: &str, foreign_key: Option<ForeignKey>) -> ColumnSchema {
+        ColumnSchema {
+            name: name.to_string(),
+            type_name: "integer".to_string(),
+            is_nullable: true,
+            is_primary_key: foreign_key.is_none(),
+            is_auto_increment: false,
+            is_unique: false,
+            default_value: None,
+            foreign_key,
+        }
+    }
+
+    fn source() -> BTreeMap<String, TableSnapshot> {
+        // "a_orders" sorts before the "customers" table it references
+        let fk = ForeignKey {
+            referenced_table: "customers".to_string(),
+            referenced_column: "id".to_string(),
+            on_delete: Some("CASCADE".to_string()),
+            on_update: None,
+        };
+        let mut source = BTreeMap::new();
+        source.insert(
+            "a_orders".to_string(),
+            TableSnapshot { columns: vec![column("id", None), column("customer_id", Some(fk))], indexes: vec![] },
+        );
+        source.insert("customers".to_string(), TableSnapshot { columns: vec![column("id", None)], indexes: vec![] });
+        source
+    }
+
+    #[test]
+    fn foreign_keys_of_new_tables_follow_all_creates() {
+        let source = source();
+        let tables: Vec<String> = source.keys().cloned().collect();
+        let sql = generate_migration_sql(&source, &tables, &[], &[], "postgres");
+
+        let add_fk = sql.find("ALTER TABLE \"a_orders\" ADD FOREIGN KEY").expect(&sql);
+        assert!(sql.find("CREATE TABLE IF NOT EXISTS \"customers\"").expect(&sql) < add_fk);
+        assert!(!sql.contains("REFERENCES \"customers\"(\"id\")"), "{}", sql);
+        assert!(sql[add_fk..].contains("ON DELETE CASCADE ON UPDATE NO ACTION"), "{}", sql);
+    }
+
+    #[test]
+    fn sqlite_keeps_foreign_keys_inline_with_their_rules() {
+        let source = source();
+        let tables: Vec<String> = source.keys().cloned().collect();
+        let sql = generate_migration_sql(&source, &tables, &[], &[], "sqlite");
+
+        assert!(!sql.contains("ADD FOREIGN KEY"), "{}", sql);
+        assert!(sql.contains("ON DELETE CASCADE ON UPDATE NO ACTION"), "{}", sql);
+    }
+
+    #[test]
+    fn unique_columns_and_composite_keys_are_declared_once() {
+        let key = |name: &str| ColumnSchema { is_primary_key: true, is_nullable: false, ..column(name, None) };
+        let email = ColumnSchema { type_name: "text".to_string(), is_unique: true, is_primary_key: false, ..column("email", None) };
+        let mut source = BTreeMap::new();
+        source.insert(
+            "memberships".to_string(),
+            TableSnapshot { columns: vec![key("user_id"), key("group_id"), email], indexes: vec![] },
+        );
+        let sql = generate_migration_sql(&source, &["memberships".to_string()], &[], &[], "postgres");
+
+        assert!(sql.contains("PRIMARY KEY (\"user_id\", \"group_id\")"), "{}", sql);
+        assert_eq!(sql.matches("PRIMARY KEY").count(), 1, "{}", sql);
+        assert!(sql.contains("\"email\" text UNIQUE"), "{}", sql);
+        assert_eq!(sql.matches("UNIQUE").count(), 1, "{}", sql);
+    }
+
+    #[test]
+    fn unknown_referential_actions_fall_back_to_no_action() {
+        assert_eq!(referential_action(&Some("set null".to_string())), "SET NULL");
+        assert_eq!(referential_action(&Some("CASCADE; DROP TABLE x".to_string())), "NO ACTION");
+        assert_eq!(referential_action(&None), "NO ACTION");
+    }
+}
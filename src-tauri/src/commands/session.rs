@@ -1,57 +1,280 @@
-use crate::db::{AppState, PoolWrapper};
-use tauri::{State, command};
+use crate::db::{AppState, DbConnectOptions, ManualLogin, PoolWrapper, SessionConnection};
+use crate::resolver::{resolve_connection, resolve_connection_with_login};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{State, Window, command};
 
+/// How long a health check waits for the database before treating the session as dead
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+pub struct SessionHealth {
+    pub healthy: bool,
+    pub reconnected: bool, // The pool had to be rebuilt to get there
+    pub latency_ms: Option<u64>,
+    pub connections: u32,
+    pub idle_connections: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub connection_id: i64,
+    pub database_name: Option<String>,
+    pub manual_login: bool,
+    pub read_only: bool,
+    pub windows: Vec<String>,
+    pub created_at: String,
+    pub idle_secs: i64,
+    pub connections: u32,
+    pub idle_connections: usize,
+}
+
+/// Open a session for a saved connection. `login` is either the one-time token a window was
+/// opened with or an existing session id whose manual login is reused; without it the
+/// connection's stored credential is used. The session is closed when the calling window closes.
 #[command]
 pub async fn create_session(
+    window: Window,
     state: State<'_, AppState>,
     connection_id: i64,
     database_name: Option<String>,
+    login: Option<String>,
 ) -> Result<String, String> {
-    // Reuse existing logic to get credentials and build connection URL
-    
-    // 1. Fetch connection details
-    let conn: crate::models::Connection = sqlx::query_as::<_, crate::models::Connection>(
-        "SELECT id, name, db_type, host, port, database_name, credential_id, ssl_mode, datetime(created_at) as created_at FROM connections WHERE id = ?"
-    )
-    .bind(connection_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| format!("Connection not found: {}", e))?;
-
-    // 2. Fetch credentials if needed
-    let (username, password) = if let Some(cred_id) = &conn.credential_id {
-        let cred: crate::models::Credential = sqlx::query_as::<_, crate::models::Credential>(
-            "SELECT id, name, username, datetime(created_at) as created_at FROM credentials WHERE id = ?"
-        )
-        .bind(cred_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|e| format!("Credential not found: {}", e))?;
-        
-        let entry = keyring::Entry::new("sql-ui-app", &cred.id).map_err(|e| e.to_string())?;
-        let password = entry.get_password().map_err(|e| e.to_string())?;
-        (Some(cred.username), Some(password))
+    let login = resolve_login(&state, connection_id, login.as_deref())?;
+    open_session(&state, connection_id, database_name, login, Some(window.label())).await
+}
+
+#[command]
+pub async fn list_sessions(state: State<'_, AppState>) -> Result<Vec<SessionInfo>, String> {
+    let mut sessions: Vec<SessionInfo> = state
+        .sessions
+        .list_sessions()
+        .into_iter()
+        .map(|s| {
+            let (connections, idle_connections) = s.pool.stats();
+            SessionInfo {
+                idle_secs: s.idle_secs(),
+                id: s.id,
+                connection_id: s.connection_id,
+                database_name: s.database_name,
+                manual_login: s.login.is_some(),
+                read_only: s.read_only,
+                windows: s.windows.into_iter().collect(),
+                created_at: s.created_at.to_rfc3339(),
+                connections,
+                idle_connections,
+            }
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(sessions)
+}
+
+#[command]
+pub async fn close_session(state: State<'_, AppState>, session_id: String) -> Result<(), String> {
+    if state.sessions.close_session(&session_id).await {
+        Ok(())
     } else {
-        (None, None)
+        Err("Session expired or invalid".to_string())
+    }
+}
+
+/// The manual login referenced by a login token or session id, if any
+pub(crate) fn resolve_login(state: &AppState, connection_id: i64, login: Option<&str>) -> Result<Option<Arc<ManualLogin>>, String> {
+    let login = match login {
+        None => return Ok(None),
+        Some(id) if id.starts_with("session:") => {
+            state.sessions.get_session(id).ok_or("Session expired or invalid")?.login
+        }
+        Some(token) => Some(state.sessions.take_login(token).ok_or("Login expired; enter the credentials again")?),
     };
+    match login {
+        Some(l) if l.connection_id != connection_id => Err("Login belongs to a different connection".to_string()),
+        other => Ok(other),
+    }
+}
 
-    // 3. Build connection string
-    let url = crate::commands::credential::build_connection_string(
-        &conn.db_type,
-        &conn.host,
-        conn.port,
-        database_name.as_deref().or(conn.database_name.as_deref()),
+pub(crate) async fn open_session(
+    state: &AppState,
+    connection_id: i64,
+    database_name: Option<String>,
+    login: Option<Arc<ManualLogin>>,
+    window: Option<&str>,
+) -> Result<String, String> {
+    let (connection, database_name) = connect_pool(state, connection_id, database_name, login.as_ref()).await?;
 
-        username.as_deref(),
-        password.as_deref(),
-        conn.ssl_mode.as_deref(),
-    );
+    // Store in SessionManager
+    let session_id = state.sessions.create_session(connection_id, database_name, connection, login, window);
 
-    // 4. Create Pool
-    let pool = PoolWrapper::new(&url).await?;
+    Ok(session_id)
+}
 
-    // 5. Store in SessionManager
-    let session_id = state.sessions.create_session(connection_id, database_name.clone().or(conn.database_name), pool);
+/// Build the pool for a session (and its SSH tunnel), with the connection's pool settings.
+/// Returns the database the pool is on.
+async fn connect_pool(
+    state: &AppState,
+    connection_id: i64,
+    database_name: Option<String>,
+    login: Option<&Arc<ManualLogin>>,
+) -> Result<(SessionConnection, Option<String>), String> {
+    let resolved = match login {
+        Some(l) => resolve_connection_with_login(state, connection_id, database_name, l.username.clone(), l.password.clone()).await?,
+        None => resolve_connection(state, connection_id, database_name).await?,
+    };
+    let (options, tunnel) = resolved.open_tunnel(state).await?;
 
-    Ok(session_id)
+    // Create Pool (SQLite sessions re-attach any databases saved for this connection)
+    let attachments = if resolved.connection.db_type == "sqlite" {
+        crate::commands::sqlite::load_attachments(&state.db, connection_id).await?
+    } else {
+        Vec::new()
+    };
+    let settings = resolved.connection.pool_settings();
+    let pool = match options {
+        DbConnectOptions::Sqlite(sqlite_options) if !attachments.is_empty() => {
+            PoolWrapper::new_sqlite_with_attachments(sqlite_options, attachments, &settings).await?
+        }
+        options => PoolWrapper::connect_with(options, &settings).await?,
+    };
+
+    Ok((SessionConnection { pool, tunnel, read_only: settings.read_only }, resolved.database_name))
+}
+
+/// Rebuild a session's pool and tunnel from the saved connection, keeping the session id
+pub(crate) async fn reconnect_session(state: &AppState, session_id: &str) -> Result<(), String> {
+    let session = state.sessions.get_session(session_id).ok_or("Session expired or invalid")?;
+    let (connection, _) = connect_pool(state, session.connection_id, session.database_name, session.login.as_ref()).await?;
+    if let Some(old) = state.sessions.replace_connection(session_id, connection) {
+        old.close().await;
+    }
+    Ok(())
+}
+
+/// Rebuild every open session of a saved connection after its settings changed, so flags such as
+/// read-only and the pool settings take effect. A session that cannot reconnect is closed rather
+/// than left running with the old settings.
+pub(crate) async fn reconnect_connection_sessions(state: &AppState, connection_id: i64) {
+    for session in state.sessions.list_sessions().into_iter().filter(|s| s.connection_id == connection_id) {
+        if let Err(e) = reconnect_session(state, &session.id).await {
+            log::warn!("Closing session {} after its connection changed: {}", session.id, e);
+            state.sessions.close_session(&session.id).await;
+        }
+    }
+}
+
+/// Close every open session of a saved connection, e.g. once it was deleted
+pub(crate) async fn close_connection_sessions(state: &AppState, connection_id: i64) {
+    for session in state.sessions.list_sessions().into_iter().filter(|s| s.connection_id == connection_id) {
+        state.sessions.close_session(&session.id).await;
+    }
+}
+
+async fn ping(pool: &PoolWrapper) -> Result<u64, String> {
+    let started = Instant::now();
+    tokio::time::timeout(HEALTH_CHECK_TIMEOUT, pool.ping())
+        .await
+        .map_err(|_| "The database did not answer in time".to_string())??;
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+/// Check a session can still reach its database, reconnecting it if not (e.g. after the
+/// machine slept and the server or a tunnel dropped the connections)
+#[command]
+pub async fn get_session_health(state: State<'_, AppState>, session_id: String) -> Result<SessionHealth, String> {
+    let session = state.sessions.get_session(&session_id).ok_or("Session expired or invalid")?;
+
+    let (result, reconnected) = match ping(&session.pool).await {
+        Ok(latency) => (Ok(latency), false),
+        Err(first_error) => {
+            log::warn!("Session {} failed its health check, reconnecting: {}", session_id, first_error);
+            match reconnect_session(&state, &session_id).await {
+                Ok(()) => {
+                    let pool = state.sessions.get_session(&session_id).ok_or("Session expired or invalid")?.pool;
+                    (ping(&pool).await, true)
+                }
+                Err(e) => (Err(e), false),
+            }
+        }
+    };
+
+    let (connections, idle_connections) = state
+        .sessions
+        .get_session(&session_id)
+        .map(|s| s.pool.stats())
+        .unwrap_or_default();
+    Ok(SessionHealth {
+        healthy: result.is_ok(),
+        reconnected,
+        latency_ms: result.as_ref().ok().copied(),
+        connections,
+        idle_connections,
+        error: result.err(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SessionManager;
+    use crate::resolver::{KeyringBackend, KeyringStore, Secrets};
+    use crate::vault::VaultStore;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    /// SQLite connections need no secrets
+    struct NoKeyring;
+
+    impl KeyringBackend for NoKeyring {
+        fn get(&self, _: &str, _: &str) -> keyring::Result<String> {
+            Err(keyring::Error::NoEntry)
+        }
+        fn set(&self, _: &str, _: &str, _: &str) -> keyring::Result<()> {
+            Err(keyring::Error::NoEntry)
+        }
+        fn delete(&self, _: &str, _: &str) -> keyring::Result<()> {
+            Err(keyring::Error::NoEntry)
+        }
+    }
+
+    /// App state over an in-memory database holding a writable SQLite connection (id 1)
+    async fn state_with_sqlite_connection() -> AppState {
+        let options = "sqlite::memory:".parse::<SqliteConnectOptions>().unwrap();
+        let db = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        crate::db::create_schema(&db).await.unwrap();
+        let file = std::env::temp_dir().join(format!("session-{}.db", uuid::Uuid::new_v4()));
+        std::fs::File::create(&file).unwrap();
+        sqlx::query("INSERT INTO connections (id, name, db_type, host, port) VALUES (1, 'local', 'sqlite', ?, 0)")
+            .bind(file.display().to_string())
+            .execute(&db)
+            .await
+            .unwrap();
+        AppState {
+            db,
+            sessions: SessionManager::new(),
+            secrets: Secrets {
+                keyring: Box::new(KeyringStore(NoKeyring)),
+                vault: VaultStore::new(std::env::temp_dir().join(format!("{}.vault", uuid::Uuid::new_v4()))),
+            },
+            ssh_known_hosts: std::env::temp_dir().join("ssh_known_hosts"),
+        }
+    }
+
+    #[tokio::test]
+    async fn open_sessions_follow_a_connection_made_read_only() {
+        let state = state_with_sqlite_connection().await;
+        let session_id = open_session(&state, 1, None, None, Some("main")).await.unwrap();
+        assert!(!state.sessions.get_session(&session_id).unwrap().read_only);
+
+        sqlx::query("UPDATE connections SET read_only = 1 WHERE id = 1").execute(&state.db).await.unwrap();
+        reconnect_connection_sessions(&state, 1).await;
+
+        // The same session id is reused by the windows, now read-only
+        assert_eq!(state.sessions.find_session(1, None, None, Some("main")), Some(session_id.clone()));
+        assert!(state.sessions.get_session(&session_id).unwrap().read_only);
+
+        close_connection_sessions(&state, 1).await;
+        assert!(state.sessions.get_session(&session_id).is_none());
+    }
 }
@@ -19,6 +19,7 @@ pub struct SidebarView {
 
 #[tauri::command]
 pub async fn get_sidebar_view(
+    window: tauri::Window,
     state: State<'_, AppState>,
     connection_string: Option<String>,
     connection_id: Option<i64>,
@@ -44,60 +45,17 @@ pub async fn get_sidebar_view(
         // Legacy Path: connection_id required
         let conn_id = connection_id.ok_or("Connection ID required when no connection string provided")?;
         
-        let row = sqlx::query("SELECT id, name, db_type, host, port, credential_id, database_name FROM connections WHERE id = ?")
-            .bind(conn_id)
-            .fetch_optional(&state.db)
-            .await
-            .map_err(|e| e.to_string())?
-            .ok_or("Connection not found")?;
-
-        let db_type: String = row.try_get("db_type").unwrap_or_default();
-        let host: String = row.try_get("host").unwrap_or_default();
-        let port: i32 = row.try_get("port").unwrap_or(3306);
-        let default_db: String = row.try_get("database_name").unwrap_or_default();
-        let credential_id: Option<String> = row.try_get("credential_id").unwrap_or_default();
-
-        let target_conn_string = if db_type == "sqlite" {
-            format!("sqlite:{}", host)
-        } else {
-            // MySQL or Postgres
-            let mut username = String::new();
-            let mut password = String::new();
-
-            if let Some(cred_id) = credential_id {
-                 let cred_row = sqlx::query("SELECT username FROM credentials WHERE id = ?")
-                     .bind(&cred_id)
-                     .fetch_optional(&state.db)
-                     .await
-                     .map_err(|e| e.to_string())?;
-                 
-                 if let Some(cr) = cred_row {
-                     username = cr.try_get("username").unwrap_or_default();
-                     password = crate::commands::credential::get_password_from_keyring(&cred_id).unwrap_or_default();
-                 }
-            }
-
-            let db_to_use = database_name.clone().unwrap_or(default_db);
-            let pass_part = if !password.is_empty() { format!(":{}", password) } else { String::new() };
-            
-            if db_type == "postgres" {
-                 if db_to_use.is_empty() {
-                      format!("postgres://{}{}{}@{}:{}", username, pass_part, "", host, port)
-                 } else {
-                      format!("postgres://{}{}{}@{}:{}/{}", username, pass_part, "", host, port, db_to_use)
-                 }
-            } else {
-                 if db_to_use.is_empty() {
-                      format!("mysql://{}{}{}@{}:{}", username, pass_part, "", host, port)
-                 } else {
-                      format!("mysql://{}{}{}@{}:{}/{}", username, pass_part, "", host, port, db_to_use)
-                 }
-            }
+        // Reuse (or open) a session for the calling window rather than a pool per call
+        let database = match &database_name {
+            Some(db) => Some(db.clone()),
+            None => crate::resolver::load_connection(&state.db, conn_id).await?.database_name,
         };
-        
-        // Use get_connection (it wraps PoolWrapper::new)
-        let p = crate::db::get_connection(&state, &target_conn_string).await.map_err(|e| e.to_string())?;
-        (p, conn_id, database_name.clone())
+        let session_id = match state.sessions.find_session(conn_id, database.as_deref(), None, Some(window.label())) {
+            Some(id) => id,
+            None => crate::commands::session::open_session(&state, conn_id, database, None, Some(window.label())).await?,
+        };
+        let session = state.sessions.get_session(&session_id).ok_or("Session expired or invalid")?;
+        (session.pool, conn_id, database_name.clone())
     };
 
     // 2. Fetch Host for Scoping (needed for SQLite fallback and Tags logic)
@@ -116,7 +74,21 @@ pub async fn get_sidebar_view(
     // 3. Fetch Tables
     let mut all_tables: Vec<String> = match &pool {
         PoolWrapper::Sqlite(p) => {
-             sqlx::query("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+             // An attached schema selected in the dropdown lists its own tables
+             let attached_schema: Option<String> = match &effective_db_name {
+                 Some(db) => sqlx::query_scalar("SELECT name FROM pragma_database_list WHERE name = ? AND name NOT IN ('main', 'temp')")
+                     .bind(db)
+                     .fetch_optional(p)
+                     .await
+                     .unwrap_or(None),
+                 None => None,
+             };
+             let master = match &attached_schema {
+                 Some(schema) => format!("{}.sqlite_master", crate::utils::escape_identifier(schema, "sqlite")),
+                 None => "sqlite_master".to_string(),
+             };
+             let q = format!("SELECT name FROM {} WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name", master);
+             sqlx::query(&q)
                  .fetch_all(p).await.map_err(|e: sqlx::Error| e.to_string())?
                  .iter().map(|r: &sqlx::sqlite::SqliteRow| r.get::<String, _>(0)).collect()
         },
@@ -192,9 +164,14 @@ pub async fn get_sidebar_view(
                  }
              }
         },
-        PoolWrapper::Sqlite(_) => {
-            // For SQLite, the "database" is the file name itself (host)
-            vec![host.clone()]
+        PoolWrapper::Sqlite(p) => {
+            // For SQLite, the "database" is the file name itself (host), followed by attached schemas
+            let mut db_list = vec![host.clone()];
+            match sqlx::query("SELECT name FROM pragma_database_list WHERE name NOT IN ('main', 'temp')").fetch_all(p).await {
+                Ok(rows) => db_list.extend(rows.iter().map(|r: &sqlx::sqlite::SqliteRow| r.get::<String, _>(0))),
+                Err(e) => log::warn!("Failed to fetch attached SQLite databases: {}", e),
+            }
+            db_list
         }
     };
 
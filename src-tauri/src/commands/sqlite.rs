@@ -0,0 +1,310 @@
+use crate::db::{AppState, PoolWrapper};
+use crate::models::AttachedDatabase;
+use crate::utils::escape_identifier;
+use sqlx::{migrate::MigrateDatabase, sqlite::SqliteConnectOptions, Pool, Row, Sqlite, SqlitePool};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Load the (schema, file) pairs attached to a connection
+pub async fn load_attachments(db: &Pool<Sqlite>, connection_id: i64) -> Result<Vec<(String, String)>, String> {
+    let rows = sqlx::query("SELECT schema_name, file_path FROM attached_sqlite_databases WHERE connection_id = ? ORDER BY id")
+        .bind(connection_id)
+        .fetch_all(db)
+        .await
+        .map_err(|e| format!("Failed to load attached databases: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|r| (r.get::<String, _>("schema_name"), r.get::<String, _>("file_path")))
+        .collect())
+}
+
+/// List every database visible on the connection (main + attached), excluding temp
+async fn database_list(p: &SqlitePool) -> Result<Vec<AttachedDatabase>, String> {
+    let rows = sqlx::query("SELECT name, file FROM pragma_database_list WHERE name != 'temp'")
+        .fetch_all(p)
+        .await
+        .map_err(|e| format!("Failed to fetch databases: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|r| AttachedDatabase {
+            schema_name: r.try_get(0).unwrap_or_default(),
+            file_path: r.try_get(1).unwrap_or_default(),
+        })
+        .collect())
+}
+
+async fn main_database_file(p: &SqlitePool) -> Result<String, String> {
+    database_list(p)
+        .await?
+        .into_iter()
+        .find(|d| d.schema_name == "main")
+        .map(|d| d.file_path)
+        .filter(|f| !f.is_empty())
+        .ok_or_else(|| "In-memory databases have no file on disk".to_string())
+}
+
+/// Resolve a database name to a file path. Bare names are placed next to the main
+/// database file and get a `.db` extension if they have none.
+fn resolve_database_path(main_file: &str, name: &str) -> PathBuf {
+    let path = Path::new(name);
+    if path.is_absolute() || path.components().count() > 1 {
+        return path.to_path_buf();
+    }
+
+    let dir = Path::new(main_file).parent().unwrap_or_else(|| Path::new("."));
+    let mut resolved = dir.join(name);
+    if resolved.extension().is_none() {
+        resolved.set_extension("db");
+    }
+    resolved
+}
+
+/// Whether a file is a SQLite database: it starts with the SQLite header, or is empty as
+/// newly created databases are until their first write
+fn is_sqlite_file(path: &Path) -> std::io::Result<bool> {
+    let mut header = Vec::with_capacity(16);
+    std::fs::File::open(path)?.take(16).read_to_end(&mut header)?;
+    Ok(header.is_empty() || header == b"SQLite format 3\0")
+}
+
+/// Create an empty SQLite database file
+pub async fn create_database_file(p: &SqlitePool, database_name: &str) -> Result<(), String> {
+    let main_file = main_database_file(p).await?;
+    let path = resolve_database_path(&main_file, database_name);
+
+    if path.exists() {
+        return Err(format!("Database file already exists: {}", path.display()));
+    }
+
+    Sqlite::create_database(&format!("sqlite://{}", path.to_string_lossy()))
+        .await
+        .map_err(|e| format!("Failed to create database: {}", e))
+}
+
+/// Copy a database (main, an attached schema, or a file path) into a new file using VACUUM INTO
+pub async fn duplicate_database_file(p: &SqlitePool, source_database: &str, target_database: &str) -> Result<(), String> {
+    let main_file = main_database_file(p).await?;
+    let target = resolve_database_path(&main_file, target_database);
+
+    if target.exists() {
+        return Err(format!("Database file already exists: {}", target.display()));
+    }
+    let target_str = target.to_string_lossy().to_string();
+
+    let attached = database_list(p).await?;
+    if let Some(db) = attached.iter().find(|d| d.schema_name == source_database || d.file_path == source_database) {
+        let query = format!("VACUUM {} INTO ?", escape_identifier(&db.schema_name, "sqlite"));
+        sqlx::query(&query)
+            .bind(&target_str)
+            .execute(p)
+            .await
+            .map_err(|e| format!("Failed to duplicate database: {}", e))?;
+        return Ok(());
+    }
+
+    // Not attached to this connection; open the source file directly
+    let source = resolve_database_path(&main_file, source_database);
+    if !source.exists() {
+        return Err(format!("Source database not found: {}", source_database));
+    }
+
+    let source_pool = SqlitePool::connect(&format!("sqlite://{}", source.to_string_lossy()))
+        .await
+        .map_err(|e| format!("Failed to open source database: {}", e))?;
+    let result = sqlx::query("VACUUM INTO ?")
+        .bind(&target_str)
+        .execute(&source_pool)
+        .await;
+    source_pool.close().await;
+
+    result
+        .map(|_| ())
+        .map_err(|e| format!("Failed to duplicate database: {}", e))
+}
+
+/// Delete a database file from disk. The caller must echo the database name back in
+/// `confirm_name`, the file must be a SQLite database, and it may not be the open main
+/// database or currently attached.
+pub async fn delete_database_file(p: &SqlitePool, database_name: &str, confirm_name: Option<&str>) -> Result<(), String> {
+    if confirm_name != Some(database_name) {
+        return Err("Deleting a SQLite database removes the file from disk; confirm by passing the database name".to_string());
+    }
+
+    let main_file = main_database_file(p).await?;
+    let path = resolve_database_path(&main_file, database_name);
+
+    let attached = database_list(p).await?;
+    if let Some(db) = attached.iter().find(|d| {
+        d.schema_name == database_name || d.file_path == database_name || Path::new(&d.file_path) == path
+    }) {
+        return Err(if db.schema_name == "main" {
+            "Cannot delete the database this connection is using".to_string()
+        } else {
+            format!("Database is attached as '{}'; detach it first", db.schema_name)
+        });
+    }
+
+    if !path.is_file() {
+        return Err(format!("Database file not found: {}", path.display()));
+    }
+    // Names may be absolute paths, so never remove a file that isn't a database
+    if !is_sqlite_file(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))? {
+        return Err(format!("Not a SQLite database: {}", path.display()));
+    }
+
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete database: {}", e))?;
+
+    // Remove journal side files left behind by WAL or rollback mode
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let side_file = PathBuf::from(format!("{}{}", path.to_string_lossy(), suffix));
+        if side_file.exists() {
+            let _ = std::fs::remove_file(side_file);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reopen a session's SQLite pool with the connection's current set of attachments
+async fn reopen_session_pool(
+    state: &AppState,
+    session_id: &str,
+    main_file: &str,
+    attachments: Vec<(String, String)>,
+) -> Result<(), String> {
+    let session = state.sessions.get_session(session_id).ok_or("Session expired or invalid")?;
+    let settings = crate::resolver::load_connection(&state.db, session.connection_id).await?.pool_settings();
+    let pool = PoolWrapper::new_sqlite_with_attachments(SqliteConnectOptions::new().filename(main_file), attachments, &settings)
+        .await
+        .map_err(|e| format!("Failed to attach database: {}", e))?;
+
+    if let Some(PoolWrapper::Sqlite(old)) = state.sessions.replace_pool(session_id, pool) {
+        old.close().await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn attach_database(
+    state: State<'_, AppState>,
+    connection_string: String,
+    file_path: String,
+    schema_name: String,
+) -> Result<(), String> {
+    let session = state
+        .sessions
+        .get_session(&connection_string)
+        .ok_or("Attaching databases requires an active session")?;
+    let PoolWrapper::Sqlite(p) = &session.pool else {
+        return Err("ATTACH DATABASE is only supported for SQLite connections".to_string());
+    };
+
+    let schema_name = schema_name.trim().to_string();
+    if schema_name.is_empty() || schema_name.eq_ignore_ascii_case("main") || schema_name.eq_ignore_ascii_case("temp") {
+        return Err(format!("Invalid schema name: '{}'", schema_name));
+    }
+
+    let main_file = main_database_file(p).await?;
+    let path = resolve_database_path(&main_file, &file_path);
+    if !path.is_file() {
+        return Err(format!("Database file not found: {}", path.display()));
+    }
+    let path_str = path.to_string_lossy().to_string();
+
+    let mut attachments = load_attachments(&state.db, session.connection_id).await?;
+    attachments.retain(|(s, _)| s != &schema_name);
+    attachments.push((schema_name.clone(), path_str.clone()));
+
+    // Open the new pool first so a bad file never gets persisted
+    reopen_session_pool(&state, &connection_string, &main_file, attachments).await?;
+
+    sqlx::query(
+        "INSERT INTO attached_sqlite_databases (connection_id, schema_name, file_path) VALUES (?, ?, ?)
+         ON CONFLICT(connection_id, schema_name) DO UPDATE SET file_path = excluded.file_path",
+    )
+    .bind(session.connection_id)
+    .bind(&schema_name)
+    .bind(&path_str)
+    .execute(&state.db)
+    .await
+    .map_err(|e| format!("Failed to save attached database: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn detach_database(
+    state: State<'_, AppState>,
+    connection_string: String,
+    schema_name: String,
+) -> Result<(), String> {
+    let session = state
+        .sessions
+        .get_session(&connection_string)
+        .ok_or("Detaching databases requires an active session")?;
+    let PoolWrapper::Sqlite(p) = &session.pool else {
+        return Err("DETACH DATABASE is only supported for SQLite connections".to_string());
+    };
+
+    let main_file = main_database_file(p).await?;
+
+    sqlx::query("DELETE FROM attached_sqlite_databases WHERE connection_id = ? AND schema_name = ?")
+        .bind(session.connection_id)
+        .bind(&schema_name)
+        .execute(&state.db)
+        .await
+        .map_err(|e| format!("Failed to remove attached database: {}", e))?;
+
+    let attachments = load_attachments(&state.db, session.connection_id).await?;
+    reopen_session_pool(&state, &connection_string, &main_file, attachments).await
+}
+
+#[tauri::command]
+pub async fn list_attached_databases(
+    state: State<'_, AppState>,
+    connection_string: String,
+) -> Result<Vec<AttachedDatabase>, String> {
+    let pool = crate::db::get_connection(&state, &connection_string).await.map_err(|e| e.to_string())?;
+    let PoolWrapper::Sqlite(p) = pool else {
+        return Err("Attached databases are only supported for SQLite connections".to_string());
+    };
+
+    let mut dbs = database_list(&p).await?;
+    dbs.retain(|d| d.schema_name != "main");
+    Ok(dbs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn delete_database_file_only_removes_databases() {
+        let dir = std::env::temp_dir().join(format!("sqlite-delete-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.db");
+        let pool = SqlitePool::connect_with(SqliteConnectOptions::new().filename(&main).create_if_missing(true))
+            .await
+            .unwrap();
+
+        let notes = dir.join("notes.txt");
+        std::fs::write(&notes, "not a database").unwrap();
+        let name = notes.to_string_lossy().to_string();
+        let err = delete_database_file(&pool, &name, Some(&name)).await.unwrap_err();
+        assert!(err.starts_with("Not a SQLite database"), "{}", err);
+        assert!(notes.exists());
+
+        create_database_file(&pool, "other").await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER)").execute(&pool).await.unwrap();
+        sqlx::query("VACUUM INTO ?").bind(dir.join("copy.db").to_string_lossy()).execute(&pool).await.unwrap();
+        delete_database_file(&pool, "other", Some("other")).await.unwrap();
+        delete_database_file(&pool, "copy", Some("copy")).await.unwrap();
+        assert!(!dir.join("other.db").exists() && !dir.join("copy.db").exists());
+
+        pool.close().await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::db::AppState;
+use crate::resolver::CredentialError;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{State, command};
+
+#[derive(Serialize)]
+pub struct SecretStoreStatus {
+    pub backend: String, // "keyring" or "vault"
+    pub vault_unlocked: bool,
+    pub keyring_available: bool,
+}
+
+/// Outcome of moving passwords between the keyring and the vault
+#[derive(Serialize)]
+pub struct SecretMigrationReport {
+    pub migrated: usize,
+    pub missing: Vec<String>, // Names of credentials that had no password to move
+}
+
+#[command]
+pub async fn get_secret_store_status(state: State<'_, AppState>) -> Result<SecretStoreStatus, String> {
+    // A lookup that finds nothing still proves the keyring is reachable
+    let keyring_available = matches!(
+        state.secrets.keyring.get_password("keyring-availability-probe"),
+        Ok(_) | Err(CredentialError::PasswordNotFound(_))
+    );
+    Ok(SecretStoreStatus {
+        backend: if state.secrets.vault.exists() { "vault" } else { "keyring" }.to_string(),
+        vault_unlocked: state.secrets.vault.is_unlocked(),
+        keyring_available,
+    })
+}
+
+#[command]
+pub async fn unlock_vault(state: State<'_, AppState>, master_password: String) -> Result<(), String> {
+    state.secrets.vault.unlock(&master_password).map_err(String::from)
+}
+
+#[command]
+pub async fn lock_vault(state: State<'_, AppState>) -> Result<(), String> {
+    state.secrets.vault.lock();
+    Ok(())
+}
+
+async fn list_credential_names(state: &AppState) -> Result<Vec<(String, String)>, String> {
+    sqlx::query_as::<_, (String, String)>("SELECT id, name FROM credentials")
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| format!("Failed to list credentials: {}", e))
+}
+
+/// Create the vault and move every keyring password into it. Keyring entries are only removed
+/// once the vault has been written; if that fails the new vault is deleted again.
+#[command]
+pub async fn migrate_to_vault(state: State<'_, AppState>, master_password: String) -> Result<SecretMigrationReport, String> {
+    move_to_vault(&state, &master_password).await
+}
+
+async fn move_to_vault(state: &AppState, master_password: &str) -> Result<SecretMigrationReport, String> {
+    let secrets = &state.secrets;
+    let credentials = list_credential_names(state).await?;
+
+    let mut passwords = HashMap::new();
+    let mut missing = Vec::new();
+    for (id, name) in credentials {
+        match secrets.keyring.get_password(&id) {
+            Ok(password) => {
+                passwords.insert(id, password);
+            }
+            // Without a reachable keyring there is nothing to move; the credentials get new passwords
+            Err(CredentialError::PasswordNotFound(_)) | Err(CredentialError::KeyringUnavailable(_)) => missing.push(name),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    secrets.vault.create(master_password)?;
+    let ids: Vec<String> = passwords.keys().cloned().collect();
+    if let Err(e) = secrets.vault.set_passwords(passwords) {
+        let _ = secrets.vault.destroy();
+        return Err(e.into());
+    }
+    for id in &ids {
+        let _ = secrets.keyring.delete_password(id);
+    }
+
+    Ok(SecretMigrationReport { migrated: ids.len(), missing })
+}
+
+/// Move every vault password back into the keyring and delete the vault. Nothing is deleted
+/// unless all passwords were written to the keyring.
+#[command]
+pub async fn migrate_to_keyring(state: State<'_, AppState>, master_password: String) -> Result<SecretMigrationReport, String> {
+    move_to_keyring(&state, &master_password).await
+}
+
+async fn move_to_keyring(state: &AppState, master_password: &str) -> Result<SecretMigrationReport, String> {
+    let secrets = &state.secrets;
+    if !secrets.vault.exists() {
+        return Err("No credential vault exists".to_string());
+    }
+    secrets.vault.unlock(master_password)?;
+    let entries = secrets.vault.entries()?;
+
+    for (id, password) in &entries {
+        secrets.keyring.set_password(id, password)?;
+    }
+    let missing = list_credential_names(state)
+        .await?
+        .into_iter()
+        .filter(|(id, _)| !entries.contains_key(id))
+        .map(|(_, name)| name)
+        .collect();
+    secrets.vault.destroy()?;
+
+    Ok(SecretMigrationReport { migrated: entries.len(), missing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::tests::{state_with, MemoryKeyring};
+    use crate::resolver::{SecretStore, KEYRING_SERVICE};
+
+    #[tokio::test]
+    async fn passwords_move_to_the_vault_and_back() {
+        let keyring = MemoryKeyring::with(&[(KEYRING_SERVICE, "cred-1", "secret")]);
+        let state = state_with(keyring, true).await;
+
+        let report = move_to_vault(&state, "master").await.unwrap();
+        assert_eq!((report.migrated, report.missing.len()), (1, 0));
+        assert_eq!(state.secrets.vault.get_password("cred-1"), Ok("secret".to_string()));
+        assert!(state.secrets.keyring.get_password("cred-1").is_err());
+
+        // A wrong master password leaves the vault in place
+        state.secrets.vault.lock();
+        assert!(move_to_keyring(&state, "wrong").await.is_err());
+        assert!(state.secrets.vault.exists());
+
+        let report = move_to_keyring(&state, "master").await.unwrap();
+        assert_eq!(report.migrated, 1);
+        assert!(!state.secrets.vault.exists());
+        assert_eq!(state.secrets.keyring.get_password("cred-1"), Ok("secret".to_string()));
+    }
+
+    #[tokio::test]
+    async fn credentials_without_a_keyring_password_are_reported_missing() {
+        let state = state_with(MemoryKeyring { unavailable: true, ..Default::default() }, true).await;
+        let report = move_to_vault(&state, "master").await.unwrap();
+        assert_eq!((report.migrated, report.missing), (0, vec!["app".to_string()]));
+        assert!(state.secrets.vault.entries().unwrap().is_empty());
+        state.secrets.vault.destroy().unwrap();
+    }
+}
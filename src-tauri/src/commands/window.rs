@@ -1,8 +1,12 @@
-use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use crate::db::{AppState, ManualLogin};
+use tauri::{Manager, State, WebviewUrl, WebviewWindowBuilder};
 
+/// Open a connection window. A manual login stays in backend memory; the window only gets a
+/// one-time token in its URL, which it redeems for a session on startup.
 #[tauri::command]
 pub async fn open_connection_window<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
+    state: State<'_, AppState>,
     connection_id: i64,
     username: Option<String>,
     password: Option<String>,
@@ -15,8 +19,9 @@ pub async fn open_connection_window<R: tauri::Runtime>(
     let label = format!("connection-{}-{}", connection_id, timestamp);
     
     let mut url = format!("index.html?connection_id={}", connection_id);
-    if let (Some(u), Some(p)) = (username, password) {
-        url.push_str(&format!("&u={}&p={}", u, p));
+    if let (Some(username), Some(password)) = (username, password) {
+        let token = state.sessions.create_login_token(ManualLogin { connection_id, username, password });
+        url.push_str(&format!("&login={}", token));
     }
 
     WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(url.into()))
@@ -1,9 +1,207 @@
-use sqlx::{migrate::MigrateDatabase, sqlite::SqlitePoolOptions, Pool, Sqlite, MySql, Postgres};
+use crate::utils::escape_identifier;
+use sqlx::{migrate::MigrateDatabase, sqlite::{SqliteConnectOptions, SqlitePoolOptions}, Pool, Sqlite, MySql, Postgres};
+use sqlx::{mysql::{MySqlConnectOptions, MySqlSslMode}, postgres::{PgConnectOptions, PgSslMode}, ConnectOptions, Connection};
+use crate::models::{PoolSettings, TlsSettings};
+use sqlx::pool::PoolOptions;
+use std::time::Duration;
+use serde::Serialize;
 use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 use tauri::{AppHandle, Manager, Runtime};
 
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// TLS mode of a connection, named as in libpq; MySQL's names are accepted too
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TlsMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull, // Also checks the certificate matches the host name
+}
+
+impl TlsMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "disable" | "disabled" => Ok(Self::Disable),
+            "allow" | "prefer" | "preferred" => Ok(Self::Prefer),
+            "require" | "required" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" | "verify-identity" => Ok(Self::VerifyFull),
+            other => Err(format!("Unknown TLS mode: {}", other)),
+        }
+    }
+}
+
+/// Encryption negotiated on a connection, as reported by the server
+#[derive(Serialize, Debug)]
+pub struct TlsStatus {
+    pub encrypted: bool,
+    pub version: Option<String>,
+    pub cipher: Option<String>,
+}
+
+/// Connect options for one engine. Saved connections are built from their parts, so
+/// credentials and hosts never need to survive a round trip through a URL.
+#[derive(Clone, Debug)]
+pub enum DbConnectOptions {
+    Mysql(MySqlConnectOptions),
+    Postgres(PgConnectOptions),
+    Sqlite(SqliteConnectOptions),
+}
+
+impl DbConnectOptions {
+    /// Parse a connection URL, choosing the engine from its scheme like `PoolWrapper::new`
+    pub fn parse(connection_string: &str) -> Result<Self, String> {
+        if connection_string.starts_with("mysql") {
+            MySqlConnectOptions::from_str(connection_string).map(Self::Mysql).map_err(|e| e.to_string())
+        } else if connection_string.starts_with("postgres") {
+            PgConnectOptions::from_str(connection_string).map(Self::Postgres).map_err(|e| e.to_string())
+        } else {
+            SqliteConnectOptions::from_str(connection_string).map(Self::Sqlite).map_err(|e| e.to_string())
+        }
+    }
+
+    /// Replace the login (SQLite has none)
+    pub fn with_login(self, username: &str, password: &str) -> Self {
+        match self {
+            Self::Mysql(o) => Self::Mysql(o.username(username).password(password)),
+            Self::Postgres(o) => Self::Postgres(o.username(username).password(password)),
+            Self::Sqlite(o) => Self::Sqlite(o),
+        }
+    }
+
+    /// Apply TLS settings (SQLite has none). Certificate files are checked up front so a wrong
+    /// path is reported as such rather than as a handshake failure.
+    pub fn with_tls(self, tls: &TlsSettings) -> Result<Self, String> {
+        let mode = tls.mode.as_deref().filter(|m| !m.trim().is_empty()).map(TlsMode::parse).transpose()?;
+        let existing = |path: &Option<String>, what: &str| -> Result<Option<String>, String> {
+            match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+                Some(p) if !Path::new(p).exists() => Err(format!("{} not found: {}", what, p)),
+                other => Ok(other.map(str::to_string)),
+            }
+        };
+        let ca = existing(&tls.ca_path, "CA certificate file")?;
+        let cert = existing(&tls.client_cert_path, "Client certificate file")?;
+        let key = existing(&tls.client_key_path, "Client key file")?;
+        if cert.is_some() != key.is_some() {
+            return Err("A client certificate and its key must be given together".to_string());
+        }
+
+        Ok(match self {
+            Self::Mysql(mut o) => {
+                if let Some(mode) = mode {
+                    o = o.ssl_mode(match mode {
+                        TlsMode::Disable => MySqlSslMode::Disabled,
+                        TlsMode::Prefer => MySqlSslMode::Preferred,
+                        TlsMode::Require => MySqlSslMode::Required,
+                        TlsMode::VerifyCa => MySqlSslMode::VerifyCa,
+                        TlsMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+                    });
+                }
+                if let Some(ca) = ca {
+                    o = o.ssl_ca(ca);
+                }
+                if let (Some(cert), Some(key)) = (cert, key) {
+                    o = o.ssl_client_cert(cert).ssl_client_key(key);
+                }
+                Self::Mysql(o)
+            }
+            Self::Postgres(mut o) => {
+                if let Some(mode) = mode {
+                    o = o.ssl_mode(match mode {
+                        TlsMode::Disable => PgSslMode::Disable,
+                        TlsMode::Prefer => PgSslMode::Prefer,
+                        TlsMode::Require => PgSslMode::Require,
+                        TlsMode::VerifyCa => PgSslMode::VerifyCa,
+                        TlsMode::VerifyFull => PgSslMode::VerifyFull,
+                    });
+                }
+                if let Some(ca) = ca {
+                    o = o.ssl_root_cert(ca);
+                }
+                if let (Some(cert), Some(key)) = (cert, key) {
+                    o = o.ssl_client_cert(cert).ssl_client_key(key);
+                }
+                Self::Postgres(o)
+            }
+            Self::Sqlite(o) => Self::Sqlite(o),
+        })
+    }
+
+    /// Host and port of a TCP connection (None for sockets and SQLite files)
+    pub fn tcp_target(&self) -> Option<(String, u16)> {
+        match self {
+            Self::Mysql(o) if o.get_socket().is_none() => Some((o.get_host().to_string(), o.get_port())),
+            Self::Postgres(o) if o.get_socket().is_none() => Some((o.get_host().to_string(), o.get_port())),
+            _ => None,
+        }
+    }
+
+    /// Whether TLS also checks the certificate against the host name (verify-full / VERIFY_IDENTITY)
+    pub fn verifies_host_name(&self) -> bool {
+        match self {
+            Self::Mysql(o) => matches!(o.get_ssl_mode(), MySqlSslMode::VerifyIdentity),
+            Self::Postgres(o) => matches!(o.get_ssl_mode(), PgSslMode::VerifyFull),
+            Self::Sqlite(_) => false,
+        }
+    }
+
+    /// Connect to the local end of a tunnel instead of the database host
+    pub fn through_tunnel(self, local_port: u16) -> Self {
+        match self {
+            Self::Mysql(o) => Self::Mysql(o.host("127.0.0.1").port(local_port)),
+            Self::Postgres(o) => Self::Postgres(o.host("127.0.0.1").port(local_port)),
+            Self::Sqlite(o) => Self::Sqlite(o),
+        }
+    }
+
+    /// Open a single connection, read the TLS state the server negotiated and close it again.
+    /// The state is None for SQLite, or when the server does not say.
+    pub async fn verify(&self) -> Result<Option<TlsStatus>, String> {
+        match self {
+            Self::Mysql(o) => {
+                let mut conn = o.connect().await.map_err(|e| e.to_string())?;
+                let status: Vec<(String, String)> = sqlx::query_as(
+                    "SHOW SESSION STATUS WHERE Variable_name IN ('Ssl_version', 'Ssl_cipher')"
+                )
+                .fetch_all(&mut conn)
+                .await
+                .unwrap_or_default();
+                conn.close().await.map_err(|e| e.to_string())?;
+
+                let value = |name: &str| {
+                    status.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.clone()).filter(|v| !v.is_empty())
+                };
+                Ok((!status.is_empty()).then(|| TlsStatus {
+                    encrypted: value("Ssl_cipher").is_some(),
+                    version: value("Ssl_version"),
+                    cipher: value("Ssl_cipher"),
+                }))
+            }
+            Self::Postgres(o) => {
+                let mut conn = o.connect().await.map_err(|e| e.to_string())?;
+                let status: Option<(bool, Option<String>, Option<String>)> = sqlx::query_as(
+                    "SELECT ssl, version, cipher FROM pg_stat_ssl WHERE pid = pg_backend_pid()"
+                )
+                .fetch_optional(&mut conn)
+                .await
+                .unwrap_or_default();
+                conn.close().await.map_err(|e| e.to_string())?;
+
+                Ok(status.map(|(encrypted, version, cipher)| TlsStatus { encrypted, version, cipher }))
+            }
+            Self::Sqlite(o) => {
+                o.connect().await.map_err(|e| e.to_string())?.close().await.map_err(|e| e.to_string())?;
+                Ok(None)
+            }
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum PoolWrapper {
@@ -26,51 +224,406 @@ impl PoolWrapper {
             Ok(PoolWrapper::Sqlite(pool))
         }
     }
+
+    /// Open a pool with the connection's pool settings. The statement timeout is set on each
+    /// new connection: `statement_timeout` on Postgres, `max_execution_time` on MySQL
+    /// (`max_statement_time` on MariaDB, which only affects reads on MySQL). A read-only
+    /// connection makes every transaction read-only, or sets `query_only` on SQLite.
+    pub async fn connect_with(options: DbConnectOptions, settings: &PoolSettings) -> Result<Self, String> {
+        let timeout_ms = settings.statement_timeout_secs.map(|s| s * 1000);
+        let read_only = settings.read_only;
+        match options {
+            DbConnectOptions::Mysql(o) => pool_options::<MySql>(settings)
+                .after_connect(move |conn, _meta| {
+                    Box::pin(async move {
+                        if read_only {
+                            sqlx::query("SET SESSION TRANSACTION READ ONLY").execute(&mut *conn).await?;
+                        }
+                        if let Some(ms) = timeout_ms {
+                            let sql = format!("SET SESSION max_execution_time = {}", ms);
+                            if sqlx::query(&sql).execute(&mut *conn).await.is_err() {
+                                let sql = format!("SET SESSION max_statement_time = {}", ms / 1000);
+                                sqlx::query(&sql).execute(&mut *conn).await?;
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+                .connect_with(o)
+                .await
+                .map(PoolWrapper::Mysql),
+            DbConnectOptions::Postgres(o) => pool_options::<Postgres>(settings)
+                .after_connect(move |conn, _meta| {
+                    Box::pin(async move {
+                        if read_only {
+                            sqlx::query("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY").execute(&mut *conn).await?;
+                        }
+                        if let Some(ms) = timeout_ms {
+                            sqlx::query(&format!("SET statement_timeout = {}", ms)).execute(&mut *conn).await?;
+                        }
+                        Ok(())
+                    })
+                })
+                .connect_with(o)
+                .await
+                .map(PoolWrapper::Postgres),
+            DbConnectOptions::Sqlite(o) => pool_options::<Sqlite>(settings)
+                .connect_with(if read_only { o.pragma("query_only", "ON") } else { o })
+                .await
+                .map(PoolWrapper::Sqlite),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Opens a SQLite pool where every connection ATTACHes the given (schema, file) pairs.
+    /// ATTACH is per-connection, so it has to run in `after_connect` rather than once on the pool.
+    pub async fn new_sqlite_with_attachments(
+        options: SqliteConnectOptions,
+        attachments: Vec<(String, String)>,
+        settings: &PoolSettings,
+    ) -> Result<Self, String> {
+        let pool = pool_options::<Sqlite>(settings)
+            .after_connect(move |conn, _meta| {
+                let attachments = attachments.clone();
+                Box::pin(async move {
+                    for (schema, file) in attachments {
+                        let sql = format!("ATTACH DATABASE ? AS {}", escape_identifier(&schema, "sqlite"));
+                        sqlx::query(&sql).bind(file).execute(&mut *conn).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect_with(if settings.read_only { options.pragma("query_only", "ON") } else { options })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(PoolWrapper::Sqlite(pool))
+    }
+
+    /// Run a trivial query to check the pool can still reach the database
+    pub async fn ping(&self) -> Result<(), String> {
+        match self {
+            PoolWrapper::Mysql(p) => sqlx::query("SELECT 1").execute(p).await.map(|_| ()),
+            PoolWrapper::Postgres(p) => sqlx::query("SELECT 1").execute(p).await.map(|_| ()),
+            PoolWrapper::Sqlite(p) => sqlx::query("SELECT 1").execute(p).await.map(|_| ()),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Open and idle connections in the pool
+    pub fn stats(&self) -> (u32, usize) {
+        match self {
+            PoolWrapper::Mysql(p) => (p.size(), p.num_idle()),
+            PoolWrapper::Postgres(p) => (p.size(), p.num_idle()),
+            PoolWrapper::Sqlite(p) => (p.size(), p.num_idle()),
+        }
+    }
+
+    pub async fn close(&self) {
+        match self {
+            PoolWrapper::Mysql(p) => p.close().await,
+            PoolWrapper::Postgres(p) => p.close().await,
+            PoolWrapper::Sqlite(p) => p.close().await,
+        }
+    }
+}
+
+/// Pool options from a connection's settings; sqlx defaults where a setting is empty
+fn pool_options<DB: sqlx::Database>(settings: &PoolSettings) -> PoolOptions<DB> {
+    let mut options = PoolOptions::<DB>::new().test_before_acquire(settings.test_before_acquire.unwrap_or(true));
+    if let Some(max) = settings.max_connections {
+        options = options.max_connections(max);
+    }
+    if let Some(secs) = settings.idle_timeout_secs {
+        options = options.idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = settings.connect_timeout_secs {
+        options = options.acquire_timeout(Duration::from_secs(secs));
+    }
+    options
+}
+
+/// Login typed in by the user instead of a stored credential. Lives in backend memory only
+/// and is never serialized; its Debug output omits the password.
+#[derive(Clone)]
+pub struct ManualLogin {
+    pub connection_id: i64,
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for ManualLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualLogin")
+            .field("connection_id", &self.connection_id)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
 }
 
+/// How long a login token handed to a new window stays redeemable
+const LOGIN_TOKEN_TTL_SECS: i64 = 120;
+
+/// Sessions and cached pools unused for this long are closed. Open windows keep their
+/// session alive through the periodic health check.
+const IDLE_TTL_SECS: i64 = 30 * 60;
+/// How often idle sessions and pools are looked for
+pub const IDLE_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long an acknowledgement for flagged statements stays valid
+const CONFIRMATION_TTL_SECS: i64 = 300;
+
+/// Login token -> (login, issued at)
+type LoginTokens = HashMap<String, (Arc<ManualLogin>, chrono::DateTime<chrono::Utc>)>;
+/// Acknowledgement token -> (connection string, query, issued at)
+type Confirmations = HashMap<String, (String, String, chrono::DateTime<chrono::Utc>)>;
 
 #[derive(Clone, Debug)]
 pub struct Session {
-    #[allow(dead_code)]
     pub id: String,
     pub connection_id: i64,
     pub database_name: Option<String>,
     pub pool: PoolWrapper,
+    pub login: Option<Arc<ManualLogin>>, // Set when the session was opened with a manual login
     #[allow(dead_code)]
+    pub tunnel: Option<Arc<crate::tunnel::SshTunnel>>, // Held open until the last clone of the session is gone
+    pub read_only: bool,
+    pub windows: HashSet<String>, // Labels of the windows using the session; it closes with the last one
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used: Arc<AtomicI64>, // Unix seconds
+}
+
+impl Session {
+    pub fn idle_secs(&self) -> i64 {
+        chrono::Utc::now().timestamp() - self.last_used.load(Ordering::Relaxed)
+    }
+}
+
+/// What a session is connected through: its pool, the SSH tunnel under it and whether the
+/// connection was opened read-only
+pub struct SessionConnection {
+    pub pool: PoolWrapper,
+    pub tunnel: Option<crate::tunnel::SshTunnel>,
+    pub read_only: bool,
+}
+
+/// A pool opened for a raw connection string, kept so later calls with the same string reuse it
+struct CachedPool {
+    pool: PoolWrapper,
+    last_used: AtomicI64,
 }
 
 pub struct SessionManager {
     sessions: RwLock<HashMap<String, Session>>,
+    login_tokens: RwLock<LoginTokens>,
+    confirmations: RwLock<Confirmations>,
+    pools: RwLock<HashMap<String, CachedPool>>, // By connection string
 }
 
 impl SessionManager {
     pub fn new() -> Self {
         Self {
             sessions: RwLock::new(HashMap::new()),
+            login_tokens: RwLock::new(HashMap::new()),
+            confirmations: RwLock::new(HashMap::new()),
+            pools: RwLock::new(HashMap::new()),
         }
     }
 
-    pub fn create_session(&self, connection_id: i64, database_name: Option<String>, pool: PoolWrapper) -> String {
+    pub fn create_session(
+        &self,
+        connection_id: i64,
+        database_name: Option<String>,
+        connection: SessionConnection,
+        login: Option<Arc<ManualLogin>>,
+        window: Option<&str>,
+    ) -> String {
         let id = format!("session:{}", uuid::Uuid::new_v4());
+        let now = chrono::Utc::now();
         let session = Session {
             id: id.clone(),
             connection_id,
             database_name,
-            pool,
-            created_at: chrono::Utc::now(),
+            pool: connection.pool,
+            login,
+            tunnel: connection.tunnel.map(Arc::new),
+            read_only: connection.read_only,
+            windows: window.map(str::to_string).into_iter().collect(),
+            created_at: now,
+            last_used: Arc::new(AtomicI64::new(now.timestamp())),
         };
         self.sessions.write().unwrap().insert(id.clone(), session);
         id
     }
 
+    /// An open session for this connection, database and login (same manual login, or none).
+    /// The session is marked as used by `window` as well.
+    pub fn find_session(
+        &self,
+        connection_id: i64,
+        database_name: Option<&str>,
+        login: Option<&Arc<ManualLogin>>,
+        window: Option<&str>,
+    ) -> Option<String> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions.values_mut().find(|s| {
+            s.connection_id == connection_id
+                && s.database_name.as_deref() == database_name
+                && match (&s.login, login) {
+                    (None, None) => true,
+                    (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                    _ => false,
+                }
+        })?;
+        if let Some(window) = window {
+            session.windows.insert(window.to_string());
+        }
+        session.last_used.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+        Some(session.id.clone())
+    }
+
+    /// Keep a manual login in memory and return the one-time token that redeems it
+    pub fn create_login_token(&self, login: ManualLogin) -> String {
+        let token = format!("login:{}", uuid::Uuid::new_v4());
+        let mut tokens = self.login_tokens.write().unwrap();
+        let now = chrono::Utc::now();
+        tokens.retain(|_, (_, created)| (now - *created).num_seconds() < LOGIN_TOKEN_TTL_SECS);
+        tokens.insert(token.clone(), (Arc::new(login), now));
+        token
+    }
+
+    /// Redeem a login token; it is removed whether or not it is still valid
+    pub fn take_login(&self, token: &str) -> Option<Arc<ManualLogin>> {
+        let (login, created) = self.login_tokens.write().unwrap().remove(token)?;
+        ((chrono::Utc::now() - created).num_seconds() < LOGIN_TOKEN_TTL_SECS).then_some(login)
+    }
+
+    /// Issue the token that acknowledges running `query` on `connection_string` once
+    pub fn create_confirmation(&self, connection_string: &str, query: &str) -> String {
+        let token = format!("confirm:{}", uuid::Uuid::new_v4());
+        let mut confirmations = self.confirmations.write().unwrap();
+        let now = chrono::Utc::now();
+        confirmations.retain(|_, (_, _, issued)| (now - *issued).num_seconds() < CONFIRMATION_TTL_SECS);
+        confirmations.insert(token.clone(), (connection_string.to_string(), query.to_string(), now));
+        token
+    }
+
+    /// Redeem an acknowledgement token; it only counts for the exact query it was issued for
+    pub fn take_confirmation(&self, token: &str, connection_string: &str, query: &str) -> bool {
+        match self.confirmations.write().unwrap().remove(token) {
+            Some((cs, q, issued)) => {
+                cs == connection_string && q == query && (chrono::Utc::now() - issued).num_seconds() < CONFIRMATION_TTL_SECS
+            }
+            None => false,
+        }
+    }
+
     pub fn get_session(&self, id: &str) -> Option<Session> {
-        self.sessions.read().unwrap().get(id).cloned()
+        let session = self.sessions.read().unwrap().get(id).cloned()?;
+        session.last_used.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+        Some(session)
     }
 
-    #[allow(dead_code)]
-    pub fn remove_session(&self, id: &str) {
-        self.sessions.write().unwrap().remove(id);
+    /// All open sessions, without marking them as used
+    pub fn list_sessions(&self) -> Vec<Session> {
+        self.sessions.read().unwrap().values().cloned().collect()
+    }
+
+    /// Swaps the pool and tunnel behind an existing session after a reconnect, returning the
+    /// previous pool so it can be closed
+    pub fn replace_connection(&self, id: &str, connection: SessionConnection) -> Option<PoolWrapper> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions.get_mut(id)?;
+        session.tunnel = connection.tunnel.map(Arc::new);
+        session.read_only = connection.read_only;
+        Some(std::mem::replace(&mut session.pool, connection.pool))
+    }
+
+    /// Swaps the pool behind an existing session, returning the previous one so it can be closed
+    pub fn replace_pool(&self, id: &str, pool: PoolWrapper) -> Option<PoolWrapper> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions.get_mut(id)?;
+        Some(std::mem::replace(&mut session.pool, pool))
+    }
+
+    /// Remove a session and close its pool
+    pub async fn close_session(&self, id: &str) -> bool {
+        let removed = self.sessions.write().unwrap().remove(id);
+        match removed {
+            Some(session) => {
+                session.pool.close().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// A window closed: close the sessions no other window uses any more
+    pub async fn release_window(&self, window: &str) {
+        let released: Vec<Session> = {
+            let mut sessions = self.sessions.write().unwrap();
+            let mut released = Vec::new();
+            sessions.retain(|_, s| {
+                if s.windows.remove(window) && s.windows.is_empty() {
+                    released.push(s.clone());
+                    return false;
+                }
+                true
+            });
+            released
+        };
+        for session in released {
+            log::info!("Closing session {} with its window {}", session.id, window);
+            session.pool.close().await;
+        }
+    }
+
+    /// The cached pool for a raw connection string, if one is open
+    pub fn cached_pool(&self, connection_string: &str) -> Option<PoolWrapper> {
+        let pools = self.pools.read().unwrap();
+        let cached = pools.get(connection_string)?;
+        cached.last_used.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+        Some(cached.pool.clone())
+    }
+
+    /// Cache a pool for a raw connection string. If another call cached one meanwhile, that
+    /// one is returned and `pool` closed.
+    pub async fn cache_pool(&self, connection_string: &str, pool: PoolWrapper) -> PoolWrapper {
+        let existing = self.cached_pool(connection_string);
+        if let Some(existing) = existing {
+            pool.close().await;
+            return existing;
+        }
+        let cached = CachedPool { pool: pool.clone(), last_used: AtomicI64::new(chrono::Utc::now().timestamp()) };
+        self.pools.write().unwrap().insert(connection_string.to_string(), cached);
+        pool
+    }
+
+    /// Close sessions and cached pools that have not been used for `IDLE_TTL_SECS`. Sessions
+    /// holding a manual login are kept while a window is open, as the login cannot be re-entered there.
+    pub async fn close_idle(&self) {
+        let now = chrono::Utc::now().timestamp();
+        let is_idle = |last_used: &AtomicI64| now - last_used.load(Ordering::Relaxed) >= IDLE_TTL_SECS;
+
+        let mut idle = Vec::new();
+        self.sessions.write().unwrap().retain(|id, s| {
+            if is_idle(&s.last_used) && (s.login.is_none() || s.windows.is_empty()) {
+                log::info!("Closing idle session {}", id);
+                idle.push(s.pool.clone());
+                return false;
+            }
+            true
+        });
+        self.pools.write().unwrap().retain(|_, cached| {
+            if is_idle(&cached.last_used) {
+                idle.push(cached.pool.clone());
+                return false;
+            }
+            true
+        });
+        for pool in idle {
+            pool.close().await;
+        }
     }
 }
 
@@ -81,12 +634,31 @@ pub async fn get_connection(state: &AppState, connection_string: &str) -> Result
         }
         return Err("Session expired or invalid".to_string());
     }
-    PoolWrapper::new(connection_string).await
+    if let Some(pool) = state.sessions.cached_pool(connection_string) {
+        return Ok(pool);
+    }
+    let pool = PoolWrapper::new(connection_string).await?;
+    Ok(state.sessions.cache_pool(connection_string, pool).await)
+}
+
+/// Whether `connection_string` is a session on a read-only connection
+pub fn is_read_only(state: &AppState, connection_string: &str) -> bool {
+    state.sessions.get_session(connection_string).is_some_and(|s| s.read_only)
+}
+
+/// Refuse a change through a read-only connection
+pub fn ensure_writable(state: &AppState, connection_string: &str) -> Result<(), String> {
+    if is_read_only(state, connection_string) {
+        return Err("This connection is read-only".to_string());
+    }
+    Ok(())
 }
 
 pub struct AppState {
     pub db: Pool<Sqlite>,
     pub sessions: SessionManager,
+    pub secrets: crate::resolver::Secrets,
+    pub ssh_known_hosts: std::path::PathBuf, // SSH host keys trusted on first use
 }
 
 pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, String> {
@@ -111,9 +683,16 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         .await
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
+    create_schema(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Create the app tables and migrate older schemas
+pub(crate) async fn create_schema(pool: &Pool<Sqlite>) -> Result<(), String> {
     // Create credentials table first (since connections references it)
     create_table_schema(
-        &pool,
+        pool,
         "credentials",
         "CREATE TABLE IF NOT EXISTS credentials (
             id TEXT PRIMARY KEY,
@@ -128,7 +707,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
     let connections_sql: String = sqlx::query_scalar(
         "SELECT sql FROM sqlite_master WHERE type='table' AND name='connections'"
     )
-    .fetch_optional(&pool)
+    .fetch_optional(pool)
     .await
     .unwrap_or(None)
     .unwrap_or_default();
@@ -140,16 +719,16 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         
         // Rename old table
         let _ = sqlx::query("DROP TABLE IF EXISTS connections_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
         let _ = sqlx::query("ALTER TABLE connections RENAME TO connections_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
     }
 
     // Create new connections table
     create_table_schema(
-        &pool,
+        pool,
         "connections",
         "CREATE TABLE IF NOT EXISTS connections (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -166,12 +745,34 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
     )
     .await?;
 
+    // TLS certificates, SSH tunnel and pool settings
+    for (column, definition) in [
+        ("ssl_ca_path", "TEXT"),
+        ("ssl_client_cert_path", "TEXT"),
+        ("ssl_client_key_path", "TEXT"),
+        ("ssh_host", "TEXT"),
+        ("ssh_port", "INTEGER"),
+        ("ssh_user", "TEXT"),
+        ("ssh_auth_method", "TEXT"),
+        ("ssh_key_path", "TEXT"),
+        ("ssh_credential_id", "TEXT"),
+        ("pool_max_connections", "INTEGER"),
+        ("pool_idle_timeout_secs", "INTEGER"),
+        ("pool_statement_timeout_secs", "INTEGER"),
+        ("pool_connect_timeout_secs", "INTEGER"),
+        ("pool_test_before_acquire", "INTEGER"),
+        ("read_only", "INTEGER NOT NULL DEFAULT 0"),
+        ("environment", "TEXT"),
+    ] {
+        add_column_if_missing(pool, "connections", column, definition).await?;
+    }
+
     // Migrate old connections if needed
     if needs_migration {
         let old_conns: Vec<(i64, String, String, String)> = sqlx::query_as(
             "SELECT id, name, connection_string, datetime(created_at) as created_at FROM connections_old"
         )
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await
         .unwrap_or_default();
 
@@ -189,29 +790,29 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
             .bind(port)
             .bind(&database_name)
             .bind(&created_at)
-            .execute(&pool)
+            .execute(pool)
             .await;
             
             println!("Migrated connection '{}' (credentials lost - please re-add)", name);
         }
 
         let _ = sqlx::query("DROP TABLE connections_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
         println!("Connection migration completed.");
     }
 
     // Cleanup any stale migration tables from previous runs
     let _ = sqlx::query("DROP TABLE IF EXISTS tags_old")
-        .execute(&pool)
+        .execute(pool)
         .await;
     let _ = sqlx::query("DROP TABLE IF EXISTS table_tags_old")
-        .execute(&pool)
+        .execute(pool)
         .await;
 
 
     create_table_schema(
-        &pool,
+        pool,
         "tags",
         "CREATE TABLE IF NOT EXISTS tags (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -229,7 +830,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
     // We check the actual SQL definition of the table
     let tags_table_sql: String =
         sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type='table' AND name='tags'")
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await
             .unwrap_or_default();
 
@@ -239,15 +840,15 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
 
         // 1. Rename existing table
         let _ = sqlx::query("DROP TABLE IF EXISTS tags_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
         let _ = sqlx::query("ALTER TABLE tags RENAME TO tags_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
 
         // 2. Create new table with correct schema
         create_table_schema(
-            &pool,
+            pool,
             "tags",
             "CREATE TABLE IF NOT EXISTS tags (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -268,16 +869,16 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
             "INSERT INTO tags (id, name, color, created_at) 
              SELECT id, name, color, created_at FROM tags_old",
         )
-        .execute(&pool)
+        .execute(pool)
         .await;
 
         // 4. Drop old table
-        let _ = sqlx::query("DROP TABLE tags_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE tags_old").execute(pool).await;
         println!("Migration of tags completed.");
     }
 
     create_table_schema(
-        &pool,
+        pool,
         "table_tags",
         "CREATE TABLE IF NOT EXISTS table_tags (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -297,7 +898,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
     let table_sql: String = sqlx::query_scalar(
         "SELECT sql FROM sqlite_master WHERE type='table' AND name='table_tags'",
     )
-    .fetch_one(&pool)
+    .fetch_one(pool)
     .await
     .unwrap_or_default();
 
@@ -319,15 +920,15 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
 
         // 1. Rename existing table
         let _ = sqlx::query("DROP TABLE IF EXISTS table_tags_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
         let _ = sqlx::query("ALTER TABLE table_tags RENAME TO table_tags_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
 
         // 2. Create new table with correct schema
         create_table_schema(
-            &pool,
+            pool,
             "table_tags",
             "CREATE TABLE IF NOT EXISTS table_tags (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -347,7 +948,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         let has_db_col_old: i32 = sqlx::query_scalar(
             "SELECT COUNT(*) FROM pragma_table_info('table_tags_old') WHERE name='database_name'",
         )
-        .fetch_one(&pool)
+        .fetch_one(pool)
         .await
         .unwrap_or(0);
 
@@ -357,7 +958,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
                 "INSERT INTO table_tags (id, table_name, connection_id, tag_id, database_name) 
                  SELECT id, table_name, connection_id, tag_id, database_name FROM table_tags_old",
             )
-            .execute(&pool)
+            .execute(pool)
             .await;
         } else {
             // Old table does NOT have column, default to empty string
@@ -365,31 +966,31 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
                 "INSERT INTO table_tags (id, table_name, connection_id, tag_id, database_name) 
                  SELECT id, table_name, connection_id, tag_id, '' FROM table_tags_old",
             )
-            .execute(&pool)
+            .execute(pool)
             .await;
         }
 
         // 4. Drop old table
         let _ = sqlx::query("DROP TABLE table_tags_old")
-            .execute(&pool)
+            .execute(pool)
             .await;
         println!("Migration of table_tags completed.");
     }
 
     // Migration: Check if saved_queries has database_name column
     let sq_sql: String = sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type='table' AND name='saved_queries'")
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await
         .unwrap_or_default()
         .unwrap_or_default();
 
     if !sq_sql.contains("database_name") {
         println!("Migrating saved_queries schema (adding database_name)...");
-        let _ = sqlx::query("DROP TABLE IF EXISTS saved_queries_old").execute(&pool).await;
-        let _ = sqlx::query("ALTER TABLE saved_queries RENAME TO saved_queries_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE IF EXISTS saved_queries_old").execute(pool).await;
+        let _ = sqlx::query("ALTER TABLE saved_queries RENAME TO saved_queries_old").execute(pool).await;
 
         create_table_schema(
-            &pool,
+            pool,
             "saved_queries",
             "CREATE TABLE IF NOT EXISTS saved_queries (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -405,16 +1006,16 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         let _ = sqlx::query(
             "INSERT INTO saved_queries (id, name, query, connection_id, created_at)
              SELECT id, name, query, connection_id, created_at FROM saved_queries_old"
-        ).execute(&pool).await;
+        ).execute(pool).await;
 
-        let _ = sqlx::query("DROP TABLE saved_queries_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE saved_queries_old").execute(pool).await;
     } else if sq_sql.contains("connections_old") {
         println!("Migrating saved_queries schema (fixing stale foreign key)...");
-        let _ = sqlx::query("DROP TABLE IF EXISTS saved_queries_old").execute(&pool).await;
-        let _ = sqlx::query("ALTER TABLE saved_queries RENAME TO saved_queries_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE IF EXISTS saved_queries_old").execute(pool).await;
+        let _ = sqlx::query("ALTER TABLE saved_queries RENAME TO saved_queries_old").execute(pool).await;
 
         create_table_schema(
-            &pool,
+            pool,
             "saved_queries",
             "CREATE TABLE IF NOT EXISTS saved_queries (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -430,13 +1031,13 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         let _ = sqlx::query(
             "INSERT INTO saved_queries (id, name, query, connection_id, database_name, created_at)
              SELECT id, name, query, connection_id, database_name, created_at FROM saved_queries_old"
-        ).execute(&pool).await;
+        ).execute(pool).await;
 
-        let _ = sqlx::query("DROP TABLE saved_queries_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE saved_queries_old").execute(pool).await;
     }
 
     create_table_schema(
-        &pool,
+        pool,
         "saved_queries",
         "CREATE TABLE IF NOT EXISTS saved_queries (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -452,18 +1053,18 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
 
     // Migration: Check if saved_functions has database_name column
     let sf_sql: String = sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type='table' AND name='saved_functions'")
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await
         .unwrap_or_default()
         .unwrap_or_default();
 
     if !sf_sql.contains("database_name") {
         println!("Migrating saved_functions schema (adding database_name)...");
-        let _ = sqlx::query("DROP TABLE IF EXISTS saved_functions_old").execute(&pool).await;
-        let _ = sqlx::query("ALTER TABLE saved_functions RENAME TO saved_functions_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE IF EXISTS saved_functions_old").execute(pool).await;
+        let _ = sqlx::query("ALTER TABLE saved_functions RENAME TO saved_functions_old").execute(pool).await;
 
         create_table_schema(
-            &pool,
+            pool,
             "saved_functions",
             "CREATE TABLE IF NOT EXISTS saved_functions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -479,16 +1080,16 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         let _ = sqlx::query(
             "INSERT INTO saved_functions (id, name, function_body, connection_id, created_at)
              SELECT id, name, function_body, connection_id, created_at FROM saved_functions_old"
-        ).execute(&pool).await;
+        ).execute(pool).await;
 
-        let _ = sqlx::query("DROP TABLE saved_functions_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE saved_functions_old").execute(pool).await;
     } else if sf_sql.contains("connections_old") {
         println!("Migrating saved_functions schema (fixing stale foreign key)...");
-        let _ = sqlx::query("DROP TABLE IF EXISTS saved_functions_old").execute(&pool).await;
-        let _ = sqlx::query("ALTER TABLE saved_functions RENAME TO saved_functions_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE IF EXISTS saved_functions_old").execute(pool).await;
+        let _ = sqlx::query("ALTER TABLE saved_functions RENAME TO saved_functions_old").execute(pool).await;
 
         create_table_schema(
-            &pool,
+            pool,
             "saved_functions",
             "CREATE TABLE IF NOT EXISTS saved_functions (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -504,13 +1105,13 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
         let _ = sqlx::query(
             "INSERT INTO saved_functions (id, name, function_body, connection_id, database_name, created_at)
              SELECT id, name, function_body, connection_id, database_name, created_at FROM saved_functions_old"
-        ).execute(&pool).await;
+        ).execute(pool).await;
 
-        let _ = sqlx::query("DROP TABLE saved_functions_old").execute(&pool).await;
+        let _ = sqlx::query("DROP TABLE saved_functions_old").execute(pool).await;
     }
 
     create_table_schema(
-        &pool,
+        pool,
         "saved_functions",
         "CREATE TABLE IF NOT EXISTS saved_functions (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -526,7 +1127,7 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
 
     // Create table_filters table to store filter configurations per table
     create_table_schema(
-        &pool,
+        pool,
         "table_filters",
         "CREATE TABLE IF NOT EXISTS table_filters (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -541,7 +1142,77 @@ pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>, Str
     )
     .await?;
 
-    Ok(pool)
+    // SQLite files attached to a connection; re-attached whenever a session pool is opened
+    create_table_schema(
+        pool,
+        "attached_sqlite_databases",
+        "CREATE TABLE IF NOT EXISTS attached_sqlite_databases (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            connection_id INTEGER NOT NULL,
+            schema_name TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(connection_id) REFERENCES connections(id) ON DELETE CASCADE,
+            UNIQUE(connection_id, schema_name)
+        );",
+    )
+    .await?;
+
+    // Batches applied through apply_batch_changes, with the inverse operations used to revert them
+    create_table_schema(
+        pool,
+        "applied_batches",
+        "CREATE TABLE IF NOT EXISTS applied_batches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            connection_id INTEGER NOT NULL,
+            database_name TEXT,
+            changes_json TEXT NOT NULL,
+            inverse_json TEXT,
+            reverts_batch_id INTEGER,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            reverted_at DATETIME,
+            FOREIGN KEY(connection_id) REFERENCES connections(id) ON DELETE CASCADE
+        );",
+    )
+    .await?;
+
+    // Staged (not yet applied) changelog edits per table, so drafts survive restarts
+    create_table_schema(
+        pool,
+        "pending_changes",
+        "CREATE TABLE IF NOT EXISTS pending_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            connection_id INTEGER NOT NULL,
+            database_name TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            changes_json TEXT NOT NULL,
+            change_count INTEGER NOT NULL DEFAULT 0,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(connection_id) REFERENCES connections(id) ON DELETE CASCADE,
+            UNIQUE(connection_id, database_name, table_name)
+        );",
+    )
+    .await?;
+
+    // Whether destructive statements need confirmation, per connection environment
+    create_table_schema(
+        pool,
+        "environment_guards",
+        "CREATE TABLE IF NOT EXISTS environment_guards (
+            environment TEXT PRIMARY KEY,
+            confirm_destructive INTEGER NOT NULL DEFAULT 1
+        );",
+    )
+    .await?;
+    sqlx::query(
+        "INSERT OR IGNORE INTO environment_guards (environment, confirm_destructive) \
+         VALUES ('development', 0), ('staging', 1), ('production', 1)"
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to seed environment guards: {}", e))?;
+
+    Ok(())
 }
 
 async fn create_table_schema(
@@ -556,6 +1227,28 @@ async fn create_table_schema(
     Ok(())
 }
 
+/// Add a column to an existing app table unless a previous run already did
+async fn add_column_if_missing(
+    pool: &Pool<Sqlite>,
+    table_name: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table_name)
+        .bind(column)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to inspect {} table: {}", table_name, e))?;
+    if exists == 0 {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table_name, column, definition))
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to add {}.{}: {}", table_name, column, e))?;
+    }
+    Ok(())
+}
+
 /// Parse a legacy connection string to extract components
 /// Returns (db_type, host, port, database_name)
 fn parse_connection_string(conn_str: &str) -> (String, String, i32, Option<String>) {
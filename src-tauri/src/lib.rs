@@ -1,7 +1,10 @@
 mod commands;
 mod db;
 mod models;
+mod resolver;
+mod tunnel;
 mod utils;
+mod vault;
 
 use db::AppState;
 use tauri::Manager;
@@ -27,11 +30,20 @@ pub fn run() {
             commands::credential::list_credentials,
             commands::credential::delete_credential,
             commands::credential::update_credential,
+            commands::vault::get_secret_store_status,
+            commands::vault::unlock_vault,
+            commands::vault::lock_vault,
+            commands::vault::migrate_to_vault,
+            commands::vault::migrate_to_keyring,
             // DB Ops
             commands::db_ops::get_tables,
             commands::db_ops::execute_query,
+            commands::guard::review_query,
+            commands::guard::list_environment_guards,
+            commands::guard::set_environment_guard,
             commands::db_ops::get_columns,
             commands::db_ops::get_table_schema,
+            commands::db_ops::get_table_indexes,
             commands::db_ops::truncate_table,
             commands::db_ops::drop_table,
             commands::db_ops::duplicate_table,
@@ -43,11 +55,38 @@ pub fn run() {
             commands::db_ops::delete_database,
             commands::db_ops::get_table_data,
             commands::db_ops::create_table,
+            // Import
+            commands::import::preview_csv,
+            commands::import::import_csv,
+            commands::import::import_json,
+            // Export
+            commands::export::export_json,
+            commands::export::export_parquet,
+            commands::export::export_xlsx,
+            // Schema Diff
+            commands::schema_diff::diff_schema,
+            // Data Compare
+            commands::data_compare::compare_table_data,
+            // SQLite
+            commands::sqlite::attach_database,
+            commands::sqlite::detach_database,
+            commands::sqlite::list_attached_databases,
             // CRUD Operations
             commands::crud::update_record,
             commands::crud::delete_record,
             commands::crud::insert_record,
             commands::crud::apply_batch_changes,
+            commands::crud::apply_batch_changes_with_report,
+            commands::crud::preview_batch_changes,
+            commands::crud::preview_bulk_update,
+            commands::crud::bulk_update,
+            commands::crud::export_batch_script,
+            commands::changelog::list_applied_batches,
+            commands::changelog::revert_batch,
+            commands::drafts::save_pending_changes,
+            commands::drafts::load_pending_changes,
+            commands::drafts::list_pending_changes,
+            commands::drafts::discard_pending_changes,
             // Tags
             commands::tag::create_tag,
             commands::tag::update_tag,
@@ -77,7 +116,22 @@ pub fn run() {
             commands::filter::delete_table_filters,
             // Session
             commands::session::create_session,
+            commands::session::get_session_health,
+            commands::session::list_sessions,
+            commands::session::close_session,
         ])
+        .on_window_event(|window, event| {
+            // Sessions belong to the windows that opened them
+            if let tauri::WindowEvent::Destroyed = event {
+                let handle = window.app_handle().clone();
+                let label = window.label().to_string();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(state) = handle.try_state::<AppState>() {
+                        state.sessions.release_window(&label).await;
+                    }
+                });
+            }
+        })
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -99,9 +153,27 @@ pub fn run() {
             tauri::async_runtime::block_on(async move {
                 match db::init_db(&handle).await {
                     Ok(pool) => {
+                        let app_data_dir = handle.path().app_data_dir().unwrap_or_default();
                         handle.manage(AppState { 
                             db: pool,
-                            sessions: db::SessionManager::new()
+                            sessions: db::SessionManager::new(),
+                            secrets: resolver::Secrets {
+                                keyring: Box::new(resolver::KeyringStore(resolver::OsKeyring)),
+                                vault: vault::VaultStore::new(app_data_dir.join("credentials.vault")),
+                            },
+                            ssh_known_hosts: app_data_dir.join("ssh_known_hosts"),
+                        });
+
+                        // Close sessions and pools nothing has used for a while
+                        let sweeper = handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let mut interval = tokio::time::interval(db::IDLE_SWEEP_INTERVAL);
+                            loop {
+                                interval.tick().await;
+                                if let Some(state) = sweeper.try_state::<AppState>() {
+                                    state.sessions.close_idle().await;
+                                }
+                            }
                         });
                     }
                     Err(e) => {
@@ -11,10 +11,99 @@ pub struct Connection {
     pub port: i32,
     pub database_name: Option<String>,
     pub credential_id: Option<String>, // Reference to keyring entry
-    pub ssl_mode: Option<String>,
+    pub ssl_mode: Option<String>,         // disable, prefer, require, verify-ca or verify-full
+    pub ssl_ca_path: Option<String>,
+    pub ssl_client_cert_path: Option<String>,
+    pub ssl_client_key_path: Option<String>,
+    pub ssh_host: Option<String>,     // Bastion host; no tunnel when empty
+    pub ssh_port: Option<i32>,
+    pub ssh_user: Option<String>,
+    pub ssh_auth_method: Option<String>, // "agent", "key" or "password"
+    pub ssh_key_path: Option<String>,
+    pub ssh_credential_id: Option<String>, // SSH password, or the key's passphrase
+    pub pool_max_connections: Option<i64>,
+    pub pool_idle_timeout_secs: Option<i64>,
+    pub pool_statement_timeout_secs: Option<i64>,
+    pub pool_connect_timeout_secs: Option<i64>,
+    pub pool_test_before_acquire: Option<bool>,
+    pub read_only: Option<bool>, // Sessions open read-only transactions and the app refuses changes
+    pub environment: Option<String>, // "development", "staging" or "production"; see environment_guards
     pub created_at: String,
 }
 
+/// Columns selected into `Connection`
+pub const CONNECTION_COLUMNS: &str = "id, name, db_type, host, port, database_name, credential_id, ssl_mode, \
+    ssl_ca_path, ssl_client_cert_path, ssl_client_key_path, ssh_host, ssh_port, ssh_user, ssh_auth_method, ssh_key_path, ssh_credential_id, \
+    pool_max_connections, pool_idle_timeout_secs, pool_statement_timeout_secs, pool_connect_timeout_secs, \
+    pool_test_before_acquire, read_only, environment, datetime(created_at) as created_at";
+
+/// TLS settings of a connection; empty fields leave the driver defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TlsSettings {
+    pub mode: Option<String>,
+    pub ca_path: Option<String>,
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+}
+
+/// Pool settings of a connection; empty fields leave the sqlx defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PoolSettings {
+    pub max_connections: Option<u32>,
+    pub idle_timeout_secs: Option<u64>,
+    pub statement_timeout_secs: Option<u64>, // Postgres and MySQL only; SQLite has no statement timeout
+    pub connect_timeout_secs: Option<u64>,
+    pub test_before_acquire: Option<bool>, // Ping connections before use, dropping ones that died (default on)
+    #[serde(skip)]
+    pub read_only: bool, // From the connection's read-only flag, not edited with the pool settings
+}
+
+/// SSH tunnel settings of a connection, as edited in the connection form
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SshSettings {
+    pub host: String,
+    pub port: i32,
+    pub user: String,
+    pub auth_method: String,
+    pub key_path: Option<String>,
+    pub credential_id: Option<String>,
+}
+
+impl Connection {
+    pub fn tls_settings(&self) -> TlsSettings {
+        TlsSettings {
+            mode: self.ssl_mode.clone(),
+            ca_path: self.ssl_ca_path.clone(),
+            client_cert_path: self.ssl_client_cert_path.clone(),
+            client_key_path: self.ssl_client_key_path.clone(),
+        }
+    }
+
+    pub fn pool_settings(&self) -> PoolSettings {
+        let positive = |n: Option<i64>| n.filter(|n| *n > 0);
+        PoolSettings {
+            max_connections: positive(self.pool_max_connections).and_then(|n| u32::try_from(n).ok()),
+            idle_timeout_secs: positive(self.pool_idle_timeout_secs).map(|n| n as u64),
+            statement_timeout_secs: positive(self.pool_statement_timeout_secs).map(|n| n as u64),
+            connect_timeout_secs: positive(self.pool_connect_timeout_secs).map(|n| n as u64),
+            test_before_acquire: self.pool_test_before_acquire,
+            read_only: self.read_only.unwrap_or(false),
+        }
+    }
+
+    pub fn ssh_settings(&self) -> Option<SshSettings> {
+        let host = self.ssh_host.clone().filter(|h| !h.trim().is_empty())?;
+        Some(SshSettings {
+            host,
+            port: self.ssh_port.unwrap_or(22),
+            user: self.ssh_user.clone().unwrap_or_default(),
+            auth_method: self.ssh_auth_method.clone().unwrap_or_else(|| "agent".to_string()),
+            key_path: self.ssh_key_path.clone(),
+            credential_id: self.ssh_credential_id.clone(),
+        })
+    }
+}
+
 
 
 /// Credential entry - stored in app DB, actual password in OS keyring
@@ -47,6 +136,9 @@ pub struct TableTag {
 pub struct ForeignKey {
     pub referenced_table: String,
     pub referenced_column: String,
+    /// Referential actions as reported by the database (e.g. CASCADE), when known
+    pub on_delete: Option<String>,
+    pub on_update: Option<String>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -67,6 +159,21 @@ pub struct ColumnSchema {
     pub foreign_key: Option<ForeignKey>,
 }
 
+/// A database attached to a SQLite connection via ATTACH DATABASE
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AttachedDatabase {
+    pub schema_name: String,
+    pub file_path: String,
+}
+
+/// Secondary index on a table (primary keys are reported through ColumnSchema)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IndexSchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
 pub struct SavedQuery {
     pub id: i64,
@@ -0,0 +1,561 @@
+//! Turns a saved connection id into what is needed to connect: the connection metadata,
+//! its credential and the password from the secret store. Every command that connects to a
+//! saved connection goes through here.
+
+use crate::db::{AppState, DbConnectOptions};
+use crate::models::{Connection, Credential, SshSettings, CONNECTION_COLUMNS};
+use crate::tunnel::{connect_through, SshAuth, SshConfig, SshTunnel};
+use crate::vault::VaultStore;
+use sqlx::mysql::MySqlConnectOptions;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Pool, Sqlite};
+use std::fmt;
+
+/// Keyring service all passwords are stored under
+pub const KEYRING_SERVICE: &str = "com.dbplus.sqlclient";
+/// Service sessions used to read from before it was unified; passwords found there are moved over
+const LEGACY_KEYRING_SERVICE: &str = "sql-ui-app";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialError {
+    ConnectionNotFound(i64),
+    CredentialMissing(String),  // The connection references a credential that no longer exists
+    KeyringUnavailable(String), // The OS keyring could not be reached
+    PasswordNotFound(String),   // The credential exists but has no password stored
+    VaultLocked,                // Passwords are in the encrypted vault, which has not been unlocked
+    Vault(String),
+    Database(String),
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::ConnectionNotFound(id) => write!(f, "Connection {} not found", id),
+            CredentialError::CredentialMissing(id) => {
+                write!(f, "Credential {} not found; edit the connection and choose a credential", id)
+            }
+            CredentialError::KeyringUnavailable(e) => write!(f, "The system keyring is unavailable: {}", e),
+            CredentialError::PasswordNotFound(id) => {
+                write!(f, "No password stored for credential {}; update the credential to save it again", id)
+            }
+            CredentialError::VaultLocked => write!(f, "The credential vault is locked; unlock it with the master password"),
+            CredentialError::Vault(e) => write!(f, "Credential vault error: {}", e),
+            CredentialError::Database(e) => write!(f, "Failed to read connection settings: {}", e),
+        }
+    }
+}
+
+impl From<CredentialError> for String {
+    fn from(e: CredentialError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Where passwords live. The app uses the OS keyring or the encrypted vault; tests can substitute an in-memory store.
+pub trait SecretStore: Send + Sync {
+    fn get_password(&self, credential_id: &str) -> Result<String, CredentialError>;
+    fn set_password(&self, credential_id: &str, password: &str) -> Result<(), CredentialError>;
+    fn delete_password(&self, credential_id: &str) -> Result<(), CredentialError>;
+}
+
+/// Password entries keyed by keyring service. `KeyringStore` looks passwords up and moves
+/// legacy entries through this, so that logic can run against an in-memory backend in tests.
+pub trait KeyringBackend: Send + Sync {
+    fn get(&self, service: &str, credential_id: &str) -> keyring::Result<String>;
+    fn set(&self, service: &str, credential_id: &str, password: &str) -> keyring::Result<()>;
+    fn delete(&self, service: &str, credential_id: &str) -> keyring::Result<()>;
+}
+
+/// The OS keyring
+pub struct OsKeyring;
+
+impl KeyringBackend for OsKeyring {
+    fn get(&self, service: &str, credential_id: &str) -> keyring::Result<String> {
+        keyring::Entry::new(service, credential_id)?.get_password()
+    }
+
+    fn set(&self, service: &str, credential_id: &str, password: &str) -> keyring::Result<()> {
+        keyring::Entry::new(service, credential_id)?.set_password(password)
+    }
+
+    fn delete(&self, service: &str, credential_id: &str) -> keyring::Result<()> {
+        keyring::Entry::new(service, credential_id)?.delete_credential()
+    }
+}
+
+pub struct KeyringStore<B: KeyringBackend = OsKeyring>(pub B);
+
+fn keyring_error(credential_id: &str, e: keyring::Error) -> CredentialError {
+    match e {
+        keyring::Error::NoEntry => CredentialError::PasswordNotFound(credential_id.to_string()),
+        other => CredentialError::KeyringUnavailable(other.to_string()),
+    }
+}
+
+impl<B: KeyringBackend> SecretStore for KeyringStore<B> {
+    fn get_password(&self, credential_id: &str) -> Result<String, CredentialError> {
+        match self.0.get(KEYRING_SERVICE, credential_id) {
+            Ok(password) => Ok(password),
+            Err(keyring::Error::NoEntry) => {
+                let password = self
+                    .0
+                    .get(LEGACY_KEYRING_SERVICE, credential_id)
+                    .map_err(|e| keyring_error(credential_id, e))?;
+                if self.set_password(credential_id, &password).is_ok() {
+                    let _ = self.0.delete(LEGACY_KEYRING_SERVICE, credential_id);
+                }
+                Ok(password)
+            }
+            Err(e) => Err(keyring_error(credential_id, e)),
+        }
+    }
+
+    fn set_password(&self, credential_id: &str, password: &str) -> Result<(), CredentialError> {
+        self.0
+            .set(KEYRING_SERVICE, credential_id, password)
+            .map_err(|e| keyring_error(credential_id, e))
+    }
+
+    fn delete_password(&self, credential_id: &str) -> Result<(), CredentialError> {
+        match self.0.delete(KEYRING_SERVICE, credential_id) {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keyring_error(credential_id, e)),
+        }
+    }
+}
+
+/// The secret stores of this install: the encrypted vault once one has been created, else the OS keyring
+pub struct Secrets {
+    pub keyring: Box<dyn SecretStore>,
+    pub vault: VaultStore,
+}
+
+impl Secrets {
+    pub fn active(&self) -> &dyn SecretStore {
+        if self.vault.exists() {
+            &self.vault
+        } else {
+            self.keyring.as_ref()
+        }
+    }
+
+    pub fn get_password(&self, credential_id: &str) -> Result<String, CredentialError> {
+        self.active().get_password(credential_id)
+    }
+
+    pub fn set_password(&self, credential_id: &str, password: &str) -> Result<(), CredentialError> {
+        self.active().set_password(credential_id, password)
+    }
+
+    pub fn delete_password(&self, credential_id: &str) -> Result<(), CredentialError> {
+        self.active().delete_password(credential_id)
+    }
+}
+
+/// A saved connection with its login resolved, ready to connect with (no Debug: holds the password)
+#[derive(Clone)]
+pub struct ResolvedConnection {
+    pub connection: Connection,
+    pub database_name: Option<String>, // Requested database, else the connection's default
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ResolvedConnection {
+    /// Options to connect with, built from the parts rather than parsed from a URL.
+    /// A host starting with `/` is a Unix socket (the socket directory on Postgres).
+    /// TLS settings are applied by `DbConnectOptions::with_tls`.
+    pub fn connect_options(&self) -> Result<DbConnectOptions, String> {
+        let conn = &self.connection;
+        let host = conn.host.trim();
+        let database = self.database_name.as_deref().filter(|d| !d.is_empty());
+        let port = u16::try_from(conn.port).map_err(|_| format!("Invalid port {}", conn.port))?;
+
+        let options = match conn.db_type.as_str() {
+            "mysql" => {
+                let mut options = MySqlConnectOptions::new();
+                options = if host.starts_with('/') {
+                    options.socket(host)
+                } else {
+                    options.host(host.trim_start_matches('[').trim_end_matches(']')).port(port)
+                };
+                if let Some(user) = &self.username {
+                    options = options.username(user);
+                }
+                if let Some(pass) = &self.password {
+                    options = options.password(pass);
+                }
+                if let Some(db) = database {
+                    options = options.database(db);
+                }
+                DbConnectOptions::Mysql(options)
+            }
+            "postgres" => {
+                let mut options = PgConnectOptions::new().port(port);
+                options = if host.starts_with('/') {
+                    options.socket(host)
+                } else {
+                    options.host(host.trim_start_matches('[').trim_end_matches(']'))
+                };
+                if let Some(user) = &self.username {
+                    options = options.username(user);
+                }
+                if let Some(pass) = &self.password {
+                    options = options.password(pass);
+                }
+                if let Some(db) = database {
+                    options = options.database(db);
+                }
+                DbConnectOptions::Postgres(options)
+            }
+            // For SQLite, host is the file path
+            "sqlite" => DbConnectOptions::Sqlite(SqliteConnectOptions::new().filename(host)),
+            other => return Err(format!("Unsupported database type: {}", other)),
+        };
+        options.with_tls(&conn.tls_settings())
+    }
+
+    /// Connect options, through a newly opened SSH tunnel when the connection has one.
+    /// The tunnel must be kept alive for as long as connections made with the options.
+    pub async fn open_tunnel(&self, state: &AppState) -> Result<(DbConnectOptions, Option<SshTunnel>), String> {
+        let options = self.connect_options()?;
+        match self.connection.ssh_settings() {
+            Some(ssh) => {
+                let (options, tunnel) = connect_through(resolve_ssh(state, &ssh).await?, options).await?;
+                Ok((options, Some(tunnel)))
+            }
+            None => Ok((options, None)),
+        }
+    }
+}
+
+/// SSH login for a tunnel, with the password or key passphrase read from the secret store
+pub async fn resolve_ssh(state: &AppState, ssh: &SshSettings) -> Result<SshConfig, String> {
+    let secret = match ssh.credential_id.as_deref().filter(|c| !c.is_empty()) {
+        Some(cred_id) => Some(resolve_credential(state, cred_id).await?.1),
+        None => None,
+    };
+    let auth = match ssh.auth_method.as_str() {
+        "agent" => SshAuth::Agent,
+        "key" => SshAuth::KeyFile {
+            path: ssh.key_path.clone().filter(|p| !p.is_empty()).ok_or("No SSH key file is set")?,
+            passphrase: secret,
+        },
+        "password" => SshAuth::Password(secret.ok_or("SSH password authentication needs a credential")?),
+        other => return Err(format!("Unknown SSH authentication method: {}", other)),
+    };
+    if ssh.user.is_empty() {
+        return Err("No SSH user is set".to_string());
+    }
+
+    Ok(SshConfig {
+        host: ssh.host.trim().to_string(),
+        port: u16::try_from(ssh.port).map_err(|_| format!("Invalid SSH port {}", ssh.port))?,
+        user: ssh.user.clone(),
+        auth,
+        known_hosts: state.ssh_known_hosts.clone(),
+    })
+}
+
+pub async fn load_connection(db: &Pool<Sqlite>, connection_id: i64) -> Result<Connection, CredentialError> {
+    sqlx::query_as::<_, Connection>(&format!("SELECT {} FROM connections WHERE id = ?", CONNECTION_COLUMNS))
+    .bind(connection_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| CredentialError::Database(e.to_string()))?
+    .ok_or(CredentialError::ConnectionNotFound(connection_id))
+}
+
+/// Username and password of a stored credential
+pub async fn resolve_credential(state: &AppState, credential_id: &str) -> Result<(String, String), CredentialError> {
+    let cred = sqlx::query_as::<_, Credential>(
+        "SELECT id, name, username, datetime(created_at) as created_at FROM credentials WHERE id = ?"
+    )
+    .bind(credential_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| CredentialError::Database(e.to_string()))?
+    .ok_or_else(|| CredentialError::CredentialMissing(credential_id.to_string()))?;
+
+    let password = state.secrets.get_password(&cred.id)?;
+    Ok((cred.username, password))
+}
+
+/// Resolve a saved connection using its stored credential (if any)
+pub async fn resolve_connection(
+    state: &AppState,
+    connection_id: i64,
+    database_name: Option<String>,
+) -> Result<ResolvedConnection, CredentialError> {
+    let connection = load_connection(&state.db, connection_id).await?;
+    let (username, password) = match &connection.credential_id {
+        Some(cred_id) => {
+            let (username, password) = resolve_credential(state, cred_id).await?;
+            (Some(username), Some(password))
+        }
+        None => (None, None),
+    };
+
+    Ok(ResolvedConnection {
+        database_name: database_name.or_else(|| connection.database_name.clone()),
+        connection,
+        username,
+        password,
+    })
+}
+
+/// Resolve a saved connection with a login entered by the user instead of the stored one
+pub async fn resolve_connection_with_login(
+    state: &AppState,
+    connection_id: i64,
+    database_name: Option<String>,
+    username: String,
+    password: String,
+) -> Result<ResolvedConnection, CredentialError> {
+    let connection = load_connection(&state.db, connection_id).await?;
+    Ok(ResolvedConnection {
+        database_name: database_name.or_else(|| connection.database_name.clone()),
+        connection,
+        username: Some(username),
+        password: Some(password),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::db::SessionManager;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Keyring entries in memory; `unavailable` makes every call fail like an unreachable keyring
+    #[derive(Default)]
+    pub(crate) struct MemoryKeyring {
+        pub(crate) entries: Mutex<HashMap<(String, String), String>>,
+        pub(crate) unavailable: bool,
+    }
+
+    impl MemoryKeyring {
+        pub(crate) fn with(entries: &[(&str, &str, &str)]) -> Self {
+            let entries = entries
+                .iter()
+                .map(|(service, id, password)| ((service.to_string(), id.to_string()), password.to_string()))
+                .collect();
+            MemoryKeyring { entries: Mutex::new(entries), unavailable: false }
+        }
+
+        fn check(&self) -> keyring::Result<()> {
+            if self.unavailable {
+                return Err(keyring::Error::NoStorageAccess("keyring locked".into()));
+            }
+            Ok(())
+        }
+
+        fn stored(&self, service: &str, credential_id: &str) -> Option<String> {
+            self.entries.lock().unwrap().get(&(service.to_string(), credential_id.to_string())).cloned()
+        }
+    }
+
+    impl KeyringBackend for MemoryKeyring {
+        fn get(&self, service: &str, credential_id: &str) -> keyring::Result<String> {
+            self.check()?;
+            self.stored(service, credential_id).ok_or(keyring::Error::NoEntry)
+        }
+
+        fn set(&self, service: &str, credential_id: &str, password: &str) -> keyring::Result<()> {
+            self.check()?;
+            self.entries
+                .lock()
+                .unwrap()
+                .insert((service.to_string(), credential_id.to_string()), password.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, service: &str, credential_id: &str) -> keyring::Result<()> {
+            self.check()?;
+            self.entries
+                .lock()
+                .unwrap()
+                .remove(&(service.to_string(), credential_id.to_string()))
+                .map(|_| ())
+                .ok_or(keyring::Error::NoEntry)
+        }
+    }
+
+    /// App state over an in-memory database holding one connection that uses credential `cred-1`
+    pub(crate) async fn state_with(keyring: MemoryKeyring, credential_exists: bool) -> AppState {
+        let options = "sqlite::memory:".parse::<SqliteConnectOptions>().unwrap().foreign_keys(false);
+        let db = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        crate::db::create_schema(&db).await.unwrap();
+        if credential_exists {
+            sqlx::query("INSERT INTO credentials (id, name, username) VALUES ('cred-1', 'app', 'app_user')")
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO connections (id, name, db_type, host, port, credential_id) \
+             VALUES (1, 'app', 'postgres', 'db', 5432, 'cred-1')",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+
+        AppState {
+            db,
+            sessions: SessionManager::new(),
+            secrets: Secrets {
+                keyring: Box::new(KeyringStore(keyring)),
+                vault: VaultStore::new(std::env::temp_dir().join(format!("{}.vault", uuid::Uuid::new_v4()))),
+            },
+            ssh_known_hosts: std::env::temp_dir().join("ssh_known_hosts"),
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_the_stored_login() {
+        let keyring = MemoryKeyring::with(&[(KEYRING_SERVICE, "cred-1", "secret")]);
+        let resolved = resolve_connection(&state_with(keyring, true).await, 1, None).await.unwrap();
+        assert_eq!(resolved.username.as_deref(), Some("app_user"));
+        assert_eq!(resolved.password.as_deref(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn missing_credential_row() {
+        let keyring = MemoryKeyring::with(&[(KEYRING_SERVICE, "cred-1", "secret")]);
+        let err = resolve_connection(&state_with(keyring, false).await, 1, None).await.err();
+        assert_eq!(err, Some(CredentialError::CredentialMissing("cred-1".to_string())));
+    }
+
+    #[tokio::test]
+    async fn missing_password() {
+        let err = resolve_connection(&state_with(MemoryKeyring::default(), true).await, 1, None).await.err();
+        assert_eq!(err, Some(CredentialError::PasswordNotFound("cred-1".to_string())));
+    }
+
+    #[tokio::test]
+    async fn unavailable_keyring() {
+        let keyring = MemoryKeyring { unavailable: true, ..Default::default() };
+        let err = resolve_connection(&state_with(keyring, true).await, 1, None).await.err();
+        assert!(matches!(err, Some(CredentialError::KeyringUnavailable(_))), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn unknown_connection() {
+        let err = resolve_connection(&state_with(MemoryKeyring::default(), true).await, 2, None).await.err();
+        assert_eq!(err, Some(CredentialError::ConnectionNotFound(2)));
+    }
+
+    #[test]
+    fn legacy_passwords_are_moved() {
+        let store = KeyringStore(MemoryKeyring::with(&[(LEGACY_KEYRING_SERVICE, "cred-1", "old-secret")]));
+        assert_eq!(store.get_password("cred-1"), Ok("old-secret".to_string()));
+        assert_eq!(store.0.stored(KEYRING_SERVICE, "cred-1").as_deref(), Some("old-secret"));
+        assert_eq!(store.0.stored(LEGACY_KEYRING_SERVICE, "cred-1"), None);
+        // Served from the unified service from now on
+        assert_eq!(store.get_password("cred-1"), Ok("old-secret".to_string()));
+    }
+
+    #[test]
+    fn current_service_wins_over_legacy() {
+        let store = KeyringStore(MemoryKeyring::with(&[
+            (KEYRING_SERVICE, "cred-1", "new-secret"),
+            (LEGACY_KEYRING_SERVICE, "cred-1", "old-secret"),
+        ]));
+        assert_eq!(store.get_password("cred-1"), Ok("new-secret".to_string()));
+        assert_eq!(store.0.stored(LEGACY_KEYRING_SERVICE, "cred-1").as_deref(), Some("old-secret"));
+    }
+
+    #[test]
+    fn deleting_a_missing_password_succeeds() {
+        let store = KeyringStore(MemoryKeyring::default());
+        assert_eq!(store.delete_password("cred-1"), Ok(()));
+        let store = KeyringStore(MemoryKeyring { unavailable: true, ..Default::default() });
+        assert!(matches!(store.delete_password("cred-1"), Err(CredentialError::KeyringUnavailable(_))));
+    }
+
+    fn resolved(db_type: &str, host: &str, port: i32) -> ResolvedConnection {
+        ResolvedConnection {
+            connection: Connection {
+                id: 1,
+                name: "app".to_string(),
+                db_type: db_type.to_string(),
+                host: host.to_string(),
+                port,
+                database_name: None,
+                credential_id: None,
+                ssl_mode: None,
+                ssl_ca_path: None,
+                ssl_client_cert_path: None,
+                ssl_client_key_path: None,
+                ssh_host: None,
+                ssh_port: None,
+                ssh_user: None,
+                ssh_auth_method: None,
+                ssh_key_path: None,
+                ssh_credential_id: None,
+                pool_max_connections: None,
+                pool_idle_timeout_secs: None,
+                pool_statement_timeout_secs: None,
+                pool_connect_timeout_secs: None,
+                pool_test_before_acquire: None,
+                read_only: None,
+                environment: None,
+                created_at: String::new(),
+            },
+            database_name: Some("app_db".to_string()),
+            username: Some("app_user".to_string()),
+            password: Some("p@ss:w/rd#%".to_string()),
+        }
+    }
+
+    #[test]
+    fn postgres_options_from_parts() {
+        let Ok(DbConnectOptions::Postgres(options)) = resolved("postgres", "[::1]", 5433).connect_options() else {
+            panic!("expected postgres options");
+        };
+        assert_eq!(options.get_host(), "::1");
+        assert_eq!(options.get_port(), 5433);
+        assert_eq!(options.get_socket(), None);
+        assert_eq!(options.get_username(), "app_user");
+        assert_eq!(options.get_database(), Some("app_db"));
+        // No getter for the password: it must be passed through verbatim, not URL-decoded
+        assert!(format!("{:?}", options).contains(r##"password: Some("p@ss:w/rd#%")"##), "{:?}", options);
+    }
+
+    #[test]
+    fn mysql_options_from_parts() {
+        let Ok(DbConnectOptions::Mysql(options)) = resolved("mysql", "[2001:db8::5]", 3307).connect_options() else {
+            panic!("expected mysql options");
+        };
+        assert_eq!(options.get_host(), "2001:db8::5");
+        assert_eq!(options.get_port(), 3307);
+        assert_eq!(options.get_socket(), None);
+        assert_eq!(options.get_username(), "app_user");
+        assert_eq!(options.get_database(), Some("app_db"));
+        assert!(format!("{:?}", options).contains(r##"password: Some("p@ss:w/rd#%")"##), "{:?}", options);
+    }
+
+    #[test]
+    fn unix_socket_hosts() {
+        let Ok(DbConnectOptions::Mysql(options)) = resolved("mysql", "/var/run/mysqld/mysqld.sock", 3306).connect_options() else {
+            panic!("expected mysql options");
+        };
+        assert_eq!(options.get_socket(), Some(&std::path::PathBuf::from("/var/run/mysqld/mysqld.sock")));
+        assert_eq!(options.get_username(), "app_user");
+
+        let Ok(DbConnectOptions::Postgres(options)) = resolved("postgres", "/var/run/postgresql", 5432).connect_options() else {
+            panic!("expected postgres options");
+        };
+        assert_eq!(options.get_socket(), Some(&std::path::PathBuf::from("/var/run/postgresql")));
+        assert_eq!(options.get_port(), 5432);
+        assert_eq!(options.get_username(), "app_user");
+    }
+
+    #[test]
+    fn invalid_port_is_rejected() {
+        assert!(resolved("postgres", "db", 70000).connect_options().is_err());
+    }
+}
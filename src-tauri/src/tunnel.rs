@@ -0,0 +1,405 @@
+//! SSH tunnels for connections behind a bastion host. A tunnel listens on a local port and
+//! forwards every connection accepted there to the database through one SSH session
+//! (`direct-tcpip` channels), so the pool simply connects to `127.0.0.1:<local port>`.
+
+use crate::db::DbConnectOptions;
+use ssh2::{CheckResult, KnownHostFileKind};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const KEEPALIVE_INTERVAL_SECS: u32 = 30;
+const IDLE_SLEEP: Duration = Duration::from_millis(5);
+
+pub enum SshAuth {
+    Agent,
+    KeyFile { path: String, passphrase: Option<String> },
+    Password(String),
+}
+
+/// A resolved SSH login (no Debug: may hold a password or passphrase)
+pub struct SshConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub auth: SshAuth,
+    pub known_hosts: PathBuf, // Host keys trusted by the app, in addition to ~/.ssh/known_hosts
+}
+
+/// A running tunnel; the forwarding thread stops when this is dropped
+#[derive(Debug)]
+pub struct SshTunnel {
+    pub local_port: u16,
+    shutdown: Arc<AtomicBool>,
+    _thread: JoinHandle<()>,
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Open a tunnel to the database `options` point at and return options pointing at its local end
+pub async fn connect_through(ssh: SshConfig, options: DbConnectOptions) -> Result<(DbConnectOptions, SshTunnel), String> {
+    let (target_host, target_port) = options
+        .tcp_target()
+        .ok_or("SSH tunnels need a TCP host and port, not a socket or file")?;
+    if options.verifies_host_name() {
+        return Err("verify-full cannot be used through an SSH tunnel: the certificate would be checked against 127.0.0.1. \
+                    Use verify-ca instead"
+            .to_string());
+    }
+    let tunnel = tokio::task::spawn_blocking(move || SshTunnel::open(&ssh, target_host, target_port))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok((options.through_tunnel(tunnel.local_port), tunnel))
+}
+
+impl SshTunnel {
+    fn open(ssh: &SshConfig, target_host: String, target_port: u16) -> Result<Self, String> {
+        let session = connect_ssh(ssh)?;
+        let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| format!("Failed to open local tunnel port: {}", e))?;
+        let local_port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = shutdown.clone();
+        let thread = std::thread::Builder::new()
+            .name(format!("ssh-tunnel-{}", local_port))
+            .spawn(move || forward(session, listener, target_host, target_port, stop))
+            .map_err(|e| format!("Failed to start SSH tunnel: {}", e))?;
+
+        log::info!("SSH tunnel via {}:{} listening on 127.0.0.1:{}", ssh.host, ssh.port, local_port);
+        Ok(Self { local_port, shutdown, _thread: thread })
+    }
+}
+
+fn connect_ssh(ssh: &SshConfig) -> Result<ssh2::Session, String> {
+    let host = ssh.host.trim_start_matches('[').trim_end_matches(']');
+    let address = (host, ssh.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve SSH host {}: {}", ssh.host, e))?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve SSH host {}", ssh.host))?;
+    let tcp = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| format!("Failed to reach SSH host {}:{}: {}", ssh.host, ssh.port, e))?;
+
+    let mut session = ssh2::Session::new().map_err(|e| e.to_string())?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+    session.handshake().map_err(|e| format!("SSH handshake with {} failed: {}", ssh.host, e))?;
+    check_host_key(&session, host, ssh.port, &ssh.known_hosts)?;
+
+    let result = match &ssh.auth {
+        SshAuth::Agent => session.userauth_agent(&ssh.user),
+        SshAuth::KeyFile { path, passphrase } => {
+            let path = match (path.strip_prefix("~/"), home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(path),
+            };
+            session.userauth_pubkey_file(&ssh.user, None, &path, passphrase.as_deref())
+        }
+        SshAuth::Password(password) => session.userauth_password(&ssh.user, password),
+    };
+    result.map_err(|e| format!("SSH authentication as {} failed: {}", ssh.user, e))?;
+    if !session.authenticated() {
+        return Err(format!("SSH authentication as {} failed", ssh.user));
+    }
+    session.set_keepalive(true, KEEPALIVE_INTERVAL_SECS);
+    Ok(session)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+/// Check the server's host key against ~/.ssh/known_hosts and the app's own file. An unknown
+/// host is trusted on first use and recorded in the app's file; a changed key is refused.
+fn check_host_key(session: &ssh2::Session, host: &str, port: u16, app_known_hosts: &Path) -> Result<(), String> {
+    let (key, key_type) = session.host_key().ok_or("The SSH server sent no host key")?;
+    let user_known_hosts = home_dir().map(|home| home.join(".ssh").join("known_hosts"));
+
+    let mut known = session.known_hosts().map_err(|e| e.to_string())?;
+    for file in user_known_hosts.iter().map(PathBuf::as_path).chain([app_known_hosts]) {
+        if file.exists() {
+            // Lines libssh2 cannot parse (newer key types) are skipped, not fatal
+            let _ = known.read_file(file, KnownHostFileKind::OpenSSH);
+        }
+    }
+
+    match known.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(format!(
+            "SSH host key for {}:{} does not match the one in known_hosts. The server's key changed or the \
+             connection is being intercepted; if the change is expected, remove the old entry from known_hosts",
+            host, port
+        )),
+        CheckResult::NotFound => {
+            let mut app_known = session.known_hosts().map_err(|e| e.to_string())?;
+            if app_known_hosts.exists() {
+                let _ = app_known.read_file(app_known_hosts, KnownHostFileKind::OpenSSH);
+            }
+            let entry = if port == 22 { host.to_string() } else { format!("[{}]:{}", host, port) };
+            app_known
+                .add(&entry, key, "added by SQL UI", key_type.into())
+                .and_then(|_| app_known.write_file(app_known_hosts, KnownHostFileKind::OpenSSH))
+                .map_err(|e| format!("Failed to record SSH host key: {}", e))?;
+            log::info!("Trusting new SSH host key for {}", entry);
+            Ok(())
+        }
+        CheckResult::Failure => Err(format!("Failed to check the SSH host key for {}", host)),
+    }
+}
+
+/// One forwarded connection: the local client socket and its SSH channel, with bytes read from
+/// one side that the other could not take yet
+struct Link {
+    stream: TcpStream,
+    channel: ssh2::Channel,
+    to_remote: Vec<u8>,
+    to_local: Vec<u8>,
+    closed: bool,
+}
+
+impl Link {
+    /// Move whatever data is ready in both directions; returns whether anything moved
+    fn pump(&mut self, buf: &mut [u8]) -> std::io::Result<bool> {
+        let mut moved = false;
+
+        if self.to_remote.is_empty() {
+            match self.stream.read(buf) {
+                Ok(0) => self.closed = true,
+                Ok(n) => self.to_remote.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.to_remote.is_empty() {
+            match self.channel.write(&self.to_remote) {
+                Ok(n) => {
+                    self.to_remote.drain(..n);
+                    moved = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.to_local.is_empty() {
+            match self.channel.read(buf) {
+                Ok(0) if self.channel.eof() => self.closed = true,
+                Ok(0) => {}
+                Ok(n) => self.to_local.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.to_local.is_empty() {
+            match self.stream.write(&self.to_local) {
+                Ok(n) => {
+                    self.to_local.drain(..n);
+                    moved = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(moved)
+    }
+
+    fn is_done(&self) -> bool {
+        self.closed && self.to_remote.is_empty() && self.to_local.is_empty()
+    }
+}
+
+/// The tunnel loop: accept local connections, open a channel for each and shuttle bytes until
+/// shut down. The SSH session is only used from this thread, in non-blocking mode.
+fn forward(session: ssh2::Session, listener: TcpListener, target_host: String, target_port: u16, shutdown: Arc<AtomicBool>) {
+    if let Err(e) = listener.set_nonblocking(true) {
+        log::error!("SSH tunnel stopped: {}", e);
+        return;
+    }
+    session.set_blocking(false);
+    let mut links: Vec<Link> = Vec::new();
+    let mut buf = vec![0u8; 32 * 1024];
+    let mut next_keepalive = Instant::now();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let mut busy = false;
+
+        match listener.accept() {
+            Ok((stream, _)) => {
+                busy = true;
+                // Opening a channel is a round trip; do it blocking
+                session.set_blocking(true);
+                let channel = session.channel_direct_tcpip(&target_host, target_port, None);
+                session.set_blocking(false);
+                match channel {
+                    Ok(channel) if stream.set_nonblocking(true).is_ok() => links.push(Link {
+                        stream,
+                        channel,
+                        to_remote: Vec::new(),
+                        to_local: Vec::new(),
+                        closed: false,
+                    }),
+                    Ok(_) => {}
+                    // Dropping the stream closes it, which the pool reports as a failed connect
+                    Err(e) => log::warn!("SSH tunnel could not reach {}:{}: {}", target_host, target_port, e),
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => {
+                log::error!("SSH tunnel stopped: {}", e);
+                break;
+            }
+        }
+
+        links.retain_mut(|link| match link.pump(&mut buf) {
+            Ok(moved) => {
+                busy |= moved;
+                if link.is_done() {
+                    let _ = link.channel.close();
+                }
+                !link.is_done()
+            }
+            Err(e) => {
+                log::warn!("SSH tunnel connection closed: {}", e);
+                false
+            }
+        });
+
+        if Instant::now() >= next_keepalive {
+            let wait = session.keepalive_send().unwrap_or(KEEPALIVE_INTERVAL_SECS);
+            next_keepalive = Instant::now() + Duration::from_secs(u64::from(wait.max(1)));
+        }
+        if !busy {
+            std::thread::sleep(IDLE_SLEEP);
+        }
+    }
+
+    let _ = session.disconnect(None, "tunnel closed", None);
+}
+
+/// Integration tests against a real sshd, ignored by default. Configure with
+/// `SSH_TUNNEL_TEST_HOST`, `SSH_TUNNEL_TEST_PORT` (22), `SSH_TUNNEL_TEST_USER` and either
+/// `SSH_TUNNEL_TEST_PASSWORD` or `SSH_TUNNEL_TEST_KEY` (else the agent), then run
+/// `cargo test tunnel -- --ignored`. The tunnels forward to the sshd's own port, which answers
+/// with its banner, so no database is needed.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TlsSettings;
+    use base64::Engine;
+
+    fn test_config(known_hosts: PathBuf) -> SshConfig {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let auth = match (var("SSH_TUNNEL_TEST_PASSWORD"), var("SSH_TUNNEL_TEST_KEY")) {
+            (Some(password), _) => SshAuth::Password(password),
+            (None, Some(path)) => SshAuth::KeyFile { path, passphrase: None },
+            (None, None) => SshAuth::Agent,
+        };
+        SshConfig {
+            host: var("SSH_TUNNEL_TEST_HOST").expect("set SSH_TUNNEL_TEST_HOST to run the SSH tunnel tests"),
+            port: var("SSH_TUNNEL_TEST_PORT").map_or(22, |p| p.parse().expect("SSH_TUNNEL_TEST_PORT")),
+            user: var("SSH_TUNNEL_TEST_USER").expect("set SSH_TUNNEL_TEST_USER"),
+            auth,
+            known_hosts,
+        }
+    }
+
+    fn temp_known_hosts() -> PathBuf {
+        std::env::temp_dir().join(format!("known_hosts-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn read_banner(port: u16) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT)).unwrap();
+        let mut banner = [0u8; 8];
+        stream.read_exact(&mut banner).unwrap();
+        String::from_utf8_lossy(&banner).to_string()
+    }
+
+    #[tokio::test]
+    async fn refuses_verify_full_before_connecting() {
+        // Nothing listens here; the check must fail before any SSH connection is tried
+        let ssh = || SshConfig {
+            host: "ssh.invalid".to_string(),
+            port: 22,
+            user: "nobody".to_string(),
+            auth: SshAuth::Agent,
+            known_hosts: temp_known_hosts(),
+        };
+        let tls = TlsSettings { mode: Some("verify-full".to_string()), ..Default::default() };
+        for options in [
+            DbConnectOptions::parse("postgres://u@db.example.com/app").unwrap().with_tls(&tls).unwrap(),
+            DbConnectOptions::parse("mysql://u@db.example.com/app").unwrap().with_tls(&tls).unwrap(),
+            DbConnectOptions::parse("postgres://u@db.example.com/app?sslmode=verify-full").unwrap(),
+        ] {
+            let err = connect_through(ssh(), options).await.err().unwrap();
+            assert!(err.starts_with("verify-full cannot be used through an SSH tunnel"), "{}", err);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn forwards_connections() {
+        let ssh = test_config(temp_known_hosts());
+        let tunnel = SshTunnel::open(&ssh, "127.0.0.1".to_string(), ssh.port).unwrap();
+        // Each accepted connection gets its own channel
+        assert_eq!(read_banner(tunnel.local_port), "SSH-2.0-");
+        assert_eq!(read_banner(tunnel.local_port), "SSH-2.0-");
+        // The first connection trusted the host key
+        assert!(std::fs::read_to_string(&ssh.known_hosts).unwrap().contains("added by SQL UI"));
+        let _ = std::fs::remove_file(&ssh.known_hosts);
+    }
+
+    #[test]
+    #[ignore]
+    fn refuses_a_changed_host_key() {
+        let ssh = test_config(temp_known_hosts());
+        connect_ssh(&ssh).unwrap();
+
+        // Flip the last byte of the recorded key, keeping its type
+        let recorded = std::fs::read_to_string(&ssh.known_hosts).unwrap();
+        let changed: String = recorded
+            .lines()
+            .map(|line| {
+                let mut fields: Vec<String> = line.split(' ').map(str::to_string).collect();
+                if fields.len() >= 3 && !line.starts_with('#') {
+                    let mut key = base64::engine::general_purpose::STANDARD.decode(&fields[2]).unwrap();
+                    *key.last_mut().unwrap() ^= 0xff;
+                    fields[2] = base64::engine::general_purpose::STANDARD.encode(key);
+                }
+                fields.join(" ") + "\n"
+            })
+            .collect();
+        std::fs::write(&ssh.known_hosts, changed).unwrap();
+
+        let err = connect_ssh(&ssh).err().expect("a changed host key must be refused");
+        assert!(err.contains("does not match"), "{}", err);
+        let _ = std::fs::remove_file(&ssh.known_hosts);
+    }
+
+    #[test]
+    #[ignore]
+    fn dropping_the_tunnel_closes_it() {
+        let ssh = test_config(temp_known_hosts());
+        let tunnel = SshTunnel::open(&ssh, "127.0.0.1".to_string(), ssh.port).unwrap();
+        let port = tunnel.local_port;
+        assert_eq!(read_banner(port), "SSH-2.0-");
+
+        drop(tunnel);
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            assert!(Instant::now() < deadline, "the tunnel still accepts connections after being dropped");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = std::fs::remove_file(&ssh.known_hosts);
+    }
+}
@@ -102,6 +102,181 @@ pub fn split_sql_statements(sql: &str, support_backslash_escape: bool) -> Vec<St
     stmts
 }
 
+/// The first keyword of a statement, uppercased, skipping leading comments and parentheses
+pub fn leading_keyword(stmt: &str) -> String {
+    let mut rest = stmt.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map_or("", |(_, r)| r).trim_start();
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map_or("", |(_, r)| r).trim_start();
+        } else if let Some(after) = rest.strip_prefix('(') {
+            rest = after.trim_start();
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Whether a statement only reads. Used to refuse writes on read-only connections, whose
+/// statements also run inside a read-only transaction, so a data-modifying CTE is still
+/// stopped by the server. Anything that changes settings (`SET`, `set_config`) is refused, as
+/// it could turn read-only off; PRAGMAs are allowed unless they set a value (`query_only`).
+pub fn is_read_only_statement(stmt: &str, support_backslash_escape: bool) -> bool {
+    let changes_settings = statement_words(stmt, support_backslash_escape)
+        .iter()
+        .any(|(w, _)| w == "SET" || w == "SET_CONFIG");
+    match leading_keyword(stmt).as_str() {
+        _ if changes_settings => false,
+        "SELECT" | "WITH" | "SHOW" | "EXPLAIN" | "DESCRIBE" | "DESC" | "VALUES" | "TABLE" => true,
+        "PRAGMA" => !stmt.contains('=') && !stmt.to_ascii_lowercase().contains("query_only"),
+        _ => false,
+    }
+}
+
+/// Why a statement needs confirmation before it runs on a guarded connection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatementRisk {
+    UnboundedUpdate, // UPDATE without a WHERE clause
+    UnboundedDelete, // DELETE without a WHERE clause
+    Merge,           // MERGE or REPLACE, which can overwrite or remove existing rows
+    Ddl,             // CREATE, ALTER, DROP, TRUNCATE or RENAME
+    Unrecognized,    // Anything else that may write, e.g. CALL, DO, GRANT or SET
+}
+
+impl StatementRisk {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementRisk::UnboundedUpdate => "unbounded_update",
+            StatementRisk::UnboundedDelete => "unbounded_delete",
+            StatementRisk::Merge => "merge",
+            StatementRisk::Ddl => "ddl",
+            StatementRisk::Unrecognized => "unrecognized",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            StatementRisk::UnboundedUpdate => "UPDATE without WHERE changes every row",
+            StatementRisk::UnboundedDelete => "DELETE without WHERE removes every row",
+            StatementRisk::Merge => "MERGE/REPLACE can overwrite or remove existing rows",
+            StatementRisk::Ddl => "changes the schema",
+            StatementRisk::Unrecognized => "may change data or permissions in ways that can't be checked",
+        }
+    }
+}
+
+/// The keywords of a statement outside quotes and comments, uppercased, with the
+/// parenthesised group they are in (0 for the top level, then a new id per opening parenthesis)
+fn statement_words(stmt: &str, support_backslash_escape: bool) -> Vec<(String, usize)> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut groups = vec![0usize];
+    let mut next_group = 1usize;
+    let mut chars = stmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c.to_ascii_uppercase());
+            continue;
+        }
+        let group = *groups.last().unwrap_or(&0);
+        if !word.is_empty() {
+            words.push((std::mem::take(&mut word), group));
+        }
+        match c {
+            '(' => {
+                groups.push(next_group);
+                next_group += 1;
+            }
+            ')' if groups.len() > 1 => {
+                groups.pop();
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '\'' | '"' | '`' => {
+                while let Some(q) = chars.next() {
+                    if support_backslash_escape && q == '\\' {
+                        chars.next();
+                    } else if q == c {
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        words.push((word, *groups.last().unwrap_or(&0)));
+    }
+    words
+}
+
+/// Flag a single statement (as split by `split_sql_statements`) that changes the schema,
+/// merges rows, or updates or deletes without a WHERE clause. Every UPDATE/DELETE is checked,
+/// including those in CTEs and subqueries, and its WHERE must be in the same parenthesised
+/// group: a WHERE in a CTE or subquery does not bound the outer statement. Fails closed:
+/// statements that aren't queries, DML or transaction control are flagged as unrecognized.
+pub fn classify_statement(stmt: &str, support_backslash_escape: bool) -> Option<StatementRisk> {
+    match leading_keyword(stmt).as_str() {
+        "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" => return Some(StatementRisk::Ddl),
+        "MERGE" | "REPLACE" => return Some(StatementRisk::Merge),
+        "UPDATE" | "DELETE" | "WITH" | "SELECT" | "INSERT" | "EXPLAIN" => {}
+        "" | "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => return None,
+        _ if is_read_only_statement(stmt, support_backslash_escape) => return None,
+        _ => return Some(StatementRisk::Unrecognized),
+    }
+
+    let words = statement_words(stmt, support_backslash_escape);
+    words.iter().enumerate().find_map(|(i, (verb, group))| {
+        // Skip SELECT ... FOR UPDATE, ON CONFLICT DO UPDATE and ON DUPLICATE KEY UPDATE
+        if !(verb == "UPDATE" || verb == "DELETE")
+            || (i > 0 && matches!(words[i - 1].0.as_str(), "FOR" | "DO" | "KEY"))
+        {
+            return None;
+        }
+        let bounded = words[i + 1..].iter().any(|(w, g)| g == group && w == "WHERE");
+        match (verb.as_str(), bounded) {
+            (_, true) => None,
+            ("UPDATE", false) => Some(StatementRisk::UnboundedUpdate),
+            _ => Some(StatementRisk::UnboundedDelete),
+        }
+    })
+}
+
+/// Quotes a string as a SQL literal. Single quotes are doubled; MySQL also treats
+/// backslash as an escape character, so backslashes are doubled there too.
+pub fn escape_literal(value: &str, db_type: &str) -> String {
+    let mut escaped = value.replace('\'', "''");
+    if db_type == "mysql" {
+        escaped = escaped.replace('\\', "\\\\");
+    }
+    format!("'{}'", escaped)
+}
+
 /// Escapes a SQL identifier (table name, column name) for safe use in queries.
 /// - MySQL uses backticks: `table_name`
 /// - PostgreSQL and SQLite use double quotes: "table_name"
@@ -112,3 +287,121 @@ pub fn escape_identifier(name: &str, db_type: &str) -> String {
         _ => format!("\"{}\"", name.replace('"', "\"\"")), // postgres/sqlite
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(stmt: &str) -> Option<StatementRisk> {
+        classify_statement(stmt, false)
+    }
+
+    #[test]
+    fn flags_unbounded_writes() {
+        assert_eq!(classify("UPDATE orders SET total = 0"), Some(StatementRisk::UnboundedUpdate));
+        assert_eq!(classify("delete from orders"), Some(StatementRisk::UnboundedDelete));
+        assert_eq!(classify("UPDATE orders SET total = 0 WHERE id = 1"), None);
+        assert_eq!(classify("DELETE FROM orders WHERE id = 1"), None);
+    }
+
+    #[test]
+    fn flags_ddl_and_merges() {
+        assert_eq!(classify("DROP TABLE orders"), Some(StatementRisk::Ddl));
+        assert_eq!(classify("TRUNCATE orders"), Some(StatementRisk::Ddl));
+        assert_eq!(classify("REPLACE INTO orders (id) VALUES (1)"), Some(StatementRisk::Merge));
+        assert_eq!(
+            classify("MERGE INTO orders o USING staged s ON o.id = s.id WHEN MATCHED THEN DELETE"),
+            Some(StatementRisk::Merge)
+        );
+    }
+
+    #[test]
+    fn flags_data_modifying_ctes() {
+        assert_eq!(
+            classify("WITH d AS (DELETE FROM orders RETURNING *) SELECT * FROM d"),
+            Some(StatementRisk::UnboundedDelete)
+        );
+        assert_eq!(
+            classify("WITH d AS (DELETE FROM orders WHERE id = 1 RETURNING *) SELECT * FROM d"),
+            None
+        );
+        assert_eq!(
+            classify("WITH u AS (UPDATE orders SET total = 0 RETURNING id) SELECT * FROM u WHERE id > 1"),
+            Some(StatementRisk::UnboundedUpdate)
+        );
+    }
+
+    #[test]
+    fn subquery_where_does_not_bound_outer_statement() {
+        assert_eq!(
+            classify("DELETE FROM orders USING (SELECT id FROM stale WHERE old) s"),
+            Some(StatementRisk::UnboundedDelete)
+        );
+        assert_eq!(
+            classify("WITH s AS (SELECT id FROM stale WHERE old) UPDATE orders SET total = 0"),
+            Some(StatementRisk::UnboundedUpdate)
+        );
+        assert_eq!(
+            classify("UPDATE orders SET total = 0 WHERE id IN (SELECT id FROM stale)"),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_locking_and_upsert_updates() {
+        assert_eq!(classify("SELECT * FROM orders FOR UPDATE"), None);
+        assert_eq!(classify("SELECT * FROM orders FOR NO KEY UPDATE"), None);
+        assert_eq!(
+            classify("INSERT INTO orders (id) VALUES (1) ON CONFLICT (id) DO UPDATE SET id = excluded.id"),
+            None
+        );
+        assert_eq!(
+            classify("INSERT INTO orders (id) VALUES (1) ON DUPLICATE KEY UPDATE id = VALUES(id)"),
+            None
+        );
+    }
+
+    #[test]
+    fn ignores_where_in_strings_and_comments() {
+        assert_eq!(classify("DELETE FROM orders -- WHERE id = 1"), Some(StatementRisk::UnboundedDelete));
+        assert_eq!(classify("DELETE FROM orders /* WHERE id = 1 */"), Some(StatementRisk::UnboundedDelete));
+        assert_eq!(
+            classify("UPDATE orders SET note = 'WHERE id = 1'"),
+            Some(StatementRisk::UnboundedUpdate)
+        );
+        assert_eq!(
+            classify_statement(r"UPDATE orders SET note = 'it\'s WHERE'", true),
+            Some(StatementRisk::UnboundedUpdate)
+        );
+    }
+
+    #[test]
+    fn flags_statements_it_cannot_check() {
+        for stmt in [
+            "DO $$ BEGIN DELETE FROM orders; END $$",
+            "CALL purge_orders()",
+            "EXEC purge_orders",
+            "EXECUTE purge_plan",
+            "GRANT ALL ON orders TO PUBLIC",
+            "REVOKE SELECT ON orders FROM app",
+            "COMMENT ON TABLE orders IS 'old'",
+            "SET FOREIGN_KEY_CHECKS = 0",
+            "PRAGMA foreign_keys = OFF",
+        ] {
+            assert_eq!(classify(stmt), Some(StatementRisk::Unrecognized), "{}", stmt);
+        }
+        for stmt in ["SHOW TABLES", "DESCRIBE orders", "PRAGMA table_info(orders)", "BEGIN", "COMMIT", "ROLLBACK"] {
+            assert_eq!(classify(stmt), None, "{}", stmt);
+        }
+    }
+
+    #[test]
+    fn read_only_statements() {
+        assert!(is_read_only_statement("SELECT * FROM orders", false));
+        assert!(is_read_only_statement("WITH t AS (SELECT 1) SELECT * FROM t", false));
+        assert!(!is_read_only_statement("SET SESSION TRANSACTION READ WRITE", false));
+        assert!(!is_read_only_statement("SELECT set_config('default_transaction_read_only', 'off', false)", false));
+        assert!(!is_read_only_statement("DELETE FROM orders WHERE id = 1", false));
+        assert!(!is_read_only_statement("PRAGMA query_only = OFF", false));
+    }
+}
@@ -0,0 +1,294 @@
+//! Encrypted credential vault, used instead of the OS keyring where none is available
+//! (headless Linux, desktops without Secret Service). Passwords are kept in one file in the
+//! app data dir, encrypted with XChaCha20-Poly1305 under a key derived from a master password
+//! with Argon2id. The vault is in use for as long as the file exists.
+
+use crate::resolver::{CredentialError, SecretStore};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// The vault file: key derivation parameters and the encrypted password map
+#[derive(Serialize, Deserialize, Clone)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An unlocked vault: the derived key and the decrypted passwords by credential id
+struct UnlockedVault {
+    key: Key,
+    file: VaultFile,
+    entries: HashMap<String, String>,
+}
+
+pub struct VaultStore {
+    path: PathBuf,
+    unlocked: Mutex<Option<UnlockedVault>>,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn vault_error(e: impl std::fmt::Display) -> CredentialError {
+    CredentialError::Vault(e.to_string())
+}
+
+fn derive_key(master_password: &str, file: &VaultFile) -> Result<Key, CredentialError> {
+    let salt = b64().decode(&file.salt).map_err(vault_error)?;
+    let params = Params::new(file.m_cost, file.t_cost, file.p_cost, Some(32)).map_err(vault_error)?;
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(master_password.as_bytes(), &salt, &mut key)
+        .map_err(vault_error)?;
+    Ok(key)
+}
+
+impl VaultStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, unlocked: Mutex::new(None) }
+    }
+
+    /// Whether a vault has been created on this install
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.lock().unwrap().is_some()
+    }
+
+    /// Create an empty vault protected by `master_password` and leave it unlocked
+    pub fn create(&self, master_password: &str) -> Result<(), CredentialError> {
+        if self.exists() {
+            return Err(vault_error("A vault already exists"));
+        }
+        if master_password.is_empty() {
+            return Err(vault_error("The master password cannot be empty"));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let params = Params::default();
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            salt: b64().encode(salt),
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+            nonce: String::new(),
+            ciphertext: String::new(),
+        };
+        let key = derive_key(master_password, &file)?;
+        let mut unlocked = UnlockedVault { key, file, entries: HashMap::new() };
+        self.write(&mut unlocked)?;
+        *self.unlocked.lock().unwrap() = Some(unlocked);
+        Ok(())
+    }
+
+    /// Decrypt the vault with `master_password`; fails without unlocking if the password is wrong
+    pub fn unlock(&self, master_password: &str) -> Result<(), CredentialError> {
+        let contents = fs::read_to_string(&self.path).map_err(|e| vault_error(format!("Failed to read vault: {}", e)))?;
+        let file: VaultFile = serde_json::from_str(&contents).map_err(|e| vault_error(format!("Vault file is corrupt: {}", e)))?;
+        if file.version != VAULT_VERSION {
+            return Err(vault_error(format!("Unsupported vault version {}", file.version)));
+        }
+        let key = derive_key(master_password, &file)?;
+        let nonce = b64().decode(&file.nonce).map_err(vault_error)?;
+        let ciphertext = b64().decode(&file.ciphertext).map_err(vault_error)?;
+        if nonce.len() != 24 {
+            return Err(vault_error("Vault file is corrupt: bad nonce"));
+        }
+        let plaintext = XChaCha20Poly1305::new(&key)
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| vault_error("Incorrect master password"))?;
+        let entries = serde_json::from_slice(&plaintext).map_err(|e| vault_error(format!("Vault file is corrupt: {}", e)))?;
+        *self.unlocked.lock().unwrap() = Some(UnlockedVault { key, file, entries });
+        Ok(())
+    }
+
+    /// Forget the key and decrypted passwords until the vault is unlocked again
+    pub fn lock(&self) {
+        *self.unlocked.lock().unwrap() = None;
+    }
+
+    /// All stored passwords by credential id (the vault must be unlocked)
+    pub fn entries(&self) -> Result<HashMap<String, String>, CredentialError> {
+        self.with_unlocked(|vault| Ok(vault.entries.clone()))
+    }
+
+    /// Store several passwords with a single write
+    pub fn set_passwords(&self, passwords: HashMap<String, String>) -> Result<(), CredentialError> {
+        self.with_unlocked(|vault| {
+            let previous = vault.entries.clone();
+            vault.entries.extend(passwords);
+            self.write(vault).inspect_err(|_| vault.entries = previous)
+        })
+    }
+
+    /// Delete the vault file and lock it; the caller has moved the passwords elsewhere
+    pub fn destroy(&self) -> Result<(), CredentialError> {
+        fs::remove_file(&self.path).map_err(|e| vault_error(format!("Failed to delete vault: {}", e)))?;
+        self.lock();
+        Ok(())
+    }
+
+    fn with_unlocked<T>(&self, f: impl FnOnce(&mut UnlockedVault) -> Result<T, CredentialError>) -> Result<T, CredentialError> {
+        let mut guard = self.unlocked.lock().unwrap();
+        let vault = guard.as_mut().ok_or(CredentialError::VaultLocked)?;
+        f(vault)
+    }
+
+    /// Encrypt the entries under a fresh nonce and replace the file atomically
+    fn write(&self, vault: &mut UnlockedVault) -> Result<(), CredentialError> {
+        let plaintext = serde_json::to_vec(&vault.entries).map_err(vault_error)?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&vault.key)
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(vault_error)?;
+        let mut file = vault.file.clone();
+        file.nonce = b64().encode(nonce);
+        file.ciphertext = b64().encode(ciphertext);
+
+        let json = serde_json::to_string_pretty(&file).map_err(vault_error)?;
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, json.as_bytes())
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .map_err(|e| vault_error(format!("Failed to write vault: {}", e)))?;
+        vault.file = file;
+        Ok(())
+    }
+}
+
+/// Write a new file readable only by the current user (on Unix; elsewhere the app data dir's ACL applies)
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    // The mode only applies to newly created files, so never reuse a leftover one
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+impl SecretStore for VaultStore {
+    fn get_password(&self, credential_id: &str) -> Result<String, CredentialError> {
+        self.with_unlocked(|vault| {
+            vault
+                .entries
+                .get(credential_id)
+                .cloned()
+                .ok_or_else(|| CredentialError::PasswordNotFound(credential_id.to_string()))
+        })
+    }
+
+    fn set_password(&self, credential_id: &str, password: &str) -> Result<(), CredentialError> {
+        self.set_passwords(HashMap::from([(credential_id.to_string(), password.to_string())]))
+    }
+
+    fn delete_password(&self, credential_id: &str) -> Result<(), CredentialError> {
+        self.with_unlocked(|vault| {
+            if let Some(previous) = vault.entries.remove(credential_id) {
+                self.write(vault).inspect_err(|_| {
+                    vault.entries.insert(credential_id.to_string(), previous);
+                })?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vault path in a directory of its own
+    fn vault_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vault-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("credentials.vault")
+    }
+
+    #[test]
+    fn passwords_survive_lock_and_unlock() {
+        let path = vault_path();
+        let vault = VaultStore::new(path.clone());
+        vault.create("master").unwrap();
+        vault.set_password("cred-1", "secret").unwrap();
+
+        vault.lock();
+        assert!(!vault.is_unlocked());
+        assert_eq!(vault.get_password("cred-1"), Err(CredentialError::VaultLocked));
+
+        // A new store reads the same file
+        let reopened = VaultStore::new(path.clone());
+        reopened.unlock("master").unwrap();
+        assert_eq!(reopened.get_password("cred-1"), Ok("secret".to_string()));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn wrong_master_password_does_not_unlock() {
+        let path = vault_path();
+        let vault = VaultStore::new(path.clone());
+        vault.create("master").unwrap();
+        vault.lock();
+
+        assert_eq!(vault.unlock("Master"), Err(CredentialError::Vault("Incorrect master password".to_string())));
+        assert!(!vault.is_unlocked());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn failed_writes_keep_the_previous_passwords() {
+        let path = vault_path();
+        let vault = VaultStore::new(path.clone());
+        vault.create("master").unwrap();
+        vault.set_password("cred-1", "secret").unwrap();
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let passwords = HashMap::from([("cred-1".to_string(), "changed".to_string()), ("cred-2".to_string(), "new".to_string())]);
+        assert!(vault.set_passwords(passwords).is_err());
+        assert_eq!(vault.get_password("cred-1"), Ok("secret".to_string()));
+        assert!(vault.get_password("cred-2").is_err());
+        assert!(vault.delete_password("cred-1").is_err());
+        assert_eq!(vault.get_password("cred-1"), Ok("secret".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_files_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("vault-{}.tmp", uuid::Uuid::new_v4()));
+        // A leftover file with wider permissions is replaced, not reused
+        fs::write(&path, b"old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"new").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -13,6 +13,7 @@ import { CredentialPromptWindow } from "./components/windows/CredentialPromptWin
 import { UpdateNotificationWindow } from "./components/windows/UpdateNotificationWindow";
 import { openCredentialPromptWindow, openErrorWindow } from "./utils/windowManager";
 import { invoke } from "@tauri-apps/api/core";
+import { setWindowLogin } from "./utils/connectionHelper";
 
 function App() {
   const [activeConnection, setActiveConnection] = useState<Connection | null>(null);
@@ -58,6 +59,15 @@ function App() {
       const connId = params.get('connection_id');
       if (connId && !windowMode) {
         try {
+          // A window opened with a manual login gets a one-time token; redeem it for a session
+          // and drop it from the URL so it is not reused on reload
+          const loginToken = params.get('login');
+          if (loginToken) {
+            const sid = await api.createSession(Number(connId), undefined, loginToken);
+            setWindowLogin(sid);
+            params.delete('login');
+            window.history.replaceState(null, '', `${window.location.pathname}?${params.toString()}`);
+          }
           const connections = await api.listConnections();
           const target = connections.find(c => c.id === Number(connId));
           if (target) {
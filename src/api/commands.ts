@@ -16,7 +16,14 @@ import {
     SavedFunction,
     RowIdentifier,
     CellUpdate,
-    BatchChange
+    BatchChange,
+    ApplyBatchOptions,
+    SecretStoreStatus,
+    SecretMigrationReport,
+    SessionHealth,
+    SessionInfo,
+    QueryReview,
+    EnvironmentGuard
 } from '../types/index';
 
 // ============================================================================
@@ -41,12 +48,40 @@ export const deleteConnection = (id: number) =>
 export const openConnectionWindow = (connectionId: number, username?: string, password?: string) =>
     invoke<void>('open_connection_window', { connectionId, username, password });
 
+// ============================================================================
+// Secret Storage
+// ============================================================================
+
+export const getSecretStoreStatus = () =>
+    invoke<SecretStoreStatus>('get_secret_store_status');
+
+export const unlockVault = (masterPassword: string) =>
+    invoke<void>('unlock_vault', { masterPassword });
+
+export const lockVault = () =>
+    invoke<void>('lock_vault');
+
+export const migrateToVault = (masterPassword: string) =>
+    invoke<SecretMigrationReport>('migrate_to_vault', { masterPassword });
+
+export const migrateToKeyring = (masterPassword: string) =>
+    invoke<SecretMigrationReport>('migrate_to_keyring', { masterPassword });
+
 // ============================================================================
 // Session Management
 // ============================================================================
 
-export const createSession = (connectionId: number, databaseName?: string) =>
-    invoke<string>('create_session', { connectionId, databaseName });
+export const createSession = (connectionId: number, databaseName?: string, login?: string) =>
+    invoke<string>('create_session', { connectionId, databaseName, login });
+
+export const getSessionHealth = (sessionId: string) =>
+    invoke<SessionHealth>('get_session_health', { sessionId });
+
+export const listSessions = () =>
+    invoke<SessionInfo[]>('list_sessions');
+
+export const closeSession = (sessionId: string) =>
+    invoke<void>('close_session', { sessionId });
 
 // ============================================================================
 // Schema & Data Operations
@@ -95,8 +130,31 @@ export const getColumns = (connectionString: string, tableName: string) =>
 export const getTableSchema = (connectionString: string, tableName: string) =>
     invoke<ColumnSchema[]>('get_table_schema', { connectionString, tableName });
 
-export const executeQuery = (connectionString: string, query: string) =>
-    invoke<QueryResult[]>('execute_query', { connectionString, query });
+export const reviewQuery = (connectionString: string, query: string) =>
+    invoke<QueryReview>('review_query', { connectionString, query });
+
+/**
+ * Run a query. On connections whose environment guards destructive statements, flagged
+ * statements are shown for confirmation first; declining throws without running anything.
+ */
+export const executeQuery = async (connectionString: string, query: string) => {
+    const review = await reviewQuery(connectionString, query);
+    if (review.acknowledgement) {
+        const list = review.statements
+            .map(s => `- ${s.statement.length > 120 ? s.statement.slice(0, 120) + '...' : s.statement}\n  (${s.reason})`)
+            .join('\n');
+        if (!window.confirm(`This ${review.environment} connection is about to run:\n\n${list}\n\nRun anyway?`)) {
+            throw 'Cancelled: the flagged statements were not confirmed';
+        }
+    }
+    return invoke<QueryResult[]>('execute_query', { connectionString, query, acknowledgement: review.acknowledgement });
+};
+
+export const listEnvironmentGuards = () =>
+    invoke<EnvironmentGuard[]>('list_environment_guards');
+
+export const setEnvironmentGuard = (environment: string, confirmDestructive: boolean) =>
+    invoke<void>('set_environment_guard', { environment, confirmDestructive });
 
 export const truncateTable = (connectionString: string, tableName: string) =>
     invoke<void>('truncate_table', { connectionString, tableName });
@@ -136,8 +194,9 @@ export const insertRecord = (
 
 export const applyBatchChanges = (
     connectionString: string,
-    changes: BatchChange[]
-) => invoke<number>('apply_batch_changes', { connectionString, changes });
+    changes: BatchChange[],
+    options?: ApplyBatchOptions
+) => invoke<number>('apply_batch_changes', { connectionString, changes, options });
 
 // ============================================================================
 // Saved Queries
@@ -8,6 +8,7 @@ import { openConnectionWindow } from '../utils/windowManager';
 import { useSystemLogs, useSavedItems, useTableOperations, useTableData, useResultsPane, useTabs, useTableActions, usePersistenceActions, useDatabaseRegistry, useChangeManager, useAppSystem, useSchemaOperations, useDataMutation, ChangeError, useSession } from '../hooks';
 import { useToast } from './common/Toast';
 import { ErrorSummaryModal } from './modals/ErrorSummaryModal';
+import { getWindowLogin } from '../utils/connectionHelper';
 
 interface MainInterfaceProps {
     connection: Connection;
@@ -21,7 +22,7 @@ export const MainInterface: React.FC<MainInterfaceProps> = ({ connection: initia
         setConnection(initialConnection);
     }, [initialConnection]);
 
-    const { sessionId } = useSession(connection);
+    const { sessionId, health: sessionHealth } = useSession(connection);
 
     const handleSwitchDatabase = (dbName: string) => {
         // Confirmation for unsaved changes
@@ -75,6 +76,16 @@ export const MainInterface: React.FC<MainInterfaceProps> = ({ connection: initia
 
     const { logs, addLog } = useSystemLogs();
 
+    // Report health checks that had to reconnect or could not reach the database
+    useEffect(() => {
+        if (!sessionHealth) return;
+        if (!sessionHealth.healthy) {
+            addLog('Session health check', 'Error', undefined, sessionHealth.error || 'Database unreachable');
+        } else if (sessionHealth.reconnected) {
+            addLog('Session reconnected', 'Success');
+        }
+    }, [sessionHealth]);
+
     const {
         tabs,
         setTabs,
@@ -539,6 +550,7 @@ export const MainInterface: React.FC<MainInterfaceProps> = ({ connection: initia
         selectedIndices,
         editData,
         connection,
+        sessionId,
         setPendingChanges,
         setShowEditWindow,
         setEditData,
@@ -611,6 +623,7 @@ export const MainInterface: React.FC<MainInterfaceProps> = ({ connection: initia
             const invoke = (await import('@tauri-apps/api/core')).invoke;
             const connectionString = await invoke<string>('get_connection_string', {
                 connectionId: connection.id,
+                login: getWindowLogin(),
                 databaseName: connection.database_name
             });
             Promise.all(tables.map(async (tableName) => {
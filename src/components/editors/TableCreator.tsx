@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import styles from '../../styles/TableCreator.module.css';
 import { RiAddLine, RiDeleteBinLine, RiKey2Line, RiCloseLine, RiSaveLine, RiFingerprintLine, RiRefreshLine } from 'react-icons/ri';
 import { PendingChange, Connection } from '../../types/index';
+import { getWindowLogin } from '../../utils/connectionHelper';
 
 export interface ColumnDef {
     name: string;
@@ -193,6 +194,7 @@ export const TableCreator: React.FC<TableCreatorProps> = ({ connection, onSucces
     const getConnectionString = useCallback(async (): Promise<string> => {
         return await invoke<string>('get_connection_string', {
             connectionId: connection.id,
+            login: getWindowLogin(),
             databaseName: connection.database_name
         });
     }, [connection.id, connection.database_name]);
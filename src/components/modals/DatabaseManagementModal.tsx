@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
 import { Connection } from '../../types';
 import { RiCloseLine, RiAddLine, RiFileCopyLine, RiDeleteBinLine, RiUploadLine, RiDownloadLine, RiDatabase2Line, RiLoader4Line, RiErrorWarningLine, RiFolderOpenLine, RiArrowRightSLine } from 'react-icons/ri';
+import { getWindowLogin } from '../../utils/connectionHelper';
 
 interface DatabaseManagementModalProps {
     isOpen: boolean;
@@ -60,7 +61,7 @@ export const DatabaseManagementModal: React.FC<DatabaseManagementModalProps> = (
     const fetchDatabases = async () => {
         setIsLoading(true);
         try {
-            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id });
+            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id, login: getWindowLogin() });
             const dbs = await invoke<string[]>('get_databases', { connectionString });
             const filteredDbs = dbs.filter(db => !SYSTEM_DATABASES.includes(db.toLowerCase()));
             setDatabases(filteredDbs);
@@ -89,7 +90,7 @@ export const DatabaseManagementModal: React.FC<DatabaseManagementModalProps> = (
         setError(null);
         try {
             addLog(`Creating database "${newDbName}"...`);
-            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id });
+            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id, login: getWindowLogin() });
             await invoke('create_database', {
                 connectionString,
                 databaseName: newDbName
@@ -118,7 +119,7 @@ export const DatabaseManagementModal: React.FC<DatabaseManagementModalProps> = (
         setError(null);
         try {
             addLog(`Duplicating database "${selectedDbToDuplicate}" to "${duplicateNewName}"...`);
-            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id });
+            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id, login: getWindowLogin() });
             await invoke('duplicate_database', {
                 connectionString,
                 sourceDatabase: selectedDbToDuplicate,
@@ -144,10 +145,11 @@ export const DatabaseManagementModal: React.FC<DatabaseManagementModalProps> = (
         setError(null);
         try {
             addLog(`Deleting database "${selectedDbToDelete}"...`);
-            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id });
+            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id, login: getWindowLogin() });
             await invoke('delete_database', {
                 connectionString,
-                databaseName: selectedDbToDelete
+                databaseName: selectedDbToDelete,
+                confirmName: confirmDeleteName
             });
             addLog(`Database "${selectedDbToDelete}" deleted successfully!`);
             setSelectedDbToDelete('');
@@ -184,7 +186,7 @@ export const DatabaseManagementModal: React.FC<DatabaseManagementModalProps> = (
         setIsExecuting(true);
         setError(null);
         try {
-            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id });
+            const connectionString = await invoke<string>('get_connection_string', { connectionId: connection.id, login: getWindowLogin() });
             if (activeTab === 'export') {
                 addLog(`Exporting schemas to "${directoryPath}"...`);
                 const result = await invoke<string>('export_schema', {
@@ -2,6 +2,8 @@ import React, { useState } from 'react';
 import { RiCloseLine, RiZoomInLine, RiZoomOutLine, RiComputerLine, RiFolderLine, RiSettings3Line, RiPaletteLine, RiFolderOpenLine } from 'react-icons/ri';
 import { open } from '@tauri-apps/plugin-dialog';
 import styles from '../../styles/MainLayout.module.css';
+import * as api from '../../api';
+import { EnvironmentGuard } from '../../types';
 
 interface PreferencesModalProps {
     isOpen: boolean;
@@ -31,6 +33,22 @@ export const PreferencesModal: React.FC<PreferencesModalProps> = ({
     ];
 
     const [activeTab, setActiveTab] = useState<TabType>('theme');
+    const [environmentGuards, setEnvironmentGuards] = useState<EnvironmentGuard[]>([]);
+
+    React.useEffect(() => {
+        if (isOpen && activeTab === 'settings') {
+            api.listEnvironmentGuards().then(setEnvironmentGuards).catch(console.error);
+        }
+    }, [isOpen, activeTab]);
+
+    const toggleEnvironmentGuard = async (guard: EnvironmentGuard) => {
+        try {
+            await api.setEnvironmentGuard(guard.environment, !guard.confirm_destructive);
+            setEnvironmentGuards(await api.listEnvironmentGuards());
+        } catch (e) {
+            console.error('Failed to update environment guard', e);
+        }
+    };
 
     // 1. Determine Current Mode and Base Name
     const currentThemeObj = availableThemes.find(t => t.id === theme);
@@ -431,6 +449,30 @@ export const PreferencesModal: React.FC<PreferencesModalProps> = ({
                                             </label>
                                         </div>
                                     </div>
+
+                                    <div style={{
+                                        marginTop: '20px',
+                                        padding: '20px',
+                                        background: 'var(--bg-tertiary)',
+                                        borderRadius: '8px',
+                                        border: '1px solid var(--border-color)'
+                                    }}>
+                                        <div style={{ fontWeight: 600, marginBottom: '5px' }}>Confirm Destructive Statements</div>
+                                        <div style={{ fontSize: '0.9rem', color: 'var(--text-secondary)', marginBottom: '15px' }}>
+                                            Queries with UPDATE or DELETE without WHERE, or schema changes (CREATE, ALTER, DROP, TRUNCATE),
+                                            must be confirmed before they run on connections in these environments.
+                                        </div>
+                                        {environmentGuards.map(guard => (
+                                            <label key={guard.environment} style={{ display: 'flex', alignItems: 'center', gap: '0.5rem', marginBottom: '8px', cursor: 'pointer', textTransform: 'capitalize' }}>
+                                                <input
+                                                    type="checkbox"
+                                                    checked={guard.confirm_destructive}
+                                                    onChange={() => toggleEnvironmentGuard(guard)}
+                                                />
+                                                {guard.environment}
+                                            </label>
+                                        ))}
+                                    </div>
                                 </div>
                             )
                         }
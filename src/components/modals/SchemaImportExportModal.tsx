@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
 import { Connection } from '../../types';
 import { RiCloseLine, RiUploadLine, RiDownloadLine, RiLoader4Line, RiErrorWarningLine, RiFolderOpenLine } from 'react-icons/ri';
+import { getWindowLogin } from '../../utils/connectionHelper';
 
 interface SchemaImportExportModalProps {
     isOpen: boolean;
@@ -55,6 +56,7 @@ export const SchemaImportExportModal: React.FC<SchemaImportExportModalProps> = (
         try {
             const connectionString = await invoke<string>('get_connection_string', { 
                 connectionId: connection.id,
+                login: getWindowLogin(),
                 databaseName: connection.database_name 
             });
             const command = mode === 'export' ? 'export_schema' : 'import_schema';
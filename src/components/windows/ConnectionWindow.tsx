@@ -1,7 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
-import { Connection, Credential, DbType } from '../../types';
+import { Connection, ConnectionEnvironment, Credential, DbType, PoolSettings, SshAuthMethod, SshSettings, TlsStatus } from '../../types';
 import styles from '../../styles/Welcome.module.css';
 import { RiCheckLine, RiFolderOpenLine } from 'react-icons/ri';
 import { CiFloppyDisk } from 'react-icons/ci';
@@ -26,6 +26,31 @@ export const ConnectionWindow: React.FC = () => {
     const [databaseName, setDatabaseName] = useState('');
     const [credentialId, setCredentialId] = useState<string | undefined>();
     const [sslMode, setSslMode] = useState('');
+    const [sslCaPath, setSslCaPath] = useState('');
+    const [sslClientCertPath, setSslClientCertPath] = useState('');
+    const [sslClientKeyPath, setSslClientKeyPath] = useState('');
+
+    // SSH Tunnel
+    const [sshEnabled, setSshEnabled] = useState(false);
+    const [sshHost, setSshHost] = useState('');
+    const [sshPort, setSshPort] = useState(22);
+    const [sshUser, setSshUser] = useState('');
+    const [sshAuthMethod, setSshAuthMethod] = useState<SshAuthMethod>('agent');
+    const [sshKeyPath, setSshKeyPath] = useState('');
+    const [sshCredentialId, setSshCredentialId] = useState<string | undefined>();
+
+    // Refuse changes and open read-only transactions
+    const [readOnly, setReadOnly] = useState(false);
+    // Decides whether destructive statements need confirmation
+    const [environment, setEnvironment] = useState<ConnectionEnvironment>('development');
+
+    // Connection Pool (empty fields use the defaults)
+    const [showPoolSettings, setShowPoolSettings] = useState(false);
+    const [poolMaxConnections, setPoolMaxConnections] = useState('');
+    const [poolIdleTimeout, setPoolIdleTimeout] = useState('');
+    const [poolStatementTimeout, setPoolStatementTimeout] = useState('');
+    const [poolConnectTimeout, setPoolConnectTimeout] = useState('');
+    const [poolTestBeforeAcquire, setPoolTestBeforeAcquire] = useState(true);
 
     // Credential List
     const [credentials, setCredentials] = useState<Credential[]>([]);
@@ -34,6 +59,7 @@ export const ConnectionWindow: React.FC = () => {
     const [isLoading, setIsLoading] = useState(false);
     const [isTesting, setIsTesting] = useState(false);
     const [testSuccess, setTestSuccess] = useState<boolean | null>(null);
+    const [tlsStatus, setTlsStatus] = useState<TlsStatus | null>(null);
 
     // Reset status on any change
     const resetStatus = () => {
@@ -81,6 +107,25 @@ export const ConnectionWindow: React.FC = () => {
                 setDatabaseName(target.database_name || '');
                 setCredentialId(target.credential_id);
                 setSslMode(target.ssl_mode || '');
+                setSslCaPath(target.ssl_ca_path || '');
+                setSslClientCertPath(target.ssl_client_cert_path || '');
+                setSslClientKeyPath(target.ssl_client_key_path || '');
+                setSshEnabled(!!target.ssh_host);
+                setSshHost(target.ssh_host || '');
+                setSshPort(target.ssh_port || 22);
+                setSshUser(target.ssh_user || '');
+                setSshAuthMethod(target.ssh_auth_method || 'agent');
+                setSshKeyPath(target.ssh_key_path || '');
+                setSshCredentialId(target.ssh_credential_id);
+                setReadOnly(!!target.read_only);
+                setEnvironment(target.environment || 'development');
+                setPoolMaxConnections(target.pool_max_connections?.toString() || '');
+                setPoolIdleTimeout(target.pool_idle_timeout_secs?.toString() || '');
+                setPoolStatementTimeout(target.pool_statement_timeout_secs?.toString() || '');
+                setPoolConnectTimeout(target.pool_connect_timeout_secs?.toString() || '');
+                setPoolTestBeforeAcquire(target.pool_test_before_acquire ?? true);
+                setShowPoolSettings(!!(target.pool_max_connections || target.pool_idle_timeout_secs ||
+                    target.pool_statement_timeout_secs || target.pool_connect_timeout_secs || target.pool_test_before_acquire === false));
             }
         } catch (e) {
             console.error(e);
@@ -99,27 +144,80 @@ export const ConnectionWindow: React.FC = () => {
         if (credentialId) {
             const cred = credentials.find(c => c.id === credentialId);
             if (cred) {
-                url += `${cred.username}@`;
+                url += `${encodeURIComponent(cred.username)}@`;
             }
         }
-        url += `${host}:${port}`;
-        if (databaseName) url += `/${databaseName}`;
-        if (sslMode) url += `?sslmode=${sslMode}`;
+        // Bracket IPv6 literals so the port separator stays unambiguous
+        const urlHost = host.includes(':') && !host.startsWith('[') ? `[${host}]` : host;
+        url += `${urlHost}:${port}`;
+        if (databaseName) url += `/${encodeURIComponent(databaseName)}`;
 
         return url;
     };
 
+    // TLS settings are sent as fields rather than URL parameters, whose names differ per engine
+    const tlsFields = () => ({
+        sslMode: sslMode || null,
+        sslCaPath: sslCaPath || null,
+        sslClientCertPath: sslClientCertPath || null,
+        sslClientKeyPath: sslClientKeyPath || null
+    });
+
+    const browseFile = async (setter: (path: string) => void) => {
+        const { open } = await import('@tauri-apps/plugin-dialog');
+        const selected = await open({ multiple: false });
+        if (selected) {
+            setter(selected as string);
+            resetStatus();
+        }
+    };
+
+    const buildSshSettings = (): SshSettings | null => {
+        if (!sshEnabled || dbType === 'sqlite' || !sshHost) return null;
+        return {
+            host: sshHost,
+            port: sshPort,
+            user: sshUser,
+            auth_method: sshAuthMethod,
+            key_path: sshAuthMethod === 'key' ? sshKeyPath || undefined : undefined,
+            credential_id: sshAuthMethod !== 'agent' ? sshCredentialId : undefined
+        };
+    };
+
+    const buildPoolSettings = (): PoolSettings => {
+        const positive = (value: string) => {
+            const n = parseInt(value);
+            return n > 0 ? n : undefined;
+        };
+        return {
+            max_connections: positive(poolMaxConnections),
+            idle_timeout_secs: positive(poolIdleTimeout),
+            statement_timeout_secs: dbType !== 'sqlite' ? positive(poolStatementTimeout) : undefined,
+            connect_timeout_secs: positive(poolConnectTimeout),
+            test_before_acquire: poolTestBeforeAcquire ? undefined : false
+        };
+    };
+
     const handleTestConnection = async () => {
         setIsTesting(true);
         setTestSuccess(null);
+        setTlsStatus(null);
 
         try {
             const tempConnStr = buildConnectionString();
             // Pass credentialId separately so backend can inject password securely
-            await invoke('verify_connection', {
+            const tls = await invoke<TlsStatus | null>('verify_connection', {
                 connectionString: tempConnStr,
-                credentialId: credentialId
+                credentialId: credentialId,
+                tls: {
+                    mode: sslMode || undefined,
+                    ca_path: sslCaPath || undefined,
+                    client_cert_path: sslClientCertPath || undefined,
+                    client_key_path: sslClientKeyPath || undefined
+                },
+                ssh: buildSshSettings()
             });
+            setTlsStatus(tls);
             setTestSuccess(true);
         } catch (e) {
             setTestSuccess(false);
@@ -151,7 +249,11 @@ export const ConnectionWindow: React.FC = () => {
                     port,
                     databaseName: databaseName || null,
                     credentialId: credentialId || null,
-                    sslMode: sslMode || null
+                    ...tlsFields(),
+                    ssh: buildSshSettings(),
+                    pool: buildPoolSettings(),
+                    readOnly,
+                    environment
                 });
             } else {
                 await invoke('save_connection', {
@@ -161,7 +263,11 @@ export const ConnectionWindow: React.FC = () => {
                     port,
                     databaseName: databaseName || null,
                     credentialId: credentialId || null,
-                    sslMode: sslMode || null
+                    ...tlsFields(),
+                    ssh: buildSshSettings(),
+                    pool: buildPoolSettings(),
+                    readOnly,
+                    environment
                 });
             }
             // Close window on success
@@ -352,6 +458,281 @@ export const ConnectionWindow: React.FC = () => {
                                 Manage your usernames and passwords securely in the Vault.
                             </div>
                         </div>
+
+                        {/* TLS */}
+                        <div style={{ marginBottom: '1rem' }}>
+                            <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>TLS</label>
+                            <select
+                                value={sslMode}
+                                onChange={e => { setSslMode(e.target.value); resetStatus(); }}
+                                style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                            >
+                                <option value="">Driver default</option>
+                                <option value="disable">Disable</option>
+                                <option value="prefer">Prefer</option>
+                                <option value="require">Require</option>
+                                <option value="verify-ca">Verify CA</option>
+                                <option value="verify-full">Verify CA and host name</option>
+                            </select>
+                        </div>
+
+                        {sslMode && sslMode !== 'disable' && (
+                            <>
+                                <div style={{ marginBottom: '1rem' }}>
+                                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>CA Certificate <span style={{ color: 'var(--text-tertiary)' }}>(optional)</span></label>
+                                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                                        <input
+                                            type="text"
+                                            value={sslCaPath}
+                                            onChange={e => { setSslCaPath(e.target.value); resetStatus(); }}
+                                            placeholder="/path/to/ca.pem"
+                                            style={{ flex: 1, padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                        <button className={mainLayoutStyles.browseBtn} onClick={() => browseFile(setSslCaPath)}>
+                                            <RiFolderOpenLine size={18} />
+                                            Browse
+                                        </button>
+                                    </div>
+                                </div>
+                                <div style={{ marginBottom: '1rem' }}>
+                                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Client Certificate <span style={{ color: 'var(--text-tertiary)' }}>(optional)</span></label>
+                                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                                        <input
+                                            type="text"
+                                            value={sslClientCertPath}
+                                            onChange={e => { setSslClientCertPath(e.target.value); resetStatus(); }}
+                                            placeholder="/path/to/client-cert.pem"
+                                            style={{ flex: 1, padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                        <button className={mainLayoutStyles.browseBtn} onClick={() => browseFile(setSslClientCertPath)}>
+                                            <RiFolderOpenLine size={18} />
+                                            Browse
+                                        </button>
+                                    </div>
+                                </div>
+                                <div style={{ marginBottom: '1rem' }}>
+                                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Client Key <span style={{ color: 'var(--text-tertiary)' }}>(optional)</span></label>
+                                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                                        <input
+                                            type="text"
+                                            value={sslClientKeyPath}
+                                            onChange={e => { setSslClientKeyPath(e.target.value); resetStatus(); }}
+                                            placeholder="/path/to/client-key.pem"
+                                            style={{ flex: 1, padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                        <button className={mainLayoutStyles.browseBtn} onClick={() => browseFile(setSslClientKeyPath)}>
+                                            <RiFolderOpenLine size={18} />
+                                            Browse
+                                        </button>
+                                    </div>
+                                </div>
+                            </>
+                        )}
+
+                        {/* SSH Tunnel */}
+                        <div style={{ marginBottom: '1.5rem' }}>
+                            <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', display: 'flex', alignItems: 'center', gap: '0.5rem', cursor: 'pointer' }}>
+                                <input
+                                    type="checkbox"
+                                    checked={sshEnabled}
+                                    onChange={e => { setSshEnabled(e.target.checked); resetStatus(); }}
+                                />
+                                Connect through SSH tunnel
+                            </label>
+                        </div>
+
+                        {sshEnabled && (
+                            <>
+                                <div style={{ display: 'flex', gap: '1rem', marginBottom: '1rem' }}>
+                                    <div style={{ flex: 2 }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>SSH Host</label>
+                                        <input
+                                            type="text"
+                                            value={sshHost}
+                                            onChange={e => { setSshHost(e.target.value); resetStatus(); }}
+                                            placeholder="bastion.example.com"
+                                            style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                    </div>
+                                    <div style={{ flex: 1 }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>SSH Port</label>
+                                        <input
+                                            type="number"
+                                            value={sshPort}
+                                            onChange={e => { setSshPort(parseInt(e.target.value) || 0); resetStatus(); }}
+                                            style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                    </div>
+                                </div>
+
+                                <div style={{ display: 'flex', gap: '1rem', marginBottom: '1rem' }}>
+                                    <div style={{ flex: 1 }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>SSH User</label>
+                                        <input
+                                            type="text"
+                                            value={sshUser}
+                                            onChange={e => { setSshUser(e.target.value); resetStatus(); }}
+                                            placeholder="ubuntu"
+                                            style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        />
+                                    </div>
+                                    <div style={{ flex: 1 }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Authentication</label>
+                                        <select
+                                            value={sshAuthMethod}
+                                            onChange={e => { setSshAuthMethod(e.target.value as SshAuthMethod); resetStatus(); }}
+                                            style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        >
+                                            <option value="agent">SSH agent</option>
+                                            <option value="key">Key file</option>
+                                            <option value="password">Password</option>
+                                        </select>
+                                    </div>
+                                </div>
+
+                                {sshAuthMethod === 'key' && (
+                                    <div style={{ marginBottom: '1rem' }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Private Key File</label>
+                                        <div style={{ display: 'flex', gap: '0.5rem' }}>
+                                            <input
+                                                type="text"
+                                                value={sshKeyPath}
+                                                onChange={e => { setSshKeyPath(e.target.value); resetStatus(); }}
+                                                placeholder="~/.ssh/id_ed25519"
+                                                style={{ flex: 1, padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                            />
+                                            <button className={mainLayoutStyles.browseBtn} onClick={() => browseFile(setSshKeyPath)}>
+                                                <RiFolderOpenLine size={18} />
+                                                Browse
+                                            </button>
+                                        </div>
+                                    </div>
+                                )}
+
+                                {sshAuthMethod !== 'agent' && (
+                                    <div style={{ marginBottom: '1.5rem' }}>
+                                        <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>
+                                            {sshAuthMethod === 'key' ? 'Key Passphrase' : 'SSH Password'} <span style={{ color: 'var(--text-tertiary)' }}>{sshAuthMethod === 'key' ? '(optional)' : ''}</span>
+                                        </label>
+                                        <select
+                                            value={sshCredentialId || ''}
+                                            onChange={e => { setSshCredentialId(e.target.value || undefined); resetStatus(); }}
+                                            style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                        >
+                                            <option value="">{sshAuthMethod === 'key' ? 'No passphrase' : 'Choose a credential'}</option>
+                                            {credentials.map(cred => (
+                                                <option key={cred.id} value={cred.id}>🔑 {cred.name} ({cred.username})</option>
+                                            ))}
+                                        </select>
+                                    </div>
+                                )}
+                            </>
+                        )}
+                    </>
+                )}
+
+                {/* Environment */}
+                <div style={{ marginBottom: '1rem' }}>
+                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Environment</label>
+                    <select
+                        value={environment}
+                        onChange={e => setEnvironment(e.target.value as ConnectionEnvironment)}
+                        style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                    >
+                        <option value="development">Development</option>
+                        <option value="staging">Staging</option>
+                        <option value="production">Production</option>
+                    </select>
+                </div>
+
+                {/* Read-only */}
+                <div style={{ marginBottom: '1.5rem' }}>
+                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', display: 'flex', alignItems: 'center', gap: '0.5rem', cursor: 'pointer' }}>
+                        <input
+                            type="checkbox"
+                            checked={readOnly}
+                            onChange={e => setReadOnly(e.target.checked)}
+                        />
+                        Read-only (refuse changes to this database)
+                    </label>
+                </div>
+
+                {/* Connection Pool */}
+                <div style={{ marginBottom: '1.5rem' }}>
+                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', display: 'flex', alignItems: 'center', gap: '0.5rem', cursor: 'pointer' }}>
+                        <input
+                            type="checkbox"
+                            checked={showPoolSettings}
+                            onChange={e => setShowPoolSettings(e.target.checked)}
+                        />
+                        Connection pool settings
+                    </label>
+                </div>
+
+                {showPoolSettings && (
+                    <>
+                        <div style={{ display: 'flex', gap: '1rem', marginBottom: '1rem' }}>
+                            <div style={{ flex: 1 }}>
+                                <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Max Connections</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    value={poolMaxConnections}
+                                    onChange={e => setPoolMaxConnections(e.target.value)}
+                                    placeholder="10"
+                                    style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                />
+                            </div>
+                            <div style={{ flex: 1 }}>
+                                <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Idle Timeout (s)</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    value={poolIdleTimeout}
+                                    onChange={e => setPoolIdleTimeout(e.target.value)}
+                                    placeholder="600"
+                                    style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                />
+                            </div>
+                        </div>
+
+                        <div style={{ display: 'flex', gap: '1rem', marginBottom: '1rem' }}>
+                            <div style={{ flex: 1 }}>
+                                <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Connect Timeout (s)</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    value={poolConnectTimeout}
+                                    onChange={e => setPoolConnectTimeout(e.target.value)}
+                                    placeholder="30"
+                                    style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                />
+                            </div>
+                            {dbType !== 'sqlite' && (
+                                <div style={{ flex: 1 }}>
+                                    <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', marginBottom: '0.25rem', display: 'block' }}>Statement Timeout (s)</label>
+                                    <input
+                                        type="number"
+                                        min={0}
+                                        value={poolStatementTimeout}
+                                        onChange={e => setPoolStatementTimeout(e.target.value)}
+                                        placeholder="None"
+                                        style={{ width: '100%', padding: '0.75rem', border: '1px solid var(--border-color)', borderRadius: '6px', background: 'var(--bg-tertiary)', color: 'var(--text-primary)' }}
+                                    />
+                                </div>
+                            )}
+                        </div>
+
+                        <div style={{ marginBottom: '1.5rem' }}>
+                            <label style={{ fontSize: '0.85rem', color: 'var(--text-secondary)', display: 'flex', alignItems: 'center', gap: '0.5rem', cursor: 'pointer' }}>
+                                <input
+                                    type="checkbox"
+                                    checked={poolTestBeforeAcquire}
+                                    onChange={e => setPoolTestBeforeAcquire(e.target.checked)}
+                                />
+                                Check connections before use
+                            </label>
+                        </div>
                     </>
                 )}
             </div>
@@ -383,7 +764,7 @@ export const ConnectionWindow: React.FC = () => {
                     {isTesting ? (
                         <>Testing...</>
                     ) : testSuccess ? (
-                        <><RiCheckLine size={20} /> Connected</>
+                        <><RiCheckLine size={20} /> Connected{tlsStatus ? (tlsStatus.encrypted ? ` (${tlsStatus.version || 'TLS'})` : ' (unencrypted)') : ''}</>
                     ) : (
                         <><IoFlaskOutline size={20} /> Test Connection</>
                     )}
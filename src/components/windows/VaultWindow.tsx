@@ -1,8 +1,8 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 
-import { Credential } from '../../types';
-import { RiDeleteBin7Line, RiKey2Line, RiAddLine, RiShieldKeyholeLine, RiSearchLine } from 'react-icons/ri';
+import { Credential, SecretStoreStatus, SecretMigrationReport } from '../../types';
+import { RiDeleteBin7Line, RiKey2Line, RiAddLine, RiShieldKeyholeLine, RiSearchLine, RiLockLine, RiLockUnlockLine } from 'react-icons/ri';
 
 export const VaultWindow: React.FC = () => {
     const [credentials, setCredentials] = useState<Credential[]>([]);
@@ -15,10 +15,43 @@ export const VaultWindow: React.FC = () => {
     const [newPassword, setNewPassword] = useState('');
     const [error, setError] = useState<string | null>(null);
 
+    // Secret storage (OS keyring or encrypted vault)
+    const [storeStatus, setStoreStatus] = useState<SecretStoreStatus | null>(null);
+    const [masterPassword, setMasterPassword] = useState('');
+    const [storeMessage, setStoreMessage] = useState<string | null>(null);
+
     useEffect(() => {
         fetchCredentials();
+        fetchStoreStatus();
     }, []);
 
+    const fetchStoreStatus = async () => {
+        try {
+            setStoreStatus(await invoke<SecretStoreStatus>('get_secret_store_status'));
+        } catch (e) {
+            console.error("Failed to fetch secret store status", e);
+        }
+    };
+
+    const runStoreAction = async (command: string, needsPassword: boolean) => {
+        if (needsPassword && !masterPassword) {
+            setStoreMessage("Enter the master password");
+            return;
+        }
+        setStoreMessage(null);
+        try {
+            const report = await invoke<SecretMigrationReport | null>(command, needsPassword ? { masterPassword } : {});
+            if (report) {
+                const missing = report.missing.length > 0 ? ` No password was stored for: ${report.missing.join(', ')}.` : '';
+                setStoreMessage(`Moved ${report.migrated} password(s).${missing}`);
+            }
+            setMasterPassword('');
+        } catch (e) {
+            setStoreMessage(String(e));
+        }
+        fetchStoreStatus();
+    };
+
     const fetchCredentials = async () => {
         try {
             const creds = await invoke<Credential[]>('list_credentials');
@@ -107,6 +140,52 @@ export const VaultWindow: React.FC = () => {
 
             </div>
 
+            {/* Secret Storage */}
+            {storeStatus && (
+                <div style={{ padding: '0.75rem 1.5rem', borderBottom: '1px solid #2d3748', display: 'flex', flexDirection: 'column', gap: '0.5rem' }}>
+                    <div style={{ display: 'flex', alignItems: 'center', gap: '0.75rem', flexWrap: 'wrap' }}>
+                        {storeStatus.backend === 'vault' && !storeStatus.vault_unlocked ? <RiLockLine size={18} color="#fbbf24" /> : <RiLockUnlockLine size={18} color="#a0aec0" />}
+                        <div style={{ fontSize: '0.85rem', color: '#cbd5e0', flex: 1 }}>
+                            {storeStatus.backend === 'vault'
+                                ? `Passwords are in the encrypted vault (${storeStatus.vault_unlocked ? 'unlocked' : 'locked'})`
+                                : storeStatus.keyring_available
+                                    ? 'Passwords are in the OS keyring'
+                                    : 'The OS keyring is unavailable; create an encrypted vault to store passwords'}
+                        </div>
+                        {!(storeStatus.backend === 'vault' && storeStatus.vault_unlocked && !storeStatus.keyring_available) && (
+                            <input
+                                type="password"
+                                placeholder="Master password"
+                                value={masterPassword}
+                                onChange={(e) => setMasterPassword(e.target.value)}
+                                style={{ padding: '0.4rem 0.6rem', borderRadius: '4px', border: '1px solid #4a5568', background: '#2d3748', color: 'white', fontSize: '0.85rem' }}
+                            />
+                        )}
+                        {storeStatus.backend === 'keyring' && (
+                            <button onClick={() => runStoreAction('migrate_to_vault', true)} style={{ padding: '0.4rem 0.8rem', background: '#2b6cb0', border: 'none', color: 'white', borderRadius: '4px', cursor: 'pointer', fontSize: '0.85rem' }}>
+                                Move to Encrypted Vault
+                            </button>
+                        )}
+                        {storeStatus.backend === 'vault' && !storeStatus.vault_unlocked && (
+                            <button onClick={() => runStoreAction('unlock_vault', true)} style={{ padding: '0.4rem 0.8rem', background: '#2b6cb0', border: 'none', color: 'white', borderRadius: '4px', cursor: 'pointer', fontSize: '0.85rem' }}>
+                                Unlock
+                            </button>
+                        )}
+                        {storeStatus.backend === 'vault' && storeStatus.vault_unlocked && (
+                            <button onClick={() => runStoreAction('lock_vault', false)} style={{ padding: '0.4rem 0.8rem', background: 'transparent', border: '1px solid #4a5568', color: '#cbd5e0', borderRadius: '4px', cursor: 'pointer', fontSize: '0.85rem' }}>
+                                Lock
+                            </button>
+                        )}
+                        {storeStatus.backend === 'vault' && storeStatus.keyring_available && (
+                            <button onClick={() => runStoreAction('migrate_to_keyring', true)} style={{ padding: '0.4rem 0.8rem', background: 'transparent', border: '1px solid #4a5568', color: '#cbd5e0', borderRadius: '4px', cursor: 'pointer', fontSize: '0.85rem' }}>
+                                Move to OS Keyring
+                            </button>
+                        )}
+                    </div>
+                    {storeMessage && <div style={{ fontSize: '0.8rem', color: '#a0aec0' }}>{storeMessage}</div>}
+                </div>
+            )}
+
             {/* Main Content */}
             <div style={{ flex: 1, padding: '1.5rem 1.5rem 1.5rem 1.5rem', display: 'flex', flexDirection: 'column', gap: '1rem', overflowY: 'auto' }} className="vault-content">
 
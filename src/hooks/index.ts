@@ -7,6 +7,7 @@
 export * from './useAppNavigation';
 export * from './useAppSystem';
 export * from './useChangeManager';
+export * from './useConnectionString';
 export * from './useDatabaseRegistry';
 export * from './useDataMutation';
 export * from './usePersistenceActions';
@@ -18,4 +19,4 @@ export * from './useSystemLogs';
 export * from './useTableActions';
 export * from './useTableData';
 export * from './useTableOperations';
-export * from './useTabs';
\ No newline at end of file
+export * from './useTabs';
\ No newline at end of file
@@ -7,10 +7,10 @@
  * - Selective confirm/discard of changes
  */
 
-import { useState, useCallback, useRef, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { useState, useCallback } from 'react';
 import { Connection, PendingChange, QueryResult, Tab, BatchChange } from '../types/index';
 import * as api from '../api';
+import { useConnectionString } from './useConnectionString';
 
 export interface ChangeError {
     change: PendingChange;
@@ -69,23 +69,7 @@ export const useChangeManager = (
     const [changelogConfirm, setChangelogConfirm] = useState<{ type: 'confirm' | 'discard' } | null>(null);
     const [highlightRowIndex, setHighlightRowIndex] = useState<number | null>(null);
 
-    const connectionStringRef = useRef<string | null>(null);
-
-    // Reset connection cache when connection changes
-    useEffect(() => {
-        connectionStringRef.current = null;
-    }, [connection.id, connection.database_name]);
-
-    const getConnectionString = useCallback(async (): Promise<string> => {
-        if (options?.sessionId) return options.sessionId;
-        if (connectionStringRef.current) return connectionStringRef.current;
-        const connStr = await invoke<string>('get_connection_string', {
-            connectionId: connection.id,
-            databaseName: connection.database_name
-        });
-        connectionStringRef.current = connStr;
-        return connStr;
-    }, [connection.id, connection.database_name, options?.sessionId]);
+    const { withConnection } = useConnectionString(connection, options?.sessionId);
 
     const handleRevertChange = useCallback((tabId: string, changeIndex: number) => {
         setPendingChanges(prev => {
@@ -108,7 +92,6 @@ export const useChangeManager = (
         fetchTableData: (tabId: string, tableName: string) => Promise<void>
     ) => {
 
-        const connectionString = await getConnectionString();
         const errors: ChangeError[] = [];
         const successfulTabIds: Set<string> = new Set();
 
@@ -120,7 +103,7 @@ export const useChangeManager = (
             for (const change of schemaChanges) {
                 if (change.generatedSql) {
                     try {
-                        await api.executeQuery(connectionString, change.generatedSql);
+                        await withConnection(cs => api.executeQuery(cs, change.generatedSql!));
                         addLog(change.generatedSql, 'Success', change.tableName, undefined, 1);
                         successfulTabIds.add(tabId);
                     } catch (e) {
@@ -173,7 +156,7 @@ export const useChangeManager = (
 
             if (batchChanges.length > 0) {
                 try {
-                    const affected = await api.applyBatchChanges(connectionString, batchChanges);
+                    const affected = await withConnection(cs => api.applyBatchChanges(cs, batchChanges));
                     addLog(`Batch Applied ${affected} changes`, 'Success', batchChanges[0].table_name, undefined, Number(affected));
                     successfulTabIds.add(tabId);
                 } catch (e) {
@@ -228,7 +211,7 @@ export const useChangeManager = (
         }
 
         setChangelogConfirm(null);
-    }, [connection.id, connection.db_type, pendingChanges, options, getConnectionString]);
+    }, [connection.id, connection.db_type, pendingChanges, options, withConnection]);
 
     // Retry failed changes with FK checks disabled (MySQL only)
     const retryWithFKDisabled = useCallback(async (
@@ -241,12 +224,11 @@ export const useChangeManager = (
             return;
         }
 
-        const connectionString = await getConnectionString();
         const newErrors: ChangeError[] = [];
 
         try {
             // Disable FK checks
-            await api.executeQuery(connectionString, 'SET FOREIGN_KEY_CHECKS=0');
+            await withConnection(cs => api.executeQuery(cs, 'SET FOREIGN_KEY_CHECKS=0'));
             addLog('SET FOREIGN_KEY_CHECKS=0', 'Success', undefined, undefined, 0);
 
             for (const { change } of errors) {
@@ -254,7 +236,7 @@ export const useChangeManager = (
                 if (!query) continue;
 
                 try {
-                    await api.executeQuery(connectionString, query);
+                    await withConnection(cs => api.executeQuery(cs, query));
                     addLog(query, 'Success', change.tableName, undefined, 1);
                 } catch (e) {
                     const errorMsg = String(e);
@@ -268,7 +250,7 @@ export const useChangeManager = (
             }
 
             // Re-enable FK checks
-            await api.executeQuery(connectionString, 'SET FOREIGN_KEY_CHECKS=1');
+            await withConnection(cs => api.executeQuery(cs, 'SET FOREIGN_KEY_CHECKS=1'));
             addLog('SET FOREIGN_KEY_CHECKS=1', 'Success', undefined, undefined, 0);
 
             if (newErrors.length > 0 && options?.onErrors) {
@@ -280,11 +262,10 @@ export const useChangeManager = (
             addLog(`Error during FK bypass: ${e}`, 'Error', undefined, String(e));
             // Try to re-enable FK checks even on error
             try {
-                const connectionString = await getConnectionString();
-                await api.executeQuery(connectionString, 'SET FOREIGN_KEY_CHECKS=1');
+                await withConnection(cs => api.executeQuery(cs, 'SET FOREIGN_KEY_CHECKS=1'));
             } catch { /* ignore */ }
         }
-    }, [connection.id, connection.db_type, options, getConnectionString]);
+    }, [connection.id, connection.db_type, options, withConnection]);
 
     // Execute confirm for selected changes only
     const executeConfirmSelected = useCallback(async (
@@ -294,7 +275,6 @@ export const useChangeManager = (
         addLog: (query: string, status: 'Success' | 'Error', table?: string, error?: string, rows?: number, user?: string) => void,
         fetchTableData: (tabId: string, tableName: string) => Promise<void>
     ) => {
-        const connectionString = await getConnectionString();
         const errors: ChangeError[] = [];
         const successfulIndices: Map<string, Set<number>> = new Map();
 
@@ -309,7 +289,7 @@ export const useChangeManager = (
                 const originalIdx = indices[selectedChanges.indexOf(change)];
                 if (change.generatedSql) {
                     try {
-                        await api.executeQuery(connectionString, change.generatedSql);
+                        await withConnection(cs => api.executeQuery(cs, change.generatedSql!));
                         addLog(change.generatedSql, 'Success', change.tableName, undefined, 1);
                         if (!successfulIndices.has(tabId)) successfulIndices.set(tabId, new Set());
                         successfulIndices.get(tabId)!.add(originalIdx);
@@ -360,7 +340,7 @@ export const useChangeManager = (
                 });
 
                 try {
-                    const affected = await api.applyBatchChanges(connectionString, batchChanges);
+                    const affected = await withConnection(cs => api.applyBatchChanges(cs, batchChanges));
                     addLog(`Batch Applied ${affected} selected changes`, 'Success', batchChanges[0].table_name, undefined, Number(affected));
 
                     if (!successfulIndices.has(tabId)) successfulIndices.set(tabId, new Set());
@@ -413,7 +393,7 @@ export const useChangeManager = (
         } else if (options?.onSuccess) {
             options.onSuccess();
         }
-    }, [connection.id, connection.db_type, pendingChanges, options, getConnectionString]);
+    }, [connection.id, connection.db_type, pendingChanges, options, withConnection]);
 
     const handleDiscardChanges = useCallback(() => {
         setChangelogConfirm({ type: 'discard' });
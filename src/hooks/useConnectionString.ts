@@ -0,0 +1,54 @@
+/**
+ * Connection String Hook
+ *
+ * Resolves the session id that data operations run against. Prefers the window's
+ * session from useSession and falls back to a cached id from get_connection_string.
+ * A call that fails because its session was closed or expired in the backend is
+ * retried once on a new session.
+ */
+
+import { useCallback, useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Connection } from '../types/index';
+import { getWindowLogin } from '../utils/connectionHelper';
+
+const isSessionExpiredError = (e: unknown): boolean =>
+    String(e).includes('Session expired or invalid');
+
+export const useConnectionString = (connection: Connection, sessionId?: string | null) => {
+    const connectionStringRef = useRef<string | null>(null);
+    // A session id from useSession that the backend no longer knows
+    const expiredSessionRef = useRef<string | null>(null);
+
+    // Reset connection cache when connection changes
+    useEffect(() => {
+        connectionStringRef.current = null;
+    }, [connection.id, connection.database_name]);
+
+    const getConnectionString = useCallback(async (): Promise<string> => {
+        if (sessionId && sessionId !== expiredSessionRef.current) return sessionId;
+        if (connectionStringRef.current) return connectionStringRef.current;
+        const connStr = await invoke<string>('get_connection_string', {
+            connectionId: connection.id,
+            login: getWindowLogin(),
+            databaseName: connection.database_name
+        });
+        connectionStringRef.current = connStr;
+        return connStr;
+    }, [connection.id, connection.database_name, sessionId]);
+
+    const withConnection = useCallback(async <T>(run: (connectionString: string) => Promise<T>): Promise<T> => {
+        const connectionString = await getConnectionString();
+        try {
+            return await run(connectionString);
+        } catch (e) {
+            if (!isSessionExpiredError(e)) throw e;
+            // The backend rejected the session before running anything, so retrying is safe
+            if (connectionString === sessionId) expiredSessionRef.current = connectionString;
+            if (connectionString === connectionStringRef.current) connectionStringRef.current = null;
+            return run(await getConnectionString());
+        }
+    }, [getConnectionString, sessionId]);
+
+    return { getConnectionString, withConnection };
+};
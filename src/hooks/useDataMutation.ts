@@ -5,10 +5,10 @@
  * Converts panel data to pending changes for batch commit.
  */
 
-import { useCallback, useRef } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { useCallback } from 'react';
 import * as api from '../api';
 import { Connection, PendingChange, TabItem, TableDataState } from '../types/index';
+import { useConnectionString } from './useConnectionString';
 
 interface UseDataMutationProps {
     activeTab: TabItem | undefined;
@@ -16,6 +16,7 @@ interface UseDataMutationProps {
     selectedIndices: Set<number>;
     editData: Record<string, any>[] | undefined;
     connection: Connection;
+    sessionId?: string | null;
     setPendingChanges: React.Dispatch<React.SetStateAction<Record<string, PendingChange[]>>>;
     setShowEditWindow: (show: boolean) => void;
     setEditData: (data: Record<string, any>[] | undefined) => void;
@@ -30,6 +31,7 @@ export const useDataMutation = ({
     selectedIndices,
     editData,
     connection,
+    sessionId,
     setPendingChanges,
     setShowEditWindow,
     setEditData,
@@ -38,17 +40,7 @@ export const useDataMutation = ({
     addLog
 }: UseDataMutationProps) => {
 
-    const connectionStringRef = useRef<string | null>(null);
-
-    const getConnectionString = useCallback(async (): Promise<string> => {
-        if (connectionStringRef.current) return connectionStringRef.current;
-        const connStr = await invoke<string>('get_connection_string', { 
-            connectionId: connection.id,
-            databaseName: connection.database_name 
-        });
-        connectionStringRef.current = connStr;
-        return connStr;
-    }, [connection.id, connection.database_name]);
+    const { withConnection } = useConnectionString(connection, sessionId);
 
     const handlePanelSubmit = useCallback(async (data: Record<string, any>[]) => {
         if (!activeTab || activeTab.type !== 'table') return;
@@ -130,7 +122,6 @@ export const useDataMutation = ({
             return;
         }
 
-        const connectionString = await getConnectionString();
         const isMysql = connection.db_type === 'mysql';
         const q = isMysql ? '`' : '"';
         const tableName = activeTab.title;
@@ -152,7 +143,7 @@ export const useDataMutation = ({
         const query = `INSERT INTO ${q}${tableName}${q} (${cols}) VALUES ${valueGroups.join(', ')}`;
 
         try {
-            await api.executeQuery(connectionString, query);
+            await withConnection(cs => api.executeQuery(cs, query));
             setShowEditWindow(false);
             setEditData(undefined);
             fetchTableData(activeTab.id, activeTab.title);
@@ -161,7 +152,7 @@ export const useDataMutation = ({
             alert(`Insert failed: ${e}`);
             addLog(`Insert failed: ${e}`, 'Error', activeTab.title, String(e));
         }
-    }, [activeTab, results, selectedIndices, editData, connection.id, connection.db_type, setPendingChanges, setShowEditWindow, setEditData, setSelectedIndices, fetchTableData, addLog, getConnectionString]);
+    }, [activeTab, results, selectedIndices, editData, connection.id, connection.db_type, setPendingChanges, setShowEditWindow, setEditData, setSelectedIndices, fetchTableData, addLog, withConnection]);
 
     return {
         handlePanelSubmit
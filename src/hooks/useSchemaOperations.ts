@@ -10,6 +10,7 @@ import { invoke } from '@tauri-apps/api/core';
 import * as api from '../api';
 import { Connection, QueryResult, TabItem, TableDataState, ColumnSchema } from '../types/index';
 import { TableCreatorState } from '../components/editors/TableCreator';
+import { getWindowLogin } from '../utils/connectionHelper';
 
 interface UseSchemaOperationsProps {
     connection: Connection;
@@ -39,6 +40,7 @@ export const useSchemaOperations = ({
         if (sessionId) return sessionId;
         return await invoke<string>('get_connection_string', { 
             connectionId: connection.id,
+            login: getWindowLogin(),
             databaseName: connection.database_name 
         });
     }, [connection.id, connection.database_name, sessionId]);
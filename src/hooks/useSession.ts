@@ -3,16 +3,25 @@
  * 
  * Creates and manages database session IDs for connection pooling.
  * Sessions allow efficient connection reuse without re-authentication.
+ * The session's health is checked periodically and when the window regains focus,
+ * which reconnects it if the connections died (e.g. after the machine slept). A session
+ * that expired in the backend is opened again.
  */
 
 import { useState, useEffect, useRef } from 'react';
-import { Connection } from '../types';
+import { Connection, SessionHealth } from '../types';
 import * as api from '../api';
+import { getWindowLogin } from '../utils/connectionHelper';
+
+const HEALTH_CHECK_INTERVAL_MS = 60_000;
 
 export const useSession = (connection: Connection) => {
     const [sessionId, setSessionId] = useState<string | null>(null);
     const [isLoading, setIsLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
+    const [health, setHealth] = useState<SessionHealth | null>(null);
+    // Bumped to open a new session after the current one expired
+    const [sessionEpoch, setSessionEpoch] = useState(0);
     
     // Track the connection that the current session belongs to
     const currentConnectionRef = useRef<{ id: number; dbName?: string } | null>(null);
@@ -43,7 +52,7 @@ export const useSession = (connection: Connection) => {
 
             try {
                 const dbName = connection.database_name || undefined;
-                const sid = await api.createSession(connection.id, dbName);
+                const sid = await api.createSession(connection.id, dbName, getWindowLogin());
 
                 if (isMounted) {
                     currentConnectionRef.current = { id: connection.id, dbName: connection.database_name };
@@ -64,7 +73,37 @@ export const useSession = (connection: Connection) => {
         initSession();
 
         return () => { isMounted = false; };
-    }, [connection.id, connection.database_name]);
+    }, [connection.id, connection.database_name, sessionEpoch]);
+
+    useEffect(() => {
+        if (!sessionId) {
+            setHealth(null);
+            return;
+        }
+        let isMounted = true;
+
+        const checkHealth = async () => {
+            try {
+                const result = await api.getSessionHealth(sessionId);
+                if (isMounted) setHealth(result);
+            } catch (e) {
+                // The session itself is gone (closed or expired); open a new one
+                if (isMounted) {
+                    currentConnectionRef.current = null;
+                    setSessionEpoch(n => n + 1);
+                }
+            }
+        };
+
+        const interval = setInterval(checkHealth, HEALTH_CHECK_INTERVAL_MS);
+        window.addEventListener('focus', checkHealth);
+
+        return () => {
+            isMounted = false;
+            clearInterval(interval);
+            window.removeEventListener('focus', checkHealth);
+        };
+    }, [sessionId]);
 
-    return { sessionId, isLoading, error };
+    return { sessionId, isLoading, error, health };
 };
@@ -9,10 +9,10 @@
  * Supports both immediate execution and changelog mode.
  */
 
-import { useCallback, useRef } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { useCallback } from 'react';
 import { Connection, PendingChange, QueryResult, Tab, ColumnSchema, RowIdentifier, CellUpdate, BatchChange } from '../types/index';
 import * as api from '../api';
+import { useConnectionString } from './useConnectionString';
 
 /** Builds a row identifier using primary key columns, or all columns if no PK exists */
 const getRowIdentifier = (row: any[], columns: string[], schema: ColumnSchema[]): RowIdentifier => {
@@ -64,18 +64,7 @@ export const useTableActions = ({
     tableSchemas
 }: UseTableActionsProps) => {
 
-    const connectionStringRef = useRef<string | null>(null);
-
-    const getConnectionString = useCallback(async (): Promise<string> => {
-        if (sessionId) return sessionId;
-        if (connectionStringRef.current) return connectionStringRef.current;
-        const connStr = await invoke<string>('get_connection_string', {
-            connectionId: connection.id,
-            databaseName: connection.database_name
-        });
-        connectionStringRef.current = connStr;
-        return connStr;
-    }, [connection.id, connection.database_name, sessionId]);
+    const { withConnection } = useConnectionString(connection, sessionId);
 
     const handleInsertRow = useCallback(() => {
         if (!activeTab || activeTab.type !== 'table') return;
@@ -154,8 +143,7 @@ export const useTableActions = ({
         if (!enableChangeLog) {
             // Execute Immediately
             try {
-                const connectionString = await getConnectionString();
-                await api.updateRecord(connectionString, activeTab.title, identifier, updates);
+                await withConnection(cs => api.updateRecord(cs, activeTab.title, identifier, updates));
                 addLog(displaySql, 'Success', activeTab.title, undefined, 1);
                 await fetchTableData(activeTab.id, activeTab.title);
             } catch (e) {
@@ -203,7 +191,7 @@ export const useTableActions = ({
             }
             return { ...prev, [tabId]: newChanges };
         });
-    }, [activeTab, results, connection.id, connection.db_type, setPendingChanges, enableChangeLog, addLog, fetchTableData, getConnectionString, tableSchemas]);
+    }, [activeTab, results, connection.id, connection.db_type, setPendingChanges, enableChangeLog, addLog, fetchTableData, withConnection, tableSchemas]);
 
     const handleRowDelete = useCallback(async (rowIndex: number) => {
         if (!activeTab || activeTab.type !== 'table') return;
@@ -246,8 +234,7 @@ export const useTableActions = ({
                 // Execute Immediately
                 if (confirm(`Are you sure you want to delete this row directly from ${activeTab.title}?`)) {
                     try {
-                        const connectionString = await getConnectionString();
-                        await api.deleteRecord(connectionString, activeTab.title, identifier);
+                        await withConnection(cs => api.deleteRecord(cs, activeTab.title, identifier));
                         addLog(displaySql, 'Success', activeTab.title, undefined, 1);
                         await fetchTableData(activeTab.id, activeTab.title);
                         setSelectedIndices(prev => {
@@ -278,7 +265,7 @@ export const useTableActions = ({
                 [activeTab.id]: [...(prev[activeTab.id] || []), newChange]
             }));
         }
-    }, [activeTab, results, pendingChanges, setPendingChanges, setSelectedIndices, enableChangeLog, connection.id, connection.db_type, addLog, fetchTableData, getConnectionString, tableSchemas]);
+    }, [activeTab, results, pendingChanges, setPendingChanges, setSelectedIndices, enableChangeLog, connection.id, connection.db_type, addLog, fetchTableData, withConnection, tableSchemas]);
 
     const handleDeleteRows = useCallback(async () => {
         if (!activeTab || activeTab.type !== 'table' || !results[activeTab.id]?.data) return;
@@ -324,8 +311,7 @@ export const useTableActions = ({
         if (!enableChangeLog && batchDeletes.length > 0) {
             if (confirm(`Are you sure you want to delete ${batchDeletes.length} rows directly from ${activeTab.title}?`)) {
                 try {
-                    const connectionString = await getConnectionString();
-                    const affected = await api.applyBatchChanges(connectionString, batchDeletes);
+                    const affected = await withConnection(cs => api.applyBatchChanges(cs, batchDeletes));
                     addLog(`Batch Delete ${affected} rows`, 'Success', activeTab.title, undefined, Number(affected));
                     await fetchTableData(activeTab.id, activeTab.title);
                     setSelectedIndices(new Set());
@@ -367,7 +353,7 @@ export const useTableActions = ({
             setSelectedIndices(new Set());
         }
 
-    }, [activeTab, results, connection.id, connection.db_type, selectedIndices, pendingChanges, setPendingChanges, setSelectedIndices, enableChangeLog, addLog, fetchTableData, getConnectionString, tableSchemas]);
+    }, [activeTab, results, connection.id, connection.db_type, selectedIndices, pendingChanges, setPendingChanges, setSelectedIndices, enableChangeLog, addLog, fetchTableData, withConnection, tableSchemas]);
 
     return {
         handleInsertRow,
@@ -9,6 +9,7 @@ import { useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Connection } from '../types/index';
 import * as api from '../api';
+import { getWindowLogin } from '../utils/connectionHelper';
 
 interface UseTableOperationsProps {
     connection: Connection;
@@ -28,6 +29,7 @@ export const useTableOperations = ({ connection, onRefreshTables, onTableDropped
     const getConnectionString = useCallback(async (): Promise<string> => {
         return await invoke<string>('get_connection_string', { 
             connectionId: connection.id,
+            login: getWindowLogin(),
             databaseName: connection.database_name 
         });
     }, [connection.id, connection.database_name]);
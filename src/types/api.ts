@@ -40,6 +40,15 @@ export interface BatchChange {
     identifier?: RowIdentifier;
     updates?: CellUpdate[];
     insert_values?: Record<string, string | null>;
+    original_values?: Record<string, string | null>;
+    expected_rows?: number;
+}
+
+export interface ApplyBatchOptions {
+    check_conflicts?: boolean;
+    version_column?: string;
+    savepoints?: boolean;
+    commit_successful?: boolean;
 }
 
 export interface LogEntry {
@@ -64,5 +73,57 @@ export interface SidebarView {
     databases: string[];
 }
 
+export interface SecretStoreStatus {
+    backend: 'keyring' | 'vault';
+    vault_unlocked: boolean;
+    keyring_available: boolean;
+}
+
+export interface SessionHealth {
+    healthy: boolean;
+    reconnected: boolean; // The pool had to be rebuilt
+    latency_ms?: number;
+    connections: number;
+    idle_connections: number;
+    error?: string;
+}
+
+export interface SessionInfo {
+    id: string;
+    connection_id: number;
+    database_name?: string;
+    manual_login: boolean;
+    read_only: boolean;
+    windows: string[]; // Labels of the windows using the session
+    created_at: string;
+    idle_secs: number;
+    connections: number;
+    idle_connections: number;
+}
+
+/** A statement the environment guard wants confirmed before it runs */
+export interface FlaggedStatement {
+    index: number;
+    statement: string;
+    risk: 'unbounded_update' | 'unbounded_delete' | 'merge' | 'ddl' | 'unrecognized';
+    reason: string;
+}
+
+export interface QueryReview {
+    environment?: import('./models').ConnectionEnvironment;
+    statements: FlaggedStatement[];
+    acknowledgement?: string; // Pass to execute_query to run the flagged statements once
+}
+
+export interface EnvironmentGuard {
+    environment: import('./models').ConnectionEnvironment;
+    confirm_destructive: boolean;
+}
+
+export interface SecretMigrationReport {
+    migrated: number;
+    missing: string[]; // Credentials that had no password to move
+}
+
 // Alias for compatibility
 export type SystemLog = LogEntry;
@@ -15,6 +15,53 @@ export interface Connection {
     database_name?: string;
     credential_id?: string;
     ssl_mode?: string;
+    ssl_ca_path?: string;
+    ssl_client_cert_path?: string;
+    ssl_client_key_path?: string;
+    ssh_host?: string;
+    ssh_port?: number;
+    ssh_user?: string;
+    ssh_auth_method?: SshAuthMethod;
+    ssh_key_path?: string;
+    ssh_credential_id?: string;
+    pool_max_connections?: number;
+    pool_idle_timeout_secs?: number;
+    pool_statement_timeout_secs?: number;
+    pool_connect_timeout_secs?: number;
+    pool_test_before_acquire?: boolean;
+    read_only?: boolean;
+    environment?: ConnectionEnvironment;
+}
+
+/** Which guard applies to a connection's destructive statements (see environment guards) */
+export type ConnectionEnvironment = 'development' | 'staging' | 'production';
+
+/** Connection pool settings as sent to save/update connection; unset fields use the defaults */
+export interface PoolSettings {
+    max_connections?: number;
+    idle_timeout_secs?: number;
+    statement_timeout_secs?: number;
+    connect_timeout_secs?: number;
+    test_before_acquire?: boolean;
+}
+
+/** TLS state negotiated with the server, as reported by verify_connection */
+export interface TlsStatus {
+    encrypted: boolean;
+    version?: string;
+    cipher?: string;
+}
+
+export type SshAuthMethod = 'agent' | 'key' | 'password';
+
+/** SSH tunnel settings as sent to save/update/verify connection */
+export interface SshSettings {
+    host: string;
+    port: number;
+    user: string;
+    auth_method: SshAuthMethod;
+    key_path?: string;
+    credential_id?: string;
 }
 
 export interface Credential {
@@ -54,6 +101,8 @@ export interface SavedFunction {
 export interface ForeignKey {
     referenced_table: string;
     referenced_column: string;
+    on_delete?: string | null;
+    on_update?: string | null;
 }
 
 export interface ColumnSchema {
@@ -1,20 +1,33 @@
 import { invoke } from '@tauri-apps/api/core';
 import { Connection } from '../types';
 
+const WINDOW_LOGIN_KEY = 'window_login';
+
 /**
- * Get the connection string for a connection by ID.
- * This fetches credentials from the keyring and builds the full connection string.
+ * Remember the session that holds this window's manually entered login.
+ * Kept in sessionStorage so it survives a reload of the window.
  */
-export async function getConnectionString(connectionId: number, databaseName?: string): Promise<string> {
-    const params = new URLSearchParams(window.location.search);
-    const u = params.get('u');
-    const p = params.get('p');
+export function setWindowLogin(sessionId: string): void {
+    sessionStorage.setItem(WINDOW_LOGIN_KEY, sessionId);
+}
+
+/**
+ * The session holding this window's manual login, if it was opened with one.
+ * The backend reuses that login for new sessions; credentials never reach the frontend.
+ */
+export function getWindowLogin(): string | undefined {
+    return sessionStorage.getItem(WINDOW_LOGIN_KEY) ?? undefined;
+}
 
+/**
+ * Get a session id for a connection by ID.
+ * Credentials are resolved in the backend; only the opaque session id is returned.
+ */
+export async function getConnectionString(connectionId: number, databaseName?: string): Promise<string> {
     return await invoke<string>('get_connection_string', {
         connectionId,
         databaseName,
-        username: u,
-        password: p
+        login: getWindowLogin()
     });
 }
 